@@ -0,0 +1,74 @@
+extern crate delinquent_food;
+extern crate cgmath;
+extern crate rand;
+
+use delinquent_food::modules::simulation::Simulation;
+use delinquent_food::modules::map::{Map, DEFAULT_WAVE_SET};
+use delinquent_food::modules::wave_file::WaveFile;
+use delinquent_food::modules::appliances::traits::ApplianceKind;
+use delinquent_food::modules::hexagon::Hexagon;
+use delinquent_food::modules::command::{GameCommand, CommandError};
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use cgmath::Vector2;
+
+// Plays random maps to completion without opening a window, buying a
+// dishwasher beside the path whenever there is money for one.
+//
+//   cargo run --release --no-default-features --example headless -- 100
+//   cargo run --release --no-default-features --example headless -- 10 endless
+fn main() {
+  let games = std::env::args().nth(1).and_then(|n| n.parse::<u32>().ok()).unwrap_or(10);
+  let endless = std::env::args().nth(2).map(|mode| mode == "endless").unwrap_or(false);
+  
+  let waves = WaveFile::load_set(DEFAULT_WAVE_SET).expect("Unable to load the default waves");
+  
+  for game in 0..games {
+    let seed = game as u64;
+    let map = Map::new_random_map(5, &mut StdRng::seed_from_u64(seed));
+    let mut simulation = Simulation::new(map, waves.clone(), seed);
+    if endless {
+      simulation.set_endless();
+    }
+    simulation.skip_map_animation();
+    
+    let mut spots = Vec::new();
+    for idx in simulation.map.get_routes()[0].clone() {
+      let qr = simulation.map.get_qr_from_index(idx as usize);
+      for hex in Hexagon::all_neighbours(&Hexagon::new(qr.x, qr.y, "".to_string())) {
+        if let Some(neighbour) = simulation.map.get_hex_from_qr(hex.q(), hex.r()) {
+          let spot = Vector2::new(hex.q(), hex.r());
+          if neighbour.is_open() && !spots.contains(&spot) {
+            spots.push(spot);
+          }
+        }
+      }
+    }
+    spots.reverse();
+    
+    let mut selected_appliance = None;
+    let mut model_sizes = Vec::new();
+    let mut ticks: u64 = 0;
+    
+    while !simulation.is_finished() {
+      if let Some(qr) = spots.last() {
+        let place = GameCommand::Place { kind: ApplianceKind::Dishwasher, q: qr.x, r: qr.y };
+        match simulation.apply_command(&place, &mut model_sizes) {
+          Err(CommandError::NotEnoughMoney { .. }) => {},
+          _ => {
+            spots.pop();
+          },
+        }
+      }
+      
+      simulation.step(&mut selected_appliance, &mut model_sizes);
+      ticks += 1;
+    }
+    
+    println!("Game {}: {:?} on wave {}, ${}, bin {}% full, {} cooked, {} binned, {} appliances left after {} ticks",
+             game+1, simulation.game_over().unwrap(), simulation.the_food_store.wave_number()+1, simulation.money,
+             simulation.bin, simulation.stats.foods_cooked, simulation.stats.foods_binned, simulation.appliances.len(), ticks);
+  }
+}
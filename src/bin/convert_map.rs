@@ -0,0 +1,45 @@
+extern crate delinquent_food;
+
+use delinquent_food::modules::map::Map;
+
+use std::env;
+use std::fs;
+
+const MAP_DIRECTORY: &str = "./resources/Maps/";
+
+// Converts .ini grid maps in resources/Maps into the .ron map format.
+// cargo run --bin convert_map -- EasyMap.ini HardMap.ini
+// With no arguments every .ini map in the folder is converted.
+fn main() {
+  let mut map_names: Vec<String> = env::args().skip(1).collect();
+  
+  if map_names.len() == 0 {
+    if let Ok(entries) = fs::read_dir(MAP_DIRECTORY) {
+      for entry in entries {
+        if let Ok(entry) = entry {
+          let file_name = entry.file_name().to_string_lossy().to_string();
+          if file_name.ends_with(".ini") {
+            map_names.push(file_name);
+          }
+        }
+      }
+    }
+    map_names.sort();
+  }
+  
+  for map_name in map_names {
+    let map = match Map::new(map_name.to_string()) {
+      Ok(map) => map,
+      Err(e) => {
+        println!("Skipping {}: {}", map_name, e);
+        continue;
+      },
+    };
+    
+    let out_name = map_name.trim_end_matches(".ini").to_owned() + ".ron";
+    match map.to_map_file().save(&(MAP_DIRECTORY.to_owned() + &out_name)) {
+      Ok(_) => println!("Converted {} to {}", map_name, out_name),
+      Err(e) => println!("Unable to write {}: {}", out_name, e),
+    }
+  }
+}
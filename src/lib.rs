@@ -0,0 +1,12 @@
+#[cfg(feature = "graphics")]
+extern crate winit;
+#[cfg(feature = "graphics")]
+extern crate maat_graphics;
+#[cfg(feature = "graphics")]
+extern crate maat_input_handler;
+extern crate cgmath;
+extern crate rand;
+extern crate serde;
+extern crate ron;
+
+pub mod modules;
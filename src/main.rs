@@ -1,13 +1,12 @@
 extern crate winit;
 extern crate maat_graphics;
-extern crate maat_input_handler;
 extern crate cgmath;
-extern crate rand;
+extern crate delinquent_food;
 
-mod modules;
-
-use crate::modules::scenes::Scene;
-use crate::modules::scenes::LoadScreen;
+use delinquent_food::modules::scenes::Scene;
+use delinquent_food::modules::scenes::LoadScreen;
+use delinquent_food::modules::settings::Settings;
+use delinquent_food::modules::system_interface::OptionsUi;
 
 use maat_graphics::graphics::CoreRender;
 use maat_graphics::CoreMaat;
@@ -66,7 +65,11 @@ fn fps_overlay(draw_calls: &mut Vec<DrawCall>, dimensions: [f32; 2], fps: f64, m
 }
 
 fn main() {
-  let mut graphics = CoreMaat::new("Delinquent Food".to_string(), (MAJOR) << 22 | (MINOR) << 12 | (PATCH), 1280.0, 720.0, true);
+  // Has to be read before the window exists so it opens at the right size
+  let settings = Settings::load();
+  
+  let mut graphics = CoreMaat::new("Delinquent Food".to_string(), (MAJOR) << 22 | (MINOR) << 12 | (PATCH), 
+                                   settings.resolution.x as f32, settings.resolution.y as f32, true);
   
   graphics.preload_font(String::from("Arial"),
                         String::from("./resources/Fonts/TimesNewRoman.png"),
@@ -98,6 +101,7 @@ fn main() {
   graphics.add_model("MeatTenderizer".to_string(), "./windys-modeling-agency/Unfinished/MeatTenderizer.glb".to_string());
   graphics.add_model("CoffeeMachine".to_string(), "./windys-modeling-agency/Unfinished/StandInModels/CoffeeMachine.glb".to_string());
     graphics.add_model("SaltGrinder".to_string(), "./windys-modeling-agency/Unfinished/StandInModels/SaltGrinder.glb".to_string());
+  graphics.add_model("CutleryDrawer".to_string(), "./windys-modeling-agency/Unfinished/Bombard.glb".to_string());
   
   // Weapons
   graphics.add_model("Spoon".to_string(), "./windys-modeling-agency/Unfinished/Spoon.glb".to_string());
@@ -124,6 +128,7 @@ fn main() {
   graphics.create_model_instance_buffer("MeatTenderizer".to_string());
   graphics.create_model_instance_buffer("CoffeeMachine".to_string());
   graphics.create_model_instance_buffer("SaltGrinder".to_string());
+  graphics.create_model_instance_buffer("CutleryDrawer".to_string());
   
   graphics.create_model_instance_buffer("Spoon".to_string());
   graphics.create_model_instance_buffer("Plate".to_string());
@@ -144,6 +149,9 @@ fn main() {
   
   let mut draw_calls: Vec<DrawCall> = Vec::with_capacity(100);
   
+  // The rest of the settings go out with the first frame
+  OptionsUi::apply(&settings, &mut draw_calls);
+  
   let mut delta_time;
   let mut last_time = time::Instant::now();
   let mut update_time = time::Instant::now();
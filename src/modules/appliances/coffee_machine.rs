@@ -1,14 +1,15 @@
-use maat_graphics::DrawCall;
-
 use crate::modules::food::Food;
-use crate::modules::appliances::traits::{Appliance, ApplianceData, Buff, TargetPriority};
+use crate::modules::appliances::traits::{Appliance, ApplianceData, SavedAppliance, Buff, TargetPriority, UpgradePath, UpgradeEffect};
 use crate::modules::weapons::{Weapon, Dish};
 use crate::modules::map::Map;
+use crate::modules::food_index::FoodIndex;
 use crate::modules::hexagon::Hexagon;
 
+use serde::{Serialize, Deserialize};
+
 use cgmath::{InnerSpace, Deg, Angle, Vector2, Vector3};
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct CoffeeMachine {
   data: ApplianceData,
 }
@@ -37,7 +38,11 @@ impl Appliance for CoffeeMachine {
     &mut self.data
   }
   
-  fn update(&mut self, foods: &mut Vec<Box<Food>>, weapons: &mut Vec<Box<Weapon>>, model_sizes: &mut Vec<(String, Vector3<f32>)>, _map: &Map, delta_time: f32) -> Vec<(Buff, Vector2<i32>, u32)> {
+  fn save(&self) -> SavedAppliance {
+    SavedAppliance::CoffeeMachine(self.clone())
+  }
+  
+  fn update(&mut self, foods: &mut Vec<Box<Food>>, _food_index: &FoodIndex, weapons: &mut Vec<Box<Weapon>>, model_sizes: &mut Vec<(String, Vector3<f32>)>, _map: &Map, delta_time: f32) -> Vec<(Buff, Vector2<i32>, u32)> {
     self.data.offset.y = 0.0;
     for (reference, size) in model_sizes {
       if *reference == "Hexagon".to_string() {
@@ -47,18 +52,32 @@ impl Appliance for CoffeeMachine {
     
     self.data.charge += delta_time;
     
-    vec!((Buff::AttackSpeed, self.get_qr_location(), self.get_range()),
-         (Buff::LifeExpectancy, self.get_qr_location(), self.get_range()),
-         (Buff::SellPrice, self.get_qr_location(), self.get_range()),
-         (Buff::Range, self.get_qr_location(), self.get_range()))
+    let mut buffs = vec!((Buff::AttackSpeed, self.get_qr_location(), self.get_range()),
+                         (Buff::LifeExpectancy, self.get_qr_location(), self.get_range()),
+                         (Buff::SellPrice, self.get_qr_location(), self.get_range()),
+                         (Buff::Range, self.get_qr_location(), self.get_range()));
+    for buff in &self.data.upgrade_buffs {
+      buffs.push((*buff, self.get_qr_location(), self.get_range()));
+    }
+    
+    buffs
   }
   
   fn fire(&mut self) {
     
   }
   
-  fn upgrade(&mut self) {
-    
+  fn upgrade_paths(&self) -> Vec<UpgradePath> {
+    vec!(
+      UpgradePath::new("Bigger Pot", vec!(
+        ("Buff radius +1", 90, UpgradeEffect::Range(1)),
+        ("Buff radius +1", 160, UpgradeEffect::Range(1)),
+      )),
+      UpgradePath::new("Strong Brew", vec!(
+        ("Buffs damage", 100, UpgradeEffect::Buff(Buff::AttackDamage)),
+        ("Buffs pierce", 150, UpgradeEffect::Buff(Buff::Pierce)),
+      )),
+    )
   }
   
   fn apply_effect(&self) {
@@ -69,10 +88,6 @@ impl Appliance for CoffeeMachine {
     
   }
   
-  fn upgrade_cost(&self) -> i32 {
-    1
-  }
-  
   fn sell(&self) -> i32 {
     1
   }
@@ -0,0 +1,108 @@
+use crate::modules::food::Food;
+use crate::modules::appliances::traits::{Appliance, ApplianceData, SavedAppliance, Buff, UpgradePath, UpgradeEffect};
+use crate::modules::weapons::{Weapon, Spoon};
+use crate::modules::map::Map;
+use crate::modules::food_index::FoodIndex;
+
+use serde::{Serialize, Deserialize};
+
+use cgmath::{InnerSpace, Vector2, Vector3};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CutleryDrawer {
+  data: ApplianceData,
+}
+
+impl CutleryDrawer {
+  pub fn new(tile: Vector2<i32>, size: Vector3<f32>, rotation: Vector3<f32>, map: &Map) -> CutleryDrawer {
+    let position = map.get_tile_position(tile.x as i32, tile.y as i32);
+    let life_expectancy = 3;
+    let range = 3;
+    let cost = 100;
+    let fire_rate = 0.7;
+    let directional_range = false;
+    
+    CutleryDrawer {
+      data: ApplianceData::new(tile, size, rotation, "CutleryDrawer".to_string(), life_expectancy, range, fire_rate, cost, directional_range, map),
+    }
+  }
+}
+
+impl Appliance for CutleryDrawer {
+  fn data(&self) -> &ApplianceData {
+    &self.data
+  }
+  
+  fn mut_data(&mut self) -> &mut ApplianceData {
+    &mut self.data
+  }
+  
+  fn save(&self) -> SavedAppliance {
+    SavedAppliance::CutleryDrawer(self.clone())
+  }
+  
+  fn update(&mut self, foods: &mut Vec<Box<Food>>, food_index: &FoodIndex, weapons: &mut Vec<Box<Weapon>>, model_sizes: &mut Vec<(String, Vector3<f32>)>, map: &Map, delta_time: f32) -> Vec<(Buff, Vector2<i32>, u32)> {
+    self.data.offset.y = 0.0;
+    for (reference, size) in model_sizes {
+      if *reference == "Hexagon".to_string() {
+        self.data.offset.y += size.y;
+      }
+      if *reference == "CutleryDrawer".to_string() {
+        self.data.offset.y += size.y*0.5;
+      }
+    }
+    
+    let some_food = self.get_prioritised_food(foods, food_index);
+    if let Some(food) = some_food {
+      self.data.rotation.y = self.rotate_towards(self.data.position, food.location, 90.0);
+      
+      if self.data.charge >= self.get_fire_rate() {
+        let loc = food.location;
+        let direction = Vector2::new(loc.x-self.data.position.x, loc.y-self.data.position.z).normalize();
+        
+        // spoons chase the food they were thrown at instead of flying straight
+        let mut weapon: Box<Weapon> = Box::new(Spoon::new());
+        self.add_weapon_modifiers(&mut weapon);
+        weapon.lock_on(&food);
+        weapon.launch(self.data.position+self.data.offset, self.data.tile_location, self.data.rotation, direction);
+        
+        weapons.push(weapon);
+        
+        self.data.charge = 0.0;
+      }
+    }
+    
+    self.data.charge += delta_time;
+    
+    Vec::new()
+  }
+  
+  fn fire(&mut self) {
+    
+  }
+  
+  fn upgrade_paths(&self) -> Vec<UpgradePath> {
+    vec!(
+      UpgradePath::new("Sharp Spoons", vec!(
+        ("Damage +4", 60, UpgradeEffect::Damage(4)),
+        ("Damage +8", 130, UpgradeEffect::Damage(8)),
+      )),
+      UpgradePath::new("Deep Drawer", vec!(
+        ("Range +1", 70, UpgradeEffect::Range(1)),
+        ("Fire rate +20%", 120, UpgradeEffect::FireRate(0.8)),
+      )),
+    )
+  }
+  
+  fn apply_effect(&self) {
+    
+  }
+  
+  fn remove_effects(&self) {
+    
+  }
+  
+  fn sell(&self) -> i32 {
+    1
+  }
+}
@@ -1,14 +1,15 @@
-use maat_graphics::DrawCall;
-
 use crate::modules::food::Food;
-use crate::modules::appliances::traits::{Appliance, ApplianceData, Buff, TargetPriority};
+use crate::modules::appliances::traits::{Appliance, ApplianceData, SavedAppliance, Buff, TargetPriority, UpgradePath, UpgradeEffect};
 use crate::modules::weapons::{Weapon, Dish};
 use crate::modules::map::Map;
+use crate::modules::food_index::FoodIndex;
 use crate::modules::hexagon::Hexagon;
 
+use serde::{Serialize, Deserialize};
+
 use cgmath::{InnerSpace, Deg, Angle, Vector2, Vector3};
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Dishwasher {
   data: ApplianceData,
 }
@@ -37,7 +38,11 @@ impl Appliance for Dishwasher {
     &mut self.data
   }
   
-  fn update(&mut self, foods: &mut Vec<Box<Food>>, weapons: &mut Vec<Box<Weapon>>, model_sizes: &mut Vec<(String, Vector3<f32>)>, map: &Map, delta_time: f32) -> Vec<(Buff, Vector2<i32>, u32)> {
+  fn save(&self) -> SavedAppliance {
+    SavedAppliance::Dishwasher(self.clone())
+  }
+  
+  fn update(&mut self, foods: &mut Vec<Box<Food>>, food_index: &FoodIndex, weapons: &mut Vec<Box<Weapon>>, model_sizes: &mut Vec<(String, Vector3<f32>)>, map: &Map, delta_time: f32) -> Vec<(Buff, Vector2<i32>, u32)> {
     self.data.offset.y = 0.0;
     for (reference, size) in model_sizes {
       if *reference == "Hexagon".to_string() {
@@ -48,12 +53,12 @@ impl Appliance for Dishwasher {
       }
     }
     
-    let some_food = self.get_prioritised_food(foods, map);
+    let some_food = self.get_prioritised_food(foods, food_index);
     if let Some(food) = some_food {
-      self.data.rotation.y = self.rotate_towards(self.data.position, &food, 90.0);
+      self.data.rotation.y = self.rotate_towards(self.data.position, food.location, 90.0);
       
       if self.data.charge >= self.get_fire_rate() {
-        let loc = food.get_tile_location();
+        let loc = food.tile_location;
         let loc = map.get_tile_position(loc.x, loc.y);
         let direction = Vector2::new(loc.x-self.data.position.x, loc.y-self.data.position.z).normalize();
         
@@ -76,8 +81,18 @@ impl Appliance for Dishwasher {
     
   }
   
-  fn upgrade(&mut self) {
-    
+  fn upgrade_paths(&self) -> Vec<UpgradePath> {
+    vec!(
+      UpgradePath::new("Long Cycle", vec!(
+        ("Range +1", 50, UpgradeEffect::Range(1)),
+        ("Range +1", 90, UpgradeEffect::Range(1)),
+      )),
+      UpgradePath::new("Heavy Plates", vec!(
+        ("Damage +3", 40, UpgradeEffect::Damage(3)),
+        ("Pierce +2", 70, UpgradeEffect::Pierce(2)),
+        ("Damage +5", 120, UpgradeEffect::Damage(5)),
+      )),
+    )
   }
   
   fn apply_effect(&self) {
@@ -88,10 +103,6 @@ impl Appliance for Dishwasher {
     
   }
   
-  fn upgrade_cost(&self) -> i32 {
-    1
-  }
-  
   fn sell(&self) -> i32 {
     1
   }
@@ -1,14 +1,15 @@
-use maat_graphics::DrawCall;
-
 use crate::modules::food::Food;
-use crate::modules::appliances::traits::{Appliance, ApplianceData, Buff, TargetPriority};
-use crate::modules::weapons::{Weapon, ColdSnap};
+use crate::modules::appliances::traits::{Appliance, ApplianceData, SavedAppliance, Buff, TargetPriority, UpgradePath, UpgradeEffect};
+use crate::modules::weapons::{Weapon, ColdSnap, Debuff};
 use crate::modules::map::Map;
+use crate::modules::food_index::FoodIndex;
 use crate::modules::hexagon::Hexagon;
 
+use serde::{Serialize, Deserialize};
+
 use cgmath::{InnerSpace, Deg, Angle, Vector2, Vector3};
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Fridge {
   data: ApplianceData,
 }
@@ -37,7 +38,11 @@ impl Appliance for Fridge {
     &mut self.data
   }
   
-  fn update(&mut self, foods: &mut Vec<Box<Food>>, weapons: &mut Vec<Box<Weapon>>, model_sizes: &mut Vec<(String, Vector3<f32>)>, map: &Map, delta_time: f32) -> Vec<(Buff, Vector2<i32>, u32)> {
+  fn save(&self) -> SavedAppliance {
+    SavedAppliance::Fridge(self.clone())
+  }
+  
+  fn update(&mut self, foods: &mut Vec<Box<Food>>, food_index: &FoodIndex, weapons: &mut Vec<Box<Weapon>>, model_sizes: &mut Vec<(String, Vector3<f32>)>, map: &Map, delta_time: f32) -> Vec<(Buff, Vector2<i32>, u32)> {
     self.data.offset.y = 0.0;
     for (reference, size) in model_sizes {
       if *reference == "Hexagon".to_string() {
@@ -45,34 +50,29 @@ impl Appliance for Fridge {
       }
     }
     
-    for food in foods.iter() {
-      let location = food.get_tile_location();
-      let dist = Hexagon::hex_distance(&Hexagon::new(self.data.tile_location.x, self.data.tile_location.y, "".to_string()), &Hexagon::new(location.x, location.y, "".to_string()));
+    // Aims at the first food in range
+    if let Some((idx, _)) = food_index.foods_in_range(self.data.tile_location, self.get_range() as i32, false).min_by_key(|&(idx, _)| idx) {
+      self.data.rotation.y = self.rotate_towards(self.data.position, foods[idx].get_location(), 90.0);
       
-      if dist <= self.get_range() as i32 {
-        self.data.rotation.y = self.rotate_towards(self.data.position, food, 90.0);
+      if self.data.charge >= self.get_fire_rate() {
+        let qr = self.get_qr_location();
+        let hex = Hexagon::new(qr.x, qr.y, "".to_string());
         
-        if self.data.charge >= self.get_fire_rate() {
-          let qr = self.get_qr_location();
-          let hex = Hexagon::new(qr.x, qr.y, "".to_string());
-          
-          let radius = self.get_range() as i32;
-          let hexagons = Hexagon::generate_hexagon_range(radius, "".to_string());
-          
-          for hexagon in hexagons {
-            let new_hex = Hexagon::hex_add(&hex, &hexagon);
-            let mut weapon: Box<Weapon> = Box::new(ColdSnap::new());
-            self.add_weapon_modifiers(&mut weapon);
-            let pos = map.get_tile_position(new_hex.q(), new_hex.r());
-            let position = Vector3::new(pos.x, self.data.position.y, pos.y);
-            weapon.launch(position, Vector2::new(new_hex.q(), new_hex.r()), Vector3::new(0.0, 90.0, 0.0), Vector2::new(0.0, 0.0));
-            
-            weapons.push(weapon);
-          }
+        let radius = self.get_range() as i32;
+        let hexagons = Hexagon::generate_hexagon_range(radius, "".to_string());
+        
+        for hexagon in hexagons {
+          let new_hex = Hexagon::hex_add(&hex, &hexagon);
+          let mut weapon: Box<Weapon> = Box::new(ColdSnap::new());
+          self.add_weapon_modifiers(&mut weapon);
+          let pos = map.get_tile_position(new_hex.q(), new_hex.r());
+          let position = Vector3::new(pos.x, self.data.position.y, pos.y);
+          weapon.launch(position, Vector2::new(new_hex.q(), new_hex.r()), Vector3::new(0.0, 90.0, 0.0), Vector2::new(0.0, 0.0));
           
-          self.data.charge = 0.0;
+          weapons.push(weapon);
         }
-        break;
+        
+        self.data.charge = 0.0;
       }
     }
     
@@ -85,8 +85,18 @@ impl Appliance for Fridge {
     
   }
   
-  fn upgrade(&mut self) {
-    
+  fn upgrade_paths(&self) -> Vec<UpgradePath> {
+    vec!(
+      UpgradePath::new("Deep Freeze", vec!(
+        ("Range +1", 80, UpgradeEffect::Range(1)),
+        ("Range +1", 140, UpgradeEffect::Range(1)),
+        ("Flash freeze", 220, UpgradeEffect::Debuff(Debuff::Freeze(0.8))),
+      )),
+      UpgradePath::new("Quick Chill", vec!(
+        ("Fire rate +15%", 60, UpgradeEffect::FireRate(0.85)),
+        ("Fire rate +20%", 110, UpgradeEffect::FireRate(0.8)),
+      )),
+    )
   }
   
   fn apply_effect(&self) {
@@ -97,10 +107,6 @@ impl Appliance for Fridge {
     
   }
   
-  fn upgrade_cost(&self) -> i32 {
-    1
-  }
-  
   fn sell(&self) -> i32 {
     1
   }
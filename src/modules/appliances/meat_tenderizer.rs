@@ -1,14 +1,15 @@
-use maat_graphics::DrawCall;
-
 use crate::modules::food::Food;
-use crate::modules::appliances::traits::{Appliance, ApplianceData, Buff, TargetPriority};
-use crate::modules::weapons::{Weapon, Tenderizer};
+use crate::modules::appliances::traits::{Appliance, ApplianceData, SavedAppliance, Buff, TargetPriority, UpgradePath, UpgradeEffect};
+use crate::modules::weapons::{Weapon, Tenderizer, Debuff};
 use crate::modules::map::Map;
+use crate::modules::food_index::FoodIndex;
 use crate::modules::hexagon::Hexagon;
 
+use serde::{Serialize, Deserialize};
+
 use cgmath::{InnerSpace, Deg, Angle, Vector2, Vector3};
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct MeatTenderizer {
   data: ApplianceData,
 }
@@ -37,7 +38,11 @@ impl Appliance for MeatTenderizer {
     &mut self.data
   }
   
-  fn update(&mut self, foods: &mut Vec<Box<Food>>, weapons: &mut Vec<Box<Weapon>>, model_sizes: &mut Vec<(String, Vector3<f32>)>, map: &Map, delta_time: f32) -> Vec<(Buff, Vector2<i32>, u32)> {
+  fn save(&self) -> SavedAppliance {
+    SavedAppliance::MeatTenderizer(self.clone())
+  }
+  
+  fn update(&mut self, foods: &mut Vec<Box<Food>>, food_index: &FoodIndex, weapons: &mut Vec<Box<Weapon>>, model_sizes: &mut Vec<(String, Vector3<f32>)>, map: &Map, delta_time: f32) -> Vec<(Buff, Vector2<i32>, u32)> {
     self.data.offset.y = 0.0;
     for (reference, size) in model_sizes {
       if *reference == "Hexagon".to_string() {
@@ -45,13 +50,13 @@ impl Appliance for MeatTenderizer {
       }
     }
     
-    let some_food = self.get_prioritised_food(foods, map);
+    let some_food = self.get_prioritised_food(foods, food_index);
     if let Some(food) = some_food {
-      self.data.rotation.y = self.rotate_towards(self.data.position, &food, 90.0);
+      self.data.rotation.y = self.rotate_towards(self.data.position, food.location, 90.0);
       
       if self.data.charge >= self.get_fire_rate() {
-        let loc = food.get_location();
-        let location = food.get_tile_location();
+        let loc = food.location;
+        let location = food.tile_location;
         let qr = self.get_qr_location();
         let hex = Hexagon::new(qr.x, qr.y, "".to_string());
         let some_hex_direction = Hexagon::get_hex_direction(&hex, &Hexagon::new(location.x, location.y, "".to_string()));
@@ -85,8 +90,18 @@ impl Appliance for MeatTenderizer {
     
   }
   
-  fn upgrade(&mut self) {
-    
+  fn upgrade_paths(&self) -> Vec<UpgradePath> {
+    vec!(
+      UpgradePath::new("Long Handle", vec!(
+        ("Range +1", 70, UpgradeEffect::Range(1)),
+        ("Range +1", 120, UpgradeEffect::Range(1)),
+      )),
+      UpgradePath::new("Heavy Hammer", vec!(
+        ("Damage +6", 60, UpgradeEffect::Damage(6)),
+        ("Damage +10", 130, UpgradeEffect::Damage(10)),
+        ("Knockback", 200, UpgradeEffect::Debuff(Debuff::Reverse(0.6))),
+      )),
+    )
   }
   
   fn apply_effect(&self) {
@@ -97,10 +112,6 @@ impl Appliance for MeatTenderizer {
     
   }
   
-  fn upgrade_cost(&self) -> i32 {
-    1
-  }
-  
   fn sell(&self) -> i32 {
     1
   }
@@ -3,6 +3,7 @@ pub use self::dishwasher::Dishwasher;
 pub use self::meat_tenderizer::MeatTenderizer;
 pub use self::coffee_machine::CoffeeMachine;
 pub use self::salt_grinder::SaltGrinder;
+pub use self::cutlery_drawer::CutleryDrawer;
 
 pub mod traits;
 mod fridge;
@@ -10,3 +11,4 @@ mod dishwasher;
 mod meat_tenderizer;
 mod coffee_machine;
 mod salt_grinder;
+mod cutlery_drawer;
@@ -1,14 +1,15 @@
-use maat_graphics::DrawCall;
-
 use crate::modules::food::Food;
-use crate::modules::appliances::traits::{Appliance, ApplianceData, Buff, TargetPriority};
+use crate::modules::appliances::traits::{Appliance, ApplianceData, SavedAppliance, Buff, TargetPriority, UpgradePath, UpgradeEffect};
 use crate::modules::weapons::{Weapon, Salt};
 use crate::modules::map::Map;
+use crate::modules::food_index::FoodIndex;
 use crate::modules::hexagon::Hexagon;
 
+use serde::{Serialize, Deserialize};
+
 use cgmath::{InnerSpace, Deg, Angle, Vector2, Vector3};
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SaltGrinder {
   data: ApplianceData,
 }
@@ -37,7 +38,11 @@ impl Appliance for SaltGrinder {
     &mut self.data
   }
   
-  fn update(&mut self, foods: &mut Vec<Box<Food>>, weapons: &mut Vec<Box<Weapon>>, model_sizes: &mut Vec<(String, Vector3<f32>)>, map: &Map, delta_time: f32) -> Vec<(Buff, Vector2<i32>, u32)> {
+  fn save(&self) -> SavedAppliance {
+    SavedAppliance::SaltGrinder(self.clone())
+  }
+  
+  fn update(&mut self, foods: &mut Vec<Box<Food>>, food_index: &FoodIndex, weapons: &mut Vec<Box<Weapon>>, model_sizes: &mut Vec<(String, Vector3<f32>)>, map: &Map, delta_time: f32) -> Vec<(Buff, Vector2<i32>, u32)> {
     self.data.offset.y = 0.0;
    // self.data.offset.z = 0.0;
     for (reference, size) in model_sizes {
@@ -49,12 +54,12 @@ impl Appliance for SaltGrinder {
       }
     }
     
-    let some_food = self.get_prioritised_food(foods, map);
+    let some_food = self.get_prioritised_food(foods, food_index);
     if let Some(food) = some_food {
-      self.data.rotation.y = self.rotate_towards(self.data.position, &food, 90.0);
+      self.data.rotation.y = self.rotate_towards(self.data.position, food.location, 90.0);
       
       if self.data.charge >= self.get_fire_rate() {
-        let loc = food.get_location();
+        let loc = food.location;
         let direction = Vector2::new(loc.x-self.data.position.x, loc.y-self.data.position.z).normalize();
         
         let mut weapon1: Box<Weapon> = Box::new(Salt::new());
@@ -87,8 +92,17 @@ impl Appliance for SaltGrinder {
     
   }
   
-  fn upgrade(&mut self) {
-    
+  fn upgrade_paths(&self) -> Vec<UpgradePath> {
+    vec!(
+      UpgradePath::new("Coarse Salt", vec!(
+        ("Pierce +1", 45, UpgradeEffect::Pierce(1)),
+        ("Pierce +2", 80, UpgradeEffect::Pierce(2)),
+      )),
+      UpgradePath::new("Quick Grind", vec!(
+        ("Fire rate +15%", 50, UpgradeEffect::FireRate(0.85)),
+        ("Fire rate +20%", 90, UpgradeEffect::FireRate(0.8)),
+      )),
+    )
   }
   
   fn apply_effect(&self) {
@@ -99,10 +113,6 @@ impl Appliance for SaltGrinder {
     
   }
   
-  fn upgrade_cost(&self) -> i32 {
-    1
-  }
-  
   fn sell(&self) -> i32 {
     1
   }
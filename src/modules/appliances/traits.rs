@@ -1,14 +1,22 @@
+#[cfg(feature = "graphics")]
 use maat_graphics::DrawCall;
+#[cfg(feature = "graphics")]
 use maat_graphics::camera;
 
-use crate::modules::food::Food;
-use crate::modules::weapons::Weapon;
+use crate::modules::food::{Food, FoodTarget};
+use crate::modules::weapons::{Weapon, Debuff};
 use crate::modules::map::Map;
+use crate::modules::food_index::FoodIndex;
 use crate::modules::hexagon::{Layout, Hexagon};
+use crate::modules::appliances::{Fridge, Dishwasher, MeatTenderizer, CoffeeMachine, SaltGrinder, CutleryDrawer};
+
+use serde::{Serialize, Deserialize};
 
 use cgmath::{InnerSpace, Angle, Deg, Vector2, Vector3, Vector4};
 
-#[derive(Clone)]
+use std::cmp::Reverse;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub enum TargetPriority {
   First,
   Last,
@@ -18,7 +26,7 @@ pub enum TargetPriority {
   Weak,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Buff {
   Range,
   AttackSpeed,
@@ -28,7 +36,40 @@ pub enum Buff {
   LifeExpectancy,
 }
 
+// What buying one tier of an upgrade path does to an appliance
+#[derive(Clone, Copy, PartialEq)]
+pub enum UpgradeEffect {
+  Range(u32),
+  FireRate(f32), // multiplies the time between shots
+  Damage(i32),
+  Pierce(i32),
+  Buff(Buff), // handed out to appliances in range along with the usual ones
+  Debuff(Debuff), // added to every weapon the appliance fires
+}
+
 #[derive(Clone)]
+pub struct UpgradeTier {
+  pub name: &'static str,
+  pub cost: i32,
+  pub effect: UpgradeEffect,
+}
+
+#[derive(Clone)]
+pub struct UpgradePath {
+  pub name: &'static str,
+  pub tiers: Vec<UpgradeTier>,
+}
+
+impl UpgradePath {
+  pub fn new(name: &'static str, tiers: Vec<(&'static str, i32, UpgradeEffect)>) -> UpgradePath {
+    UpgradePath {
+      name,
+      tiers: tiers.into_iter().map(|(name, cost, effect)| UpgradeTier { name, cost, effect }).collect(),
+    }
+  }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ApplianceData {
   pub position: Vector3<f32>,
   pub offset: Vector3<f32>,
@@ -46,6 +87,16 @@ pub struct ApplianceData {
   pub draw_range: bool,
   pub buy_cost: i32,
   pub directional_range: bool,
+  #[serde(default)]
+  pub upgrades: Vec<usize>,
+  #[serde(default)]
+  pub upgrade_damage: i32,
+  #[serde(default)]
+  pub upgrade_pierce: i32,
+  #[serde(default)]
+  pub upgrade_buffs: Vec<Buff>,
+  #[serde(default)]
+  pub upgrade_debuffs: Vec<Debuff>,
 }
 
 impl ApplianceData {
@@ -69,6 +120,87 @@ impl ApplianceData {
       draw_range: false,
       buy_cost: cost,
       directional_range,
+      upgrades: Vec::new(),
+      upgrade_damage: 0,
+      upgrade_pierce: 0,
+      upgrade_buffs: Vec::new(),
+      upgrade_debuffs: Vec::new(),
+    }
+  }
+}
+
+// The appliances that can be bought, used wherever an appliance has to be
+// named rather than held, like replays
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum ApplianceKind {
+  Dishwasher,
+  Fridge,
+  MeatTenderizer,
+  CoffeeMachine,
+  SaltGrinder,
+  CutleryDrawer,
+}
+
+impl ApplianceKind {
+  pub fn all() -> Vec<ApplianceKind> {
+    vec!(ApplianceKind::Dishwasher, ApplianceKind::Fridge, ApplianceKind::MeatTenderizer,
+         ApplianceKind::CoffeeMachine, ApplianceKind::SaltGrinder, ApplianceKind::CutleryDrawer)
+  }
+  
+  pub fn name(&self) -> &'static str {
+    match self {
+      ApplianceKind::Dishwasher => "Dishwasher",
+      ApplianceKind::Fridge => "Fridge",
+      ApplianceKind::MeatTenderizer => "Meat Tenderizer",
+      ApplianceKind::CoffeeMachine => "Coffee Machine",
+      ApplianceKind::SaltGrinder => "Salt Grinder",
+      ApplianceKind::CutleryDrawer => "Cutlery Drawer",
+    }
+  }
+  
+  pub fn description(&self) -> &'static str {
+    match self {
+      ApplianceKind::Dishwasher => "Single shot, medium firing",
+      ApplianceKind::Fridge => "Hits the whole hex, slow firing",
+      ApplianceKind::MeatTenderizer => "Hits along hex faces, very slow",
+      ApplianceKind::CoffeeMachine => "Buffs range, life and fire rate",
+      ApplianceKind::SaltGrinder => "Multi shot, fast firing",
+      ApplianceKind::CutleryDrawer => "Homing shot, fast firing",
+    }
+  }
+  
+  pub fn create(&self, tile: Vector2<i32>, map: &Map) -> Box<Appliance> {
+    match self {
+      ApplianceKind::Dishwasher => Box::new(Dishwasher::new(tile, Vector3::new(2.0, 2.0, 2.0), Vector3::new(0.0, 0.0, 0.0), map)),
+      ApplianceKind::Fridge => Box::new(Fridge::new(tile, Vector3::new(3.0, 3.0, 3.0), Vector3::new(0.0, 0.0, 0.0), map)),
+      ApplianceKind::MeatTenderizer => Box::new(MeatTenderizer::new(tile, Vector3::new(3.0, 3.0, 3.0), Vector3::new(0.0, 0.0, 0.0), map)),
+      ApplianceKind::CoffeeMachine => Box::new(CoffeeMachine::new(tile, Vector3::new(0.3, 0.3, 0.3), Vector3::new(0.0, 0.0, 0.0), map)),
+      ApplianceKind::SaltGrinder => Box::new(SaltGrinder::new(tile, Vector3::new(2.0, 2.0, 2.0), Vector3::new(0.0, 0.0, -90.0), map)),
+      ApplianceKind::CutleryDrawer => Box::new(CutleryDrawer::new(tile, Vector3::new(2.0, 2.0, 2.0), Vector3::new(0.0, 0.0, 0.0), map)),
+    }
+  }
+}
+
+// The concrete appliance behind a Box<Appliance>, used by save games
+#[derive(Clone, Serialize, Deserialize)]
+pub enum SavedAppliance {
+  Fridge(Fridge),
+  Dishwasher(Dishwasher),
+  MeatTenderizer(MeatTenderizer),
+  CoffeeMachine(CoffeeMachine),
+  SaltGrinder(SaltGrinder),
+  CutleryDrawer(CutleryDrawer),
+}
+
+impl SavedAppliance {
+  pub fn restore(self) -> Box<Appliance> {
+    match self {
+      SavedAppliance::Fridge(appliance) => Box::new(appliance),
+      SavedAppliance::Dishwasher(appliance) => Box::new(appliance),
+      SavedAppliance::MeatTenderizer(appliance) => Box::new(appliance),
+      SavedAppliance::CoffeeMachine(appliance) => Box::new(appliance),
+      SavedAppliance::SaltGrinder(appliance) => Box::new(appliance),
+      SavedAppliance::CutleryDrawer(appliance) => Box::new(appliance),
     }
   }
 }
@@ -92,15 +224,72 @@ impl Clone for Box<Appliance> {
 pub trait Appliance: ApplianceClone {
   fn data(&self) -> &ApplianceData;
   fn mut_data(&mut self) -> &mut ApplianceData;
+  fn save(&self) -> SavedAppliance;
   
-  fn update(&mut self, foods: &mut Vec<Box<Food>>, weapons: &mut Vec<Box<Weapon>>, model_sizes: &mut Vec<(String, Vector3<f32>)>, map: &Map, delta_time: f32) -> Vec<(Buff, Vector2<i32>, u32)>;
+  fn update(&mut self, foods: &mut Vec<Box<Food>>, food_index: &FoodIndex, weapons: &mut Vec<Box<Weapon>>, model_sizes: &mut Vec<(String, Vector3<f32>)>, map: &Map, delta_time: f32) -> Vec<(Buff, Vector2<i32>, u32)>;
   
   fn fire(&mut self);
   
   fn apply_effect(&self);
   fn remove_effects(&self);
   
-  fn upgrade(&mut self);
+  fn upgrade_paths(&self) -> Vec<UpgradePath>;
+  
+  // How many tiers of the path have been bought
+  fn upgrade_tier(&self, path: usize) -> usize {
+    self.data().upgrades.get(path).cloned().unwrap_or(0)
+  }
+  
+  fn next_upgrade(&self, path: usize) -> Option<UpgradeTier> {
+    self.upgrade_paths().get(path).and_then(|upgrade_path| upgrade_path.tiers.get(self.upgrade_tier(path)).cloned())
+  }
+  
+  fn upgrade_cost(&self, path: usize) -> Option<i32> {
+    self.next_upgrade(path).map(|tier| tier.cost)
+  }
+  
+  fn upgrades_value(&self) -> i32 {
+    let paths = self.upgrade_paths();
+    let mut value = 0;
+    for i in 0..paths.len() {
+      for tier in paths[i].tiers.iter().take(self.upgrade_tier(i)) {
+        value += tier.cost;
+      }
+    }
+    
+    value
+  }
+  
+  // Buys the next tier of path, paying for it is left to the caller
+  fn upgrade(&mut self, path: usize) {
+    if let Some(tier) = self.next_upgrade(path) {
+      match tier.effect {
+        UpgradeEffect::Range(range) => {
+          self.mut_data().range += range;
+        },
+        UpgradeEffect::FireRate(modifier) => {
+          self.mut_data().fire_rate *= modifier;
+        },
+        UpgradeEffect::Damage(damage) => {
+          self.mut_data().upgrade_damage += damage;
+        },
+        UpgradeEffect::Pierce(pierce) => {
+          self.mut_data().upgrade_pierce += pierce;
+        },
+        UpgradeEffect::Buff(buff) => {
+          self.mut_data().upgrade_buffs.push(buff);
+        },
+        UpgradeEffect::Debuff(debuff) => {
+          self.mut_data().upgrade_debuffs.push(debuff);
+        },
+      }
+      
+      while self.data().upgrades.len() <= path {
+        self.mut_data().upgrades.push(0);
+      }
+      self.mut_data().upgrades[path] += 1;
+    }
+  }
   
   fn buy_cost(&self) -> i32 {
     self.data().buy_cost
@@ -114,14 +303,13 @@ pub trait Appliance: ApplianceClone {
         0.6
       }
     };
-    ((self.data().buy_cost as f32*modifier)*(self.current_life_expectancy() as f32/self.max_life_expectancy() as f32)).ceil() as i32
+    (((self.data().buy_cost+self.upgrades_value()) as f32*modifier)*(self.current_life_expectancy() as f32/self.max_life_expectancy() as f32)).ceil() as i32
   }
   
   fn clean_cost(&self) -> i32 {
     ((self.max_life_expectancy() - self.current_life_expectancy()) as f32 * (self.buy_cost() as f32*0.6)*(1.0/self.max_life_expectancy() as f32)).ceil() as i32
   }
   
-  fn upgrade_cost(&self) -> i32;
   fn sell(&self) -> i32;
   
   fn get_position(&self) -> Vector3<f32> {
@@ -178,6 +366,18 @@ pub trait Appliance: ApplianceClone {
     }
   }
   
+  // Gives back a buff remove_buff took, without the extra life apply_buff
+  // would hand out, plus whatever life was lost when it went
+  fn restore_buff(&mut self, buff: Buff, life: i32) {
+    if !self.data().buffs.contains(&buff) {
+      self.mut_data().buffs.push(buff);
+      if buff == Buff::LifeExpectancy {
+        self.mut_data().max_life_expectancy += 1;
+        self.mut_data().life_expectancy += life;
+      }
+    }
+  }
+  
   fn remove_buff(&mut self, buff: &Buff) {
     if self.data().buffs.contains(&buff) {
       for i in 0..self.data().buffs.len() {
@@ -195,8 +395,7 @@ pub trait Appliance: ApplianceClone {
     }
   }
   
-  fn rotate_towards(&self, position: Vector3<f32>, food: &Box<Food>, angle_offset: f32) -> f32 {
-    let loc = food.get_location();
+  fn rotate_towards(&self, position: Vector3<f32>, loc: Vector2<f32>, angle_offset: f32) -> f32 {
     let direction = Vector2::new(loc.x-position.x, loc.y-position.z).normalize();
     let mut angle = Deg::atan2(direction.x, direction.y);
     
@@ -217,106 +416,44 @@ pub trait Appliance: ApplianceClone {
     if self.data().buffs.contains(&Buff::AttackDamage) { 
       weapon.damage_multiplier(0.5);
     }
+    if self.data().upgrade_damage > 0 {
+      weapon.add_damage(self.data().upgrade_damage);
+    }
+    if self.data().upgrade_pierce > 0 {
+      weapon.add_pierce(self.data().upgrade_pierce);
+    }
+    for debuff in &self.data().upgrade_debuffs {
+      weapon.add_debuff(*debuff);
+    }
   }
   
-  fn get_prioritised_food(&self, foods: &mut Vec<Box<Food>>, map: &Map) -> Option<Box<Food>> {
-    let mut food = None;
+  fn get_prioritised_food(&self, foods: &Vec<Box<Food>>, food_index: &FoodIndex) -> Option<FoodTarget> {
+    let directional = self.data().directional_range;
+    let in_range = || food_index.foods_in_range(self.data().tile_location, self.get_range() as i32, directional);
     
-    if foods.len() > 0 {
-      let mut food_in_range = Vec::new();
-      let mut food_distances = Vec::new();
-      
-      for food in foods {
-        let food_pos = food.get_location();
-        let other_hex = map.pixel_to_hex(food_pos);
-        let hex = Hexagon::new(self.data().tile_location.x, self.data().tile_location.y, "".to_string());
-        
-        let dist = Hexagon::hex_distance(&hex, &other_hex);
-      
-        if dist <= self.get_range() as i32 {
-          if self.data().directional_range && self.get_range() > 1 {
-            if !Hexagon::is_on_same_axis(&hex, &other_hex) {
-              continue;
-            }
-          }
-          food_in_range.push(food.clone());
-          food_distances.push(dist);
-        }
-      }
-      
-      if food_in_range.len() == 0 {
-        return food;
-      }
-      
-      match &self.data().target {
-        TargetPriority::First => {
-          food = Some(food_in_range[0].clone());
-        },
-        TargetPriority::Last => {
-          food = Some(food_in_range[food_in_range.len()-1].clone());
-        },
-        TargetPriority::Close => {
-          let mut idx = 0;
-          let mut closest_distance = food_distances[idx];
-          for i in 1..food_distances.len() {
-            let other_dist = food_distances[i];
-            
-            if other_dist < closest_distance {
-              idx = i;
-              closest_distance = other_dist;
-            }
-          }
-          
-          food = Some(food_in_range[idx].clone());
-        },
-        TargetPriority::Far => {
-          let mut idx = 0;
-          let mut furthest_distance = food_distances[idx];
-          for i in 1..food_distances.len() {
-            let other_dist = food_distances[i];
-            
-            if other_dist > furthest_distance {
-              idx = i;
-              furthest_distance = other_dist;
-            }
-          }
-          
-          food = Some(food_in_range[idx].clone());
-        },
-        TargetPriority::Strong => {
-          let mut idx = 0;
-          let mut most_health = food_in_range[idx].get_health();
-          for i in (1..food_in_range.len()).rev() {
-            let other_food_health = food_in_range[i].get_health();
-            if other_food_health > most_health {
-              most_health = other_food_health;
-              idx = i;
-            }
-          }
-          food = Some(food_in_range[idx].clone());
-        },
-        TargetPriority::Weak => {
-          let mut idx = 0;
-          let mut least_health = food_in_range[idx].get_health();
-          for i in (1..food_in_range.len()).rev() {
-            let other_food_health = food_in_range[i].get_health();
-            if other_food_health < least_health {
-              least_health = other_food_health;
-              idx = i;
-            }
-          }
-          food = Some(food_in_range[idx].clone());
-        },
-      }
-    }
+    // First and Last go by order in the foods vec. Ties go the way they did
+    // when foods were checked in that order: closest or furthest picks the
+    // earliest food, strongest or weakest the latest unless the first is level
+    let first = in_range().map(|(idx, _)| idx).min()?;
+    let health = |idx: usize| foods[idx].get_health();
+    
+    let food_idx = match &self.data().target {
+      TargetPriority::First => first,
+      TargetPriority::Last => in_range().map(|(idx, _)| idx).max()?,
+      TargetPriority::Close => in_range().min_by_key(|&(idx, dist)| (dist, idx))?.0,
+      TargetPriority::Far => in_range().max_by_key(|&(idx, dist)| (dist, Reverse(idx)))?.0,
+      TargetPriority::Strong => in_range().map(|(idx, _)| idx).max_by_key(|&idx| (health(idx), idx == first, idx))?,
+      TargetPriority::Weak => in_range().map(|(idx, _)| idx).min_by_key(|&idx| (health(idx), idx != first, Reverse(idx)))?,
+    };
     
-    food
+    Some(foods[food_idx].as_target(food_idx))
   }
   
   fn should_draw_range(&mut self, should_draw: bool) {
     self.mut_data().draw_range = should_draw;
   }
   
+  #[cfg(feature = "graphics")]
   fn draw_range_coloured(&self, map: &Map, colour: Vector3<f32>, draw_calls: &mut Vec<DrawCall>) {
     let mut layout = map.get_layout();
     let new_origin = Vector2::new(self.data().position.x, self.data().position.z);
@@ -342,6 +479,7 @@ pub trait Appliance: ApplianceClone {
     }
   }
   
+  #[cfg(feature = "graphics")]
   fn draw_range(&self, map: &Map, valid: bool, draw_calls: &mut Vec<DrawCall>) {
     let mut layout = map.get_layout();
     let new_origin = Vector2::new(self.data().position.x, self.data().position.z);
@@ -371,6 +509,7 @@ pub trait Appliance: ApplianceClone {
     }
   }
   
+  #[cfg(feature = "graphics")]
   fn draw_hologram_invalid(&self, map: &Map, draw_calls: &mut Vec<DrawCall>) {
     draw_calls.push(DrawCall::add_instanced_hologram_model_overwrite_colour(self.data().model.to_string(), self.data().position+self.data().offset, self.data().size, self.data().rotation, Vector3::new(1.0, 0.0, 0.0)));
     
@@ -379,6 +518,7 @@ pub trait Appliance: ApplianceClone {
     }
   }
   
+  #[cfg(feature = "graphics")]
   fn draw_hologram(&self, map: &Map, draw_calls: &mut Vec<DrawCall>) {
     draw_calls.push(DrawCall::add_instanced_hologram_model_overwrite_colour(self.data().model.to_string(), self.data().position+self.data().offset, self.data().size, self.data().rotation, Vector3::new(0.0, 1.0, 0.0)));
     
@@ -387,6 +527,7 @@ pub trait Appliance: ApplianceClone {
     }
   }
   
+  #[cfg(feature = "graphics")]
   fn draw(&self, map: &Map, camera: &camera::Camera, window_dim: Vector2<f32>, draw_calls: &mut Vec<DrawCall>) {
     draw_calls.push(DrawCall::add_instanced_model(self.data().model.to_string(), self.data().position+self.data().offset, self.data().size, self.data().rotation));
     
@@ -0,0 +1,111 @@
+use crate::modules::appliances::traits::{ApplianceKind, TargetPriority};
+
+use serde::{Serialize, Deserialize};
+
+use std::fmt;
+use std::error::Error;
+
+pub const GAME_SPEEDS: [i32; 5] = [1, 2, 4, 8, 16];
+// Replays recorded before pausing had its own flag paused with this speed
+pub const LEGACY_PAUSE_SPEED: i32 = 0;
+
+// Everything the player can do to a game. Keyboard, mouse, replays and
+// headless tools all go through Simulation::apply_command
+#[derive(Clone, Serialize, Deserialize)]
+pub enum GameCommand {
+  Place { kind: ApplianceKind, q: i32, r: i32 },
+  Move { appliance: usize, q: i32, r: i32 },
+  Sell { appliance: usize },
+  Clean { appliance: usize },
+  Upgrade { appliance: usize, path: usize },
+  Targeting { appliance: usize, target: TargetPriority },
+  Speed(i32),
+  EmptyBin,
+  Restart,
+  // Takes back the last place, move, sale, clean or bin empty
+  Undo,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CommandOutcome {
+  Placed { appliance: usize, cost: i32 },
+  Moved { appliance: usize, tiles: i32 },
+  Sold { refund: i32 },
+  Cleaned { cost: i32 },
+  Upgraded { appliance: usize, path: usize, cost: i32 },
+  TargetingChanged,
+  SpeedChanged(i32),
+  BinEmptied { cost: i32 },
+  Restarted,
+  // refund is negative when undoing a sale hands the money back
+  Undone { refund: i32 },
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum CommandError {
+  NoSuchAppliance(usize),
+  InvalidHex { q: i32, r: i32 },
+  HexNotOpen { q: i32, r: i32 },
+  OutOfMoveRange { distance: i32, max: i32 },
+  NotEnoughMoney { cost: i32, money: i32 },
+  NothingToClean,
+  NoSuchUpgrade { path: usize },
+  FullyUpgraded { path: usize },
+  BinEmpty,
+  InvalidSpeed(i32),
+  NothingToUndo,
+  ApplianceGone { q: i32, r: i32 },
+  HexBuiltOn { q: i32, r: i32 },
+  UndoWouldFillBin,
+}
+
+impl fmt::Display for CommandError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      CommandError::NoSuchAppliance(idx) => {
+        write!(f, "There is no appliance {}", idx)
+      },
+      CommandError::InvalidHex { q, r } => {
+        write!(f, "Hex {},{} is not on the map", q, r)
+      },
+      CommandError::HexNotOpen { q, r } => {
+        write!(f, "Hex {},{} is not free to build on", q, r)
+      },
+      CommandError::OutOfMoveRange { distance, max } => {
+        write!(f, "Cant move an appliance {} tiles, it can only go {}", distance, max)
+      },
+      CommandError::NotEnoughMoney { cost, money } => {
+        write!(f, "Costs ${} but there is only ${}", cost, money)
+      },
+      CommandError::NothingToClean => {
+        write!(f, "Appliance is already clean")
+      },
+      CommandError::NoSuchUpgrade { path } => {
+        write!(f, "Appliance has no upgrade path {}", path)
+      },
+      CommandError::FullyUpgraded { path } => {
+        write!(f, "Upgrade path {} is already maxed out", path)
+      },
+      CommandError::BinEmpty => {
+        write!(f, "The bin is already empty")
+      },
+      CommandError::InvalidSpeed(speed) => {
+        write!(f, "{} is not a game speed", speed)
+      },
+      CommandError::NothingToUndo => {
+        write!(f, "There is nothing left to undo")
+      },
+      CommandError::ApplianceGone { q, r } => {
+        write!(f, "The appliance at {},{} is gone, it can't be undone", q, r)
+      },
+      CommandError::HexBuiltOn { q, r } => {
+        write!(f, "Hex {},{} has been built on since, it can't be undone", q, r)
+      },
+      CommandError::UndoWouldFillBin => {
+        write!(f, "Putting the food back would fill the bin")
+      },
+    }
+  }
+}
+
+impl Error for CommandError {}
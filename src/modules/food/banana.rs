@@ -1,17 +1,21 @@
-use crate::modules::food::{Food, FoodData};
+use crate::modules::food::{Food, FoodData, FoodIds, SavedFood};
 use crate::modules::map::Map;
 
+use serde::{Serialize, Deserialize};
+
 use cgmath::{Vector2, Vector3};
 
+use std::rc::Rc;
+
 const MAX_HEALTH: i32 = 30;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Banana {
   data: FoodData,
 }
 
 impl Banana {
-  pub fn new(id: i32, position: Vector2<f32>, path: Vec<u32>, location: Vector2<i32>) -> Banana {
+  pub fn new(id: i32, position: Vector2<f32>, path: Rc<Vec<u32>>, location: Vector2<i32>) -> Banana {
     let health = MAX_HEALTH;
     let speed = 15.0;
     let position = Vector3::new(position.x, 5.0, position.y);
@@ -33,11 +37,15 @@ impl Food for Banana {
     &mut self.data
   }
   
+  fn save(&self) -> SavedFood {
+    SavedFood::Banana(self.clone())
+  }
+  
   fn get_bin_space(&self) -> i32 {
     (5.0 * (self.data().health as f32 / MAX_HEALTH as f32)).ceil() as i32
   }
   
-  fn get_children(&self, map: &Map) -> Vec<Box<Food>> {
+  fn get_children(&self, map: &Map, ids: &mut FoodIds) -> Vec<Box<Food>> {
     Vec::new()
   }
   
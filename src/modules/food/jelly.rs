@@ -1,34 +1,36 @@
-use maat_graphics::math;
-
 use std::f32::consts::FRAC_PI_2;
 
-use crate::modules::food::{Food, FoodData};
+use crate::modules::food::{Food, FoodData, FoodIds, SavedFood};
 use crate::modules::map::Map;
 
+use serde::{Serialize, Deserialize};
+
 use cgmath::{InnerSpace, Vector2, Vector3};
 
+use std::rc::Rc;
+
 const MAX_HEALTH: i32 = 250;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Jelly {
   data: FoodData,
 }
 
 impl Jelly {
-  pub fn new(id: i32, position: Vector2<f32>, path: Vec<u32>, location: Vector2<i32>) -> Jelly {
+  pub fn new(id: i32, position: Vector2<f32>, path: Rc<Vec<u32>>, location: Vector2<i32>) -> Jelly {
     let health = MAX_HEALTH;
     let speed = 12.0;
     let position = Vector3::new(position.x, 0.0, position.y);
     let rotation = Vector3::new(0.0, -30.0, 0.0);
     let size = Vector3::new(3.0, 3.0, 3.0);
     let sell_price = 50;
-    let mut path = path.clone();
+    let mut path = (*path).clone();
     for i in 1..(path.len() as f32*0.5).floor() as usize {
       path.remove(i*2-i);
     }
     
     Jelly {
-      data: FoodData::new(id, position, rotation, size, speed, health, "Salt".to_string(), path, location, sell_price),
+      data: FoodData::new(id, position, rotation, size, speed, health, "Salt".to_string(), Rc::new(path), location, sell_price),
     }
   }
 }
@@ -42,11 +44,15 @@ impl Food for Jelly {
     &mut self.data
   }
   
+  fn save(&self) -> SavedFood {
+    SavedFood::Jelly(self.clone())
+  }
+  
   fn get_bin_space(&self) -> i32 {
     (30.0 * (self.data().health as f32 / MAX_HEALTH as f32)).ceil() as i32
   }
   
-  fn get_children(&self, map: &Map) -> Vec<Box<Food>> {
+  fn get_children(&self, map: &Map, ids: &mut FoodIds) -> Vec<Box<Food>> {
     Vec::new()
   }
   
@@ -12,14 +12,24 @@ mod pineapple;
 mod mushroom;
 mod jelly;
 
+#[cfg(feature = "graphics")]
 use maat_graphics::DrawCall;
 
 use crate::modules::weapons::Debuff;
 use crate::modules::map::Map;
 
+use serde::{Serialize, Deserialize};
+
 use cgmath::{InnerSpace, Angle, Deg, Vector2, Vector3};
 
-#[derive(Clone)]
+use std::rc::Rc;
+
+// Seconds after a freeze or reverse wears off before another can take hold,
+// stops a line of appliances stun locking a food forever
+pub const FREEZE_IMMUNITY: f32 = 2.0;
+pub const REVERSE_IMMUNITY: f32 = 3.0;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct FoodData {
   id: i32,
   position: Vector3<f32>,
@@ -31,17 +41,28 @@ pub struct FoodData {
   path_location: Vector2<i32>,
   speed: f32,
   target: Vector2<f32>,
-  path: Vec<u32>,
+  // Left out of saves for foods on one of the map's routes, see SavedFood
+  #[serde(default, skip_serializing_if = "no_path")]
+  path: Rc<Vec<u32>>, // shared with every other food on the same route
+  // Only set in saves, which of the routes path was
+  #[serde(default)]
+  route: Option<usize>,
   health: i32,
   max_health: i32,
   total_dt: f32,
   cooked: bool,
   rotten: bool,
   sell_price: i32,
+  #[serde(default)]
+  reversed: bool,
+  #[serde(default)]
+  freeze_immunity: f32,
+  #[serde(default)]
+  reverse_immunity: f32,
 }
 
 impl FoodData {
-  pub fn new(id: i32, position: Vector3<f32>, rotation: Vector3<f32>, size: Vector3<f32>, speed: f32, health: i32, model: String, path: Vec<u32>, location: Vector2<i32>, sell_price: i32) -> FoodData {
+  pub fn new(id: i32, position: Vector3<f32>, rotation: Vector3<f32>, size: Vector3<f32>, speed: f32, health: i32, model: String, path: Rc<Vec<u32>>, location: Vector2<i32>, sell_price: i32) -> FoodData {
     FoodData {
       id,
       position,
@@ -54,12 +75,127 @@ impl FoodData {
       speed,
       target: position.xz(),
       path,
+      route: None,
       health,
       max_health: health,
       total_dt: 0.0,
       cooked: false,
       rotten: false,
       sell_price,
+      reversed: false,
+      freeze_immunity: 0.0,
+      reverse_immunity: 0.0,
+    }
+  }
+}
+
+fn no_path(path: &Rc<Vec<u32>>) -> bool {
+  path.is_empty()
+}
+
+// Hands out food ids that are never reused within a game, so weapons can
+// tell apart every food including children split off a cooked parent
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FoodIds {
+  next_id: i32,
+}
+
+impl FoodIds {
+  pub fn new() -> FoodIds {
+    FoodIds {
+      next_id: 0,
+    }
+  }
+  
+  // Carries on after the highest id already in use
+  pub fn after(foods: &Vec<Box<Food>>) -> FoodIds {
+    FoodIds {
+      next_id: foods.iter().map(|food| food.get_id()+1).max().unwrap_or(0),
+    }
+  }
+  
+  pub fn allocate(&mut self) -> i32 {
+    let id = self.next_id;
+    self.next_id += 1;
+    id
+  }
+}
+
+// Enough about a food for an appliance to aim at it without cloning it,
+// index is only good until the foods vec next changes
+#[derive(Clone, Copy)]
+pub struct FoodTarget {
+  pub id: i32,
+  pub index: usize,
+  pub location: Vector2<f32>,
+  pub tile_location: Vector2<i32>,
+}
+
+// Each kind of food by value so a game in progress can be written to a save file.
+// Foods on one of the map's routes only keep its index, and share the route
+// again once loaded
+#[derive(Clone, Serialize, Deserialize)]
+pub enum SavedFood {
+  Strawberry(Strawberry),
+  Banana(Banana),
+  Cake(Cake),
+  Pineapple(Pineapple),
+  Mushroom(Mushroom),
+  Jelly(Jelly),
+}
+
+impl SavedFood {
+  pub fn new(food: &Food, routes: &[Rc<Vec<u32>>]) -> SavedFood {
+    let mut saved = food.save();
+    
+    let data = saved.mut_data();
+    if let Some(route) = routes.iter().position(|path| Rc::ptr_eq(path, &data.path) || *path == data.path) {
+      data.route = Some(route);
+      data.path = Rc::new(Vec::new());
+    }
+    
+    saved
+  }
+  
+  // None if the food was on a route the map no longer has
+  pub fn restore(self, routes: &[Rc<Vec<u32>>]) -> Option<Box<Food>> {
+    let mut food: Box<Food> = match self {
+      SavedFood::Strawberry(food) => Box::new(food),
+      SavedFood::Banana(food) => Box::new(food),
+      SavedFood::Cake(food) => Box::new(food),
+      SavedFood::Pineapple(food) => Box::new(food),
+      SavedFood::Mushroom(food) => Box::new(food),
+      SavedFood::Jelly(food) => Box::new(food),
+    };
+    
+    let data = food.mut_data();
+    match data.route.take() {
+      Some(route) => {
+        data.path = routes.get(route)?.clone();
+      },
+      None => {
+        // Older saves kept every path in full
+        if let Some(path) = routes.iter().find(|path| **path == data.path) {
+          data.path = path.clone();
+        }
+      },
+    }
+    
+    if data.path.is_empty() {
+      return None;
+    }
+    
+    Some(food)
+  }
+  
+  fn mut_data(&mut self) -> &mut FoodData {
+    match self {
+      SavedFood::Strawberry(food) => food.mut_data(),
+      SavedFood::Banana(food) => food.mut_data(),
+      SavedFood::Cake(food) => food.mut_data(),
+      SavedFood::Pineapple(food) => food.mut_data(),
+      SavedFood::Mushroom(food) => food.mut_data(),
+      SavedFood::Jelly(food) => food.mut_data(),
     }
   }
 }
@@ -84,7 +220,8 @@ pub trait Food: FoodClone {
   fn data(&self) -> &FoodData;
   fn mut_data(&mut self) -> &mut FoodData;
   fn get_bin_space(&self) -> i32;
-  fn get_children(&self, map: &Map) -> Vec<Box<Food>>;
+  fn get_children(&self, map: &Map, ids: &mut FoodIds) -> Vec<Box<Food>>;
+  fn save(&self) -> SavedFood;
   
   fn local_update(&mut self, map: &Map, move_angle: f32, delta_time: f32);
   fn update(&mut self, map: &Map, delta_time: f32) {
@@ -93,27 +230,11 @@ pub trait Food: FoodClone {
     }
     
     let mut speed = self.data().speed;
+    let mut frozen = false;
+    let mut reversed = false;
     
-    if (self.data().position.x-self.data().target.x + self.data().position.z-self.data().target.y).abs() < 0.4 {
-      self.mut_data().path_number += 1;
-      if self.data().path_number >= self.data().path.len() as u32 {
-        self.mut_data().rotten = true;
-        self.mut_data().cooked = false;
-        self.mut_data().path_number -= 1;
-        self.mut_data().position.y -= speed*delta_time;
-        return;
-      }
-      
-      let map_pos = map.tile_position_from_index(self.data().path[self.data().path_number as usize] as usize);
-      
-      self.mut_data().path_location = map.get_qr_from_index(self.data().path[self.data().path_number as usize] as usize);
-      
-      self.mut_data().target.x = map_pos.x;
-      self.mut_data().target.y = map_pos.y;
-    }
-    
-    let direction = Vector2::new(self.data().target.x-self.data().position.x, self.data().target.y-self.data().position.z).normalize();
-    let angle = Deg::atan2(direction.x, direction.y);
+    self.mut_data().freeze_immunity = (self.data().freeze_immunity - delta_time).max(0.0);
+    self.mut_data().reverse_immunity = (self.data().reverse_immunity - delta_time).max(0.0);
     
     let mut remove_debuffs = Vec::new();
     for i in 0..self.data().debuffs.len() {
@@ -127,31 +248,112 @@ pub trait Food: FoodClone {
           }
         },
         Debuff::Freeze(timer) => {
-          speed = 0.0;
+          *timer -= delta_time;
+          if *timer <= 0.0 {
+            remove_debuffs.push(i);
+          } else {
+            frozen = true;
+          }
         },
         Debuff::Reverse(timer) => {
-          speed = -speed;
+          *timer -= delta_time;
+          if *timer <= 0.0 {
+            remove_debuffs.push(i);
+          } else {
+            reversed = true;
+          }
         }
       }
     }
     
-    let mut offset = 0;
-    for i in 0..remove_debuffs.len() {
-      self.mut_data().debuffs.remove(remove_debuffs[i]-offset);
-      offset += 1;
+    for i in remove_debuffs.into_iter().rev() {
+      match self.mut_data().debuffs.remove(i) {
+        Debuff::Freeze(_) => {
+          self.mut_data().freeze_immunity = FREEZE_IMMUNITY;
+        },
+        Debuff::Reverse(_) => {
+          self.mut_data().reverse_immunity = REVERSE_IMMUNITY;
+        },
+        Debuff::Slow(_) => {},
+      }
     }
     
-    direction.normalize();
-    self.mut_data().position.x += direction.x*speed*delta_time;
-    self.mut_data().position.z += direction.y*speed*delta_time;
+    // Turning around means heading back to the tile we just came from
+    if reversed != self.data().reversed && !self.data().rotten {
+      self.mut_data().reversed = reversed;
+      let path_number = self.data().path_number;
+      if reversed && path_number > 0 {
+        self.set_path_target(map, path_number-1);
+      } else if !reversed && path_number+1 < self.data().path.len() as u32 {
+        self.set_path_target(map, path_number+1);
+      }
+    }
+    
+    if (self.data().position.x-self.data().target.x + self.data().position.z-self.data().target.y).abs() < 0.4 {
+      if self.data().reversed {
+        let path_number = self.data().path_number;
+        if path_number > 0 {
+          self.set_path_target(map, path_number-1);
+        } else {
+          // back where it spawned, wait there until the reverse wears off
+          frozen = true;
+        }
+      } else {
+        self.mut_data().path_number += 1;
+        if self.data().path_number >= self.data().path.len() as u32 {
+          self.mut_data().rotten = true;
+          self.mut_data().cooked = false;
+          self.mut_data().path_number -= 1;
+          self.mut_data().position.y -= speed*delta_time;
+          return;
+        }
+        
+        let path_number = self.data().path_number;
+        self.set_path_target(map, path_number);
+      }
+    }
+    
+    let direction = Vector2::new(self.data().target.x-self.data().position.x, self.data().target.y-self.data().position.z).normalize();
+    let angle = Deg::atan2(direction.x, direction.y);
+    
+    if !frozen {
+      self.mut_data().position.x += direction.x*speed*delta_time;
+      self.mut_data().position.z += direction.y*speed*delta_time;
+    }
     
     self.local_update(map, angle.0, delta_time);
   }
   
+  fn set_path_target(&mut self, map: &Map, path_number: u32) {
+    let index = self.data().path[path_number as usize] as usize;
+    let map_pos = map.tile_position_from_index(index);
+    
+    self.mut_data().path_number = path_number;
+    self.mut_data().path_location = map.get_qr_from_index(index);
+    self.mut_data().target.x = map_pos.x;
+    self.mut_data().target.y = map_pos.y;
+  }
+  
   fn get_health(&self) -> i32 {
     self.data().health
   }
   
+  fn set_route(&mut self, path: Rc<Vec<u32>>, position: Vector2<f32>, location: Vector2<i32>) {
+    let height = self.data().position.y;
+    self.mut_data().position = Vector3::new(position.x, height, position.y);
+    self.mut_data().target = position;
+    self.mut_data().path_location = location;
+    self.mut_data().path_number = 0;
+    self.mut_data().path = path;
+  }
+  
+  fn scale_stats(&mut self, health: f32, speed: f32) {
+    let scaled_health = ((self.data().health as f32*health).round() as i32).max(1);
+    self.mut_data().health = scaled_health;
+    self.mut_data().max_health = scaled_health;
+    self.mut_data().speed *= speed;
+  }
+  
   fn get_path_num(&self) -> u32 {
     self.data().path_number
   }
@@ -160,6 +362,10 @@ pub trait Food: FoodClone {
     self.data().id
   }
   
+  fn set_id(&mut self, id: i32) {
+    self.mut_data().id = id;
+  }
+  
   fn is_cooked(&self) -> bool {
     self.data().cooked
   }
@@ -179,7 +385,19 @@ pub trait Food: FoodClone {
   
   fn apply_debuffs(&mut self, debuffs: Vec<Debuff>) {
     for debuff in debuffs {
-      if !self.data().debuffs.contains(&debuff) {
+      let already_applied = match debuff {
+        Debuff::Slow(_) => self.data().debuffs.contains(&debuff),
+        Debuff::Freeze(_) => {
+          self.data().freeze_immunity > 0.0 ||
+          self.data().debuffs.iter().any(|d| if let Debuff::Freeze(_) = d { true } else { false })
+        },
+        Debuff::Reverse(_) => {
+          self.data().reverse_immunity > 0.0 ||
+          self.data().debuffs.iter().any(|d| if let Debuff::Reverse(_) = d { true } else { false })
+        },
+      };
+      
+      if !already_applied {
         self.mut_data().debuffs.push(debuff);
       }
     }
@@ -197,6 +415,16 @@ pub trait Food: FoodClone {
     self.data().position.xz()
   }
   
+  fn as_target(&self, index: usize) -> FoodTarget {
+    FoodTarget {
+      id: self.get_id(),
+      index,
+      location: self.get_location(),
+      tile_location: self.get_tile_location(),
+    }
+  }
+  
+  #[cfg(feature = "graphics")]
   fn draw(&self, draw_calls: &mut Vec<DrawCall>) {
     draw_calls.push(DrawCall::add_instanced_model_overwrite_colour(self.data().model.to_string(), self.data().position, self.data().size, self.data().rotation, Vector3::new(1.0 - (self.data().health as f32/self.data().max_health as f32), self.data().health as f32/self.data().max_health as f32, 0.0)));
   }
@@ -1,15 +1,19 @@
-use crate::modules::food::{Food, FoodData};
+use crate::modules::food::{Food, FoodData, FoodIds, SavedFood};
 use crate::modules::map::Map;
 
+use serde::{Serialize, Deserialize};
+
 use cgmath::{Vector2, Vector3};
 
+use std::rc::Rc;
+
 const GROW_SPEED: f32 = 0.15;
 const MAX_SIZE: f32 = 3.0;
 
 const START_SPEED: f32 = 40.0;
 const START_HEALTH: i32 = 10;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Mushroom {
   data: FoodData,
   grow_speed: f32,
@@ -17,7 +21,7 @@ pub struct Mushroom {
 }
 
 impl Mushroom {
-  pub fn new(id: i32, position: Vector2<f32>, path: Vec<u32>, location: Vector2<i32>) -> Mushroom {
+  pub fn new(id: i32, position: Vector2<f32>, path: Rc<Vec<u32>>, location: Vector2<i32>) -> Mushroom {
     let health = START_HEALTH;
     let speed = START_SPEED;
     let position = Vector3::new(position.x, 0.0, position.y);
@@ -31,7 +35,7 @@ impl Mushroom {
     }
   }
   
-  pub fn new_baby_shroom(id: i32, position: Vector2<f32>, path: Vec<u32>, path_number: u32, location: Vector2<i32>) -> Mushroom {
+  pub fn new_baby_shroom(id: i32, position: Vector2<f32>, path: Rc<Vec<u32>>, path_number: u32, location: Vector2<i32>) -> Mushroom {
     let health = START_HEALTH;
     let speed = START_SPEED;
     let position = Vector3::new(position.x, 0.0, position.y);
@@ -59,11 +63,15 @@ impl Food for Mushroom {
     &mut self.data
   }
   
+  fn save(&self) -> SavedFood {
+    SavedFood::Mushroom(self.clone())
+  }
+  
   fn get_bin_space(&self) -> i32 {
     (15.0 * self.data().size.x).ceil() as i32
   }
   
-  fn get_children(&self, map: &Map) -> Vec<Box<Food>> {
+  fn get_children(&self, map: &Map, ids: &mut FoodIds) -> Vec<Box<Food>> {
     let mut shroom_childs: Vec<Box<Food>> = Vec::new();
     
     let size = self.data().size.x;
@@ -92,7 +100,7 @@ impl Food for Mushroom {
       let index = self.data().path[path_num as usize];
       let qr = map.get_qr_from_index(index as usize);
       let pos = map.tile_position_from_index(index as usize);
-      shroom_childs.push(Box::new(Mushroom::new_baby_shroom(self.data().id, pos, self.data().path.clone(), path_num, qr)));
+      shroom_childs.push(Box::new(Mushroom::new_baby_shroom(ids.allocate(), pos, self.data().path.clone(), path_num, qr)));
     }
     
     shroom_childs
@@ -1,18 +1,22 @@
-use crate::modules::food::{Food, FoodData};
+use crate::modules::food::{Food, FoodData, FoodIds, SavedFood};
 use crate::modules::map::Map;
 
+use serde::{Serialize, Deserialize};
+
 use cgmath::{Vector2, Vector3};
 
+use std::rc::Rc;
+
 const MAX_HEALTH: i32 = 60;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Pineapple {
   data: FoodData,
   is_inner: bool,
 }
 
 impl Pineapple {
-  pub fn new(id: i32, position: Vector2<f32>, path: Vec<u32>, location: Vector2<i32>) -> Pineapple {
+  pub fn new(id: i32, position: Vector2<f32>, path: Rc<Vec<u32>>, location: Vector2<i32>) -> Pineapple {
     let health = MAX_HEALTH;
     let speed = 12.5;
     let position = Vector3::new(position.x, 0.0, position.y);
@@ -26,7 +30,7 @@ impl Pineapple {
     }
   }
   
-  pub fn new_inner(id: i32, position: Vector2<f32>, path: Vec<u32>, path_number: u32, location: Vector2<i32>) -> Pineapple {
+  pub fn new_inner(id: i32, position: Vector2<f32>, path: Rc<Vec<u32>>, path_number: u32, location: Vector2<i32>) -> Pineapple {
     let health = MAX_HEALTH/2;
     let speed = 18.0;
     let position = Vector3::new(position.x, 0.0, position.y);
@@ -53,13 +57,17 @@ impl Food for Pineapple {
     &mut self.data
   }
   
+  fn save(&self) -> SavedFood {
+    SavedFood::Pineapple(self.clone())
+  }
+  
   fn get_bin_space(&self) -> i32 {
     (30.0*self.data().size.x * (self.data().health as f32 / MAX_HEALTH as f32)).ceil() as i32
   }
   
-  fn get_children(&self, map: &Map) -> Vec<Box<Food>> {
+  fn get_children(&self, map: &Map, ids: &mut FoodIds) -> Vec<Box<Food>> {
     if !self.is_inner {
-      vec!(Box::new(Pineapple::new_inner(self.data.id, self.data.position.xz(), self.data.path.clone(), self.data.path_number, self.data.path_location)))
+      vec!(Box::new(Pineapple::new_inner(ids.allocate(), self.data.position.xz(), self.data.path.clone(), self.data.path_number, self.data.path_location)))
     } else {
       Vec::new()
     }
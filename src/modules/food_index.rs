@@ -0,0 +1,56 @@
+use crate::modules::food::Food;
+use crate::modules::hexagon::Hexagon;
+use crate::modules::map::Map;
+
+use cgmath::Vector2;
+
+use std::collections::HashMap;
+
+// Foods bucketed by the hex they are standing on. Rebuilt every tick so
+// collisions and targeting only look at foods near them instead of every
+// food on the map. Buckets hold indices into the foods vec it was built from
+pub struct FoodIndex {
+  buckets: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl FoodIndex {
+  pub fn new(foods: &Vec<Box<Food>>, map: &Map) -> FoodIndex {
+    let mut buckets: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+    
+    for i in 0..foods.len() {
+      let hex = map.pixel_to_hex(foods[i].get_location());
+      buckets.entry((hex.q(), hex.r())).or_insert(Vec::new()).push(i);
+    }
+    
+    FoodIndex {
+      buckets,
+    }
+  }
+  
+  pub fn foods_on(&self, hex: &Hexagon) -> &[usize] {
+    match self.buckets.get(&(hex.q(), hex.r())) {
+      Some(foods) => foods,
+      None => &[],
+    }
+  }
+  
+  // Every food within range of the centre hex with its distance, in no
+  // particular order. Runs for every appliance every tick so it works from
+  // the bucket keys and allocates nothing
+  pub fn foods_in_range<'a>(&'a self, centre: Vector2<i32>, range: i32, directional: bool) -> impl Iterator<Item = (usize, i32)> + 'a {
+    self.buckets.iter().filter_map(move |(&(q, r), bucket)| {
+      let (dq, dr) = (q - centre.x, r - centre.y);
+      let dist = (dq.abs() + dr.abs() + (dq+dr).abs())/2;
+      
+      if dist > range {
+        return None;
+      }
+      // Off every axis through the centre
+      if directional && range > 1 && dq != 0 && dr != 0 && dq+dr != 0 {
+        return None;
+      }
+      
+      Some(bucket.iter().map(move |&idx| (idx, dist)))
+    }).flatten()
+  }
+}
@@ -1,10 +1,14 @@
+#[cfg(feature = "graphics")]
 use maat_graphics::DrawCall;
+#[cfg(feature = "graphics")]
 use maat_graphics::math;
 
 use crate::modules::map::Map;
 
 use cgmath::{Vector2, Vector3};
 
+use std::collections::{HashMap, VecDeque};
+
 // Orientation information
 const F0: f32 = 1.732050808;
 const F1: f32 = 1.732050808 * 0.5;
@@ -16,6 +20,9 @@ const B1: f32 = -1.0 / 3.0;
 const B2: f32 = 0.0;
 const B3: f32 = 2.0 / 3.0;
 
+// Routes kept for each start, so one start can't use up every route
+const MAX_ROUTES_PER_START: usize = 16;
+
 #[derive(Clone, PartialEq)]
 pub enum HexagonType {
   Start,
@@ -23,6 +30,7 @@ pub enum HexagonType {
   Path,
   Open,
   Closed,
+  Blocked,
 }
 
 #[derive(Clone)]
@@ -104,7 +112,7 @@ impl Layout {
     Hexagon::new(rnd_q as i32, rnd_r as i32, "".to_string())
   }
   
-  pub fn calculate_path(hexagons: &mut Vec<Hexagon>) -> Vec<u32> {
+  pub fn calculate_path(hexagons: &mut Vec<Hexagon>) -> Option<Vec<u32>> {
     let mut start_idx = 0;
     let mut end_idx = 0;
     
@@ -154,12 +162,159 @@ impl Layout {
     let mut path = Vec::new();
     while current != start_idx {
       path.push(current as u32);
-      current = came_from[current].unwrap();
+      if let Some(previous) = came_from[current] {
+        current = previous;
+      } else {
+        return None;
+      }
     }
     path.push(start_idx as u32);
     path.reverse();
     
-    path
+    Some(path)
+  }
+  
+  // Indices of the path tiles next to each path tile
+  fn path_neighbours(hexagons: &[Hexagon]) -> Vec<Vec<usize>> {
+    let mut index = HashMap::with_capacity(hexagons.len());
+    for (i, hexagon) in hexagons.iter().enumerate() {
+      index.insert((hexagon.q(), hexagon.r()), i);
+    }
+    
+    let mut path_neighbours: Vec<Vec<usize>> = Vec::with_capacity(hexagons.len());
+    for hexagon in hexagons {
+      let mut neighbours = Vec::new();
+      if hexagon.is_path() {
+        for next in &Hexagon::all_neighbours(hexagon) {
+          if let Some(&j) = index.get(&(next.q(), next.r())) {
+            if hexagons[j].is_path() {
+              neighbours.push(j);
+            }
+          }
+        }
+      }
+      path_neighbours.push(neighbours);
+    }
+    
+    path_neighbours
+  }
+  
+  // Breadth first from start along the path, never walking through another
+  // start or on past an end, the same way food does. Returns where each
+  // reached tile was reached from, skipping the blocked tile if there is one
+  fn search_path(hexagons: &[Hexagon], path_neighbours: &[Vec<usize>], start: usize, blocked: Option<usize>) -> Vec<Option<usize>> {
+    let mut came_from = vec![None; hexagons.len()];
+    came_from[start] = Some(start);
+    
+    let mut frontier = VecDeque::new();
+    frontier.push_back(start);
+    while let Some(current) = frontier.pop_front() {
+      if current != start && hexagons[current].is_end() {
+        continue;
+      }
+      
+      for &next in &path_neighbours[current] {
+        if came_from[next].is_some() || hexagons[next].is_start() || Some(next) == blocked {
+          continue;
+        }
+        
+        came_from[next] = Some(current);
+        frontier.push_back(next);
+      }
+    }
+    
+    came_from
+  }
+  
+  fn route_to(came_from: &[Option<usize>], start: usize, end: usize) -> Option<Vec<u32>> {
+    came_from[end]?;
+    
+    let mut route = vec!(end as u32);
+    let mut current = end;
+    while current != start {
+      current = came_from[current]?;
+      route.push(current as u32);
+    }
+    route.reverse();
+    
+    Some(route)
+  }
+  
+  // Every start has to reach an end and every end has to be reached from a
+  // start. Returns the index of the first start or end that isn't
+  pub fn check_routes(hexagons: &[Hexagon]) -> Result<(), usize> {
+    let path_neighbours = Layout::path_neighbours(hexagons);
+    let mut end_reached = vec![false; hexagons.len()];
+    
+    for i in 0..hexagons.len() {
+      if hexagons[i].is_start() {
+        let came_from = Layout::search_path(hexagons, &path_neighbours, i, None);
+        let mut reaches_end = false;
+        for j in 0..hexagons.len() {
+          if hexagons[j].is_end() && came_from[j].is_some() {
+            end_reached[j] = true;
+            reaches_end = true;
+          }
+        }
+        
+        if !reaches_end {
+          return Err(i);
+        }
+      }
+    }
+    
+    for i in 0..hexagons.len() {
+      if hexagons[i].is_end() && !end_reached[i] {
+        return Err(i);
+      }
+    }
+    
+    Ok(())
+  }
+  
+  // The shortest route from each start to each end it can reach, then as many
+  // detours as fit, each the shortest way round one tile of a shortest route.
+  // Shortest routes never step next to a tile they already passed, so wide
+  // lanes don't turn into zig zags. Every start gets its own share of routes.
+  // Returns the index of a start or end that isn't connected if there is one.
+  pub fn calculate_routes(hexagons: &[Hexagon]) -> Result<Vec<Vec<u32>>, usize> {
+    Layout::check_routes(hexagons)?;
+    
+    let path_neighbours = Layout::path_neighbours(hexagons);
+    let ends: Vec<usize> = (0..hexagons.len()).filter(|&i| hexagons[i].is_end()).collect();
+    
+    let mut routes = Vec::new();
+    for start in 0..hexagons.len() {
+      if !hexagons[start].is_start() {
+        continue;
+      }
+      
+      let came_from = Layout::search_path(hexagons, &path_neighbours, start, None);
+      let shortest: Vec<Vec<u32>> = ends.iter().filter_map(|&end| Layout::route_to(&came_from, start, end)).collect();
+      
+      let mut start_routes = shortest.clone();
+      start_routes.truncate(MAX_ROUTES_PER_START);
+      
+      'detours: for route in &shortest {
+        let end = route[route.len()-1] as usize;
+        for &tile in &route[1..route.len()-1] {
+          if start_routes.len() >= MAX_ROUTES_PER_START {
+            break 'detours;
+          }
+          
+          let came_from = Layout::search_path(hexagons, &path_neighbours, start, Some(tile as usize));
+          if let Some(detour) = Layout::route_to(&came_from, start, end) {
+            if !start_routes.contains(&detour) {
+              start_routes.push(detour);
+            }
+          }
+        }
+      }
+      
+      routes.append(&mut start_routes);
+    }
+    
+    Ok(routes)
   }
   
   pub fn pixel_to_hex(&self, pixel: Vector2<f32>) -> Hexagon {
@@ -203,6 +358,7 @@ pub struct Hexagon {
   position: Vector3<i32>,
   model: String,
   hex_type: HexagonType,
+  height: f32,
 }
 
 impl Hexagon {
@@ -211,6 +367,7 @@ impl Hexagon {
       position: Vector3::new(q, r, -q-r),
       model,
       hex_type: HexagonType::Open,
+      height: 0.0,
     }
   }
   
@@ -258,6 +415,23 @@ impl Hexagon {
     self.hex_type = HexagonType::Path;
   }
   
+  pub fn set_as_blocked(&mut self, model: String) {
+    self.model = model;
+    self.hex_type = HexagonType::Blocked;
+  }
+  
+  pub fn is_blocked(&self) -> bool {
+    self.hex_type == HexagonType::Blocked
+  }
+  
+  pub fn set_height(&mut self, height: f32) {
+    self.height = height;
+  }
+  
+  pub fn height(&self) -> f32 {
+    self.height
+  }
+  
   pub fn is_open(&self) -> bool {
     self.hex_type == HexagonType::Open
   }
@@ -282,6 +456,7 @@ impl Hexagon {
     (((self.position.x).abs() + (self.position.y).abs() + (self.position.z).abs()) as f32 * 0.5) as i32
   }
   
+  #[cfg(feature = "graphics")]
   pub fn draw_hologram_coloured(&self, map: &Map, layout: &Layout, y_pos: f32, height: f32, colour: Vector3<f32>, draw_calls: &mut Vec<DrawCall>) {
     let position = layout.hex_to_pixel(self);
     
@@ -291,6 +466,7 @@ impl Hexagon {
                                            colour));
   }
   
+  #[cfg(feature = "graphics")]
   pub fn draw_hologram(&self, map: &Map, layout: &Layout, y_pos: f32, height: f32, draw_calls: &mut Vec<DrawCall>) {
     let position = layout.hex_to_pixel(self);
     
@@ -299,6 +475,7 @@ impl Hexagon {
                                            Vector3::new(0.0, 90.0, 0.0)));
   }
   
+  #[cfg(feature = "graphics")]
   pub fn draw(&self, map: &Map, layout: &Layout, y_pos: f32, height: f32, draw_calls: &mut Vec<DrawCall>) {
     let position = layout.hex_to_pixel(self);
     
@@ -307,11 +484,12 @@ impl Hexagon {
                                            Vector3::new(0.0, 90.0, 0.0)));
   }
   
+  #[cfg(feature = "graphics")]
   pub fn draw_scaled(&self, map: &Map, layout: &Layout, model_size_org: Vector3<f32>, y_pos: f32, scale: f32, height: f32, draw_calls: &mut Vec<DrawCall>) {
     let height = 0.2;
     let mut position = layout.hex_to_pixel(self);
     let mut model_size = Vector3::new(model_size_org.x*2.025316456*scale, model_size_org.y*height, model_size_org.z*2.025316456*scale);
-    let mut y_pos = y_pos;
+    let mut y_pos = y_pos + self.height;
     if self.is_end() {
       y_pos -= 10.0;
     }
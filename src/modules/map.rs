@@ -1,14 +1,18 @@
+use std::fmt;
+use std::error::Error;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
 use crate::modules::hexagon::Hexagon;
 use crate::modules::hexagon::Layout;
 use crate::modules::hexagon::{HexDirection, HexagonType};
+use crate::modules::map_file::{MapFile, TileFile, TileKind, RouteSelection, MAP_FILE_VERSION};
 
+#[cfg(feature = "graphics")]
 use maat_graphics::DrawCall;
 
-use rand;
 use rand::Rng;
+use rand::rngs::StdRng;
 use cgmath::{InnerSpace, Vector2, Vector3};
 
 const TILE_DEFAULT_HEIGHT: f32 = 0.0;
@@ -16,11 +20,100 @@ const TILE_MAX_HEIGHT: f32 = 200.0;
 const TILE_MIN_HEIGHT: f32 = -700.0;
 const TILE_SPEED: f32 = 500.0;
 
+pub const DEFAULT_STARTING_MONEY: i32 = 300;
+pub const DEFAULT_BIN_CAPACITY: i32 = 100;
+pub const DEFAULT_WAVE_SET: &str = "default";
+
+#[derive(Debug)]
+pub enum MapError {
+  MissingFile(String),
+  BadFormat(String),
+  UnsupportedVersion { found: u32, supported: u32 },
+  InvalidTile { q: i32, r: i32, reason: String },
+  UnreadableLine { line: usize },
+  BadRadius { line: usize, text: String },
+  RowLength { line: usize, expected: usize, found: usize },
+  RowCount { line: usize, expected: usize, found: usize },
+  UnknownGlyph { line: usize, column: usize, glyph: String },
+  MissingStart,
+  MissingEnd,
+  DisconnectedPath { line: usize, column: usize },
+}
+
+impl fmt::Display for MapError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      MapError::MissingFile(map_name) => {
+        write!(f, "Cant find map file {}", map_name)
+      },
+      MapError::BadFormat(message) => {
+        write!(f, "Invalid map file: {}", message)
+      },
+      MapError::UnsupportedVersion { found, supported } => {
+        write!(f, "Map file version {} is newer than the supported version {}", found, supported)
+      },
+      MapError::InvalidTile { q, r, reason } => {
+        write!(f, "Tile q {} r {}: {}", q, r, reason)
+      },
+      MapError::UnreadableLine { line } => {
+        write!(f, "Line {}: unable to read line", line)
+      },
+      MapError::BadRadius { line, text } => {
+        write!(f, "Line {}: expected a radius of at least 1, found '{}'", line, text)
+      },
+      MapError::RowLength { line, expected, found } => {
+        write!(f, "Line {}: expected {} tiles in this row, found {}", line, expected, found)
+      },
+      MapError::RowCount { line, expected, found } => {
+        write!(f, "Line {}: expected {} rows, found {}", line, expected, found)
+      },
+      MapError::UnknownGlyph { line, column, glyph } => {
+        write!(f, "Line {}, column {}: unknown tile '{}'", line, column, glyph)
+      },
+      MapError::MissingStart => {
+        write!(f, "Map has no start tile 's'")
+      },
+      MapError::MissingEnd => {
+        write!(f, "Map has no end tile 'e'")
+      },
+      MapError::DisconnectedPath { line, column } => {
+        write!(f, "Line {}, column {}: no path joins this tile to both a start and an end", line, column)
+      },
+    }
+  }
+}
+
+impl Error for MapError {}
+
+#[derive(Clone)]
+pub struct MapInfo {
+  pub name: String,
+  pub author: String,
+  pub starting_money: i32,
+  pub bin_capacity: i32,
+  pub wave_set: String,
+  pub route_selection: RouteSelection,
+}
+
+impl MapInfo {
+  pub fn new(name: String) -> MapInfo {
+    MapInfo {
+      name,
+      author: "Unknown".to_string(),
+      starting_money: DEFAULT_STARTING_MONEY,
+      bin_capacity: DEFAULT_BIN_CAPACITY,
+      wave_set: DEFAULT_WAVE_SET.to_string(),
+      route_selection: RouteSelection::RoundRobin,
+    }
+  }
+}
+
 #[derive(Clone)]
 pub struct Map {
+  info: MapInfo,
   radius: i32,
   layout: Layout,
-  path: Vec<u32>,
+  routes: Vec<Vec<u32>>,
   map: Vec<Hexagon>,
   is_ready: bool,
   resetting: bool,
@@ -28,7 +121,7 @@ pub struct Map {
 }
 
 impl Map {
-  pub fn new_random_map(radius: i32, rng: &mut rand::prelude::ThreadRng) -> Map {
+  pub fn new_random_map(radius: i32, rng: &mut StdRng) -> Map {
     let mut radius = radius;
     if radius < 2 {
       radius = 2;
@@ -106,7 +199,9 @@ impl Map {
     
     let layout = Layout::new(Vector2::new(0.0, 0.0), Vector2::new(8.0, 8.0));
     
-    let path = Layout::calculate_path(&mut hexagons);
+    // The walk only ever steps onto path tiles so start and end are always connected
+    let path = Layout::calculate_path(&mut hexagons).expect("Random map path is disconnected");
+    let routes = vec!(path.clone());
     for i in 0..hexagons.len() {
       if !path.contains(&(i as u32)) {
         if !hexagons[i].is_start() && !hexagons[i].is_end() {
@@ -116,9 +211,10 @@ impl Map {
     }
     
     Map {
+      info: MapInfo::new("Random".to_string()),
       radius,
       layout,
-      path,
+      routes,
       map: hexagons,
       is_ready: false,
       resetting: false,
@@ -126,83 +222,319 @@ impl Map {
     }
   }
   
-  pub fn new(map_name: String) -> Map {
+  pub fn new(map_name: String) -> Result<Map, MapError> {
+    if map_name.ends_with(".ron") {
+      let map_file = MapFile::load(&("./resources/Maps/".to_owned() + &(map_name)))?;
+      return Map::from_map_file(map_file);
+    }
+    
+    let f = match File::open("./resources/Maps/".to_owned() + &(map_name)) {
+      Ok(f) => f,
+      Err(_) => return Err(MapError::MissingFile(map_name)),
+    };
+    let f = BufReader::new(f);
+    
     let mut hexagons: Vec<Hexagon> = Vec::new();
     
     let mut radius: i32 = 0;
     let mut offset = 1;
     let mut y = 0;
+    let mut last_line = 0;
+    
+    let mut starts = Vec::new();
+    let mut ends = Vec::new();
     
-    if let Ok(f) = File::open("./resources/Maps/".to_owned() + &(map_name)) {
-      println!("Settings file exists");
-      let f = BufReader::new(f);
+    for (i, line) in f.lines().enumerate() {
+      let line_number = i+1;
+      let line = match line {
+        Ok(line) => line,
+        Err(_) => return Err(MapError::UnreadableLine { line: line_number }),
+      };
+      let line = line.trim_end();
+      if line.trim() == "" {
+        continue;
+      }
+      last_line = line_number;
       
-      for line in f.lines() {
-        let line = line.expect("Unable to read line");
-        let v: Vec<&str> = line.split(" ").collect();
-        if radius == 0 {
-          radius = v[0].parse::<i32>().unwrap();
-          y = -radius;
-          hexagons = Hexagon::generate_hexagon_range(radius, "Hexagon".to_string());
-          continue;
+      if radius == 0 {
+        radius = match line.trim().parse::<i32>() {
+          Ok(r) if r > 0 => r,
+          _ => return Err(MapError::BadRadius { line: line_number, text: line.trim().to_string() }),
+        };
+        y = -radius;
+        hexagons = Hexagon::generate_hexagon_range(radius, "Hexagon".to_string());
+        continue;
+      }
+      
+      let total_rows = (radius*2+1) as usize;
+      if y > radius {
+        return Err(MapError::RowCount { line: line_number, expected: total_rows, found: total_rows+1 });
+      }
+      
+      // Columns are counted in characters so they match what a text editor shows
+      let mut glyphs = Vec::new();
+      let mut column = 1;
+      for glyph in line.split(" ") {
+        if glyph != "" {
+          glyphs.push((column, glyph));
         }
+        column += glyph.chars().count()+1;
+      }
+      
+      let row_length = (radius*2+1 - y.abs()) as usize;
+      if glyphs.len() != row_length {
+        return Err(MapError::RowLength { line: line_number, expected: row_length, found: glyphs.len() });
+      }
+      
+      for x in 0..glyphs.len() {
+        let (column, glyph) = glyphs[x];
+        let q = (x+1) as i32 - offset;
+        let r = y as i32;
+        
+        let hex = Hexagon::new(q,r,"".to_string());
+        
+        let mut hex_idx = 0;
         
-        let mut x = 0;
-        for i in 0..v.len() {
-          if v[i] != "" {
-            x += 1;
-            let q = x as i32- offset;
-            let r = y as i32;
-            
-            let hex = Hexagon::new(q,r,"".to_string());
-            
-            let mut hex_idx = 0;
-            
-            for i in 0..hexagons.len() {
-              if Hexagon::hex_equals(&hexagons[i], &hex) {
-                hex_idx = i;
-                break;
-              }
-            }
-            
-            if v[i] == "s" {
-              hexagons[hex_idx].set_as_start();
-              println!("start q {} r {}", hexagons[hex_idx].q(), hexagons[hex_idx].r());
-            }
-            
-            if v[i] == "0" {
-              hexagons[hex_idx].set_as_path();
-            }
-            
-            if v[i] == "e" {
-              hexagons[hex_idx].set_as_end();
-              println!("end q {} r {}", hexagons[hex_idx].q(), hexagons[hex_idx].r());
-            }
+        for i in 0..hexagons.len() {
+          if Hexagon::hex_equals(&hexagons[i], &hex) {
+            hex_idx = i;
+            break;
           }
         }
-        y+=1;
-        if y < 0 {
-          offset += 1;
-        } else if y == 0 {
-          offset += 1;
+        
+        match glyph {
+          "x" => {},
+          "0" => {
+            hexagons[hex_idx].set_as_path();
+          },
+          "s" => {
+            hexagons[hex_idx].set_as_start();
+            println!("start q {} r {}", hexagons[hex_idx].q(), hexagons[hex_idx].r());
+            starts.push((hex_idx, line_number, column));
+          },
+          "e" => {
+            hexagons[hex_idx].set_as_end();
+            println!("end q {} r {}", hexagons[hex_idx].q(), hexagons[hex_idx].r());
+            ends.push((hex_idx, line_number, column));
+          },
+          _ => {
+            return Err(MapError::UnknownGlyph { line: line_number, column, glyph: glyph.to_string() });
+          },
         }
       }
-    } else {
-      panic!("Cant find map file");
+      
+      y+=1;
+      if y <= 0 {
+        offset += 1;
+      }
+    }
+    
+    if radius == 0 {
+      return Err(MapError::BadRadius { line: last_line.max(1), text: "".to_string() });
+    }
+    
+    if y <= radius {
+      let total_rows = (radius*2+1) as usize;
+      return Err(MapError::RowCount { line: last_line, expected: total_rows, found: (y+radius) as usize });
+    }
+    
+    if starts.len() == 0 {
+      return Err(MapError::MissingStart);
+    }
+    
+    if ends.len() == 0 {
+      return Err(MapError::MissingEnd);
     }
     
     let layout = Layout::new(Vector2::new(0.0, 0.0), Vector2::new(8.0, 8.0));
     
-    let path = Layout::calculate_path(&mut hexagons);
+    let routes = match Layout::calculate_routes(&hexagons) {
+      Ok(routes) => routes,
+      Err(hex_idx) => {
+        let mut location = (0, 0);
+        for &(idx, line, column) in starts.iter().chain(ends.iter()) {
+          if idx == hex_idx {
+            location = (line, column);
+          }
+        }
+        return Err(MapError::DisconnectedPath { line: location.0, column: location.1 });
+      },
+    };
     
-    Map {
+    let name = map_name.trim_end_matches(".ini").to_string();
+    
+    Ok(Map {
+      info: MapInfo::new(name),
       radius: radius,
       layout,
-      path,
+      routes,
       map: hexagons,
       is_ready: false,
       resetting: false,
       tile_delta: TILE_MAX_HEIGHT,
+    })
+  }
+  
+  pub fn new_empty(radius: i32, info: MapInfo) -> Map {
+    let layout = Layout::new(Vector2::new(0.0, 0.0), Vector2::new(8.0, 8.0));
+    
+    Map {
+      info,
+      radius,
+      layout,
+      routes: Vec::new(),
+      map: Hexagon::generate_hexagon_range(radius, "Hexagon".to_string()),
+      is_ready: false,
+      resetting: false,
+      tile_delta: TILE_MAX_HEIGHT,
+    }
+  }
+  
+  pub fn from_map_file(map_file: MapFile) -> Result<Map, MapError> {
+    if map_file.version > MAP_FILE_VERSION {
+      return Err(MapError::UnsupportedVersion { found: map_file.version, supported: MAP_FILE_VERSION });
+    }
+    
+    if map_file.radius < 1 {
+      return Err(MapError::BadFormat(format!("expected a radius of at least 1, found {}", map_file.radius)));
+    }
+    
+    let info = MapInfo {
+      name: map_file.name,
+      author: map_file.author,
+      starting_money: map_file.starting_money,
+      bin_capacity: map_file.bin_capacity,
+      wave_set: map_file.wave_set,
+      route_selection: map_file.route_selection,
+    };
+    
+    let mut map = Map::new_empty(map_file.radius, info);
+    
+    for tile in &map_file.tiles {
+      if !map.set_tile(tile.q, tile.r, &tile.kind) {
+        return Err(MapError::InvalidTile { q: tile.q, r: tile.r, reason: "outside of the map radius".to_string() });
+      }
+      
+      if let Some(idx) = map.get_index_from_qr(tile.q, tile.r) {
+        map.map[idx].set_height(tile.height);
+      }
+    }
+    
+    map.recalculate_routes()?;
+    
+    Ok(map)
+  }
+  
+  // Returns false if q r is outside of the map
+  pub fn set_tile(&mut self, q: i32, r: i32, kind: &TileKind) -> bool {
+    let hex_idx = match self.get_index_from_qr(q, r) {
+      Some(idx) => idx,
+      None => return false,
+    };
+    
+    match kind {
+      TileKind::Open => {
+        self.map[hex_idx].plain();
+      },
+      TileKind::Path => {
+        self.map[hex_idx].set_as_path();
+      },
+      TileKind::Start => {
+        self.map[hex_idx].set_as_start();
+      },
+      TileKind::End => {
+        self.map[hex_idx].set_as_end();
+      },
+      TileKind::Blocked => {
+        self.map[hex_idx].set_as_blocked("Hexagon".to_string());
+      },
+      TileKind::Decoration(model) => {
+        self.map[hex_idx].set_as_blocked(model.to_string());
+      },
+    }
+    
+    true
+  }
+  
+  // Whether every start can reach an end and every end can be reached, without
+  // building any routes. Cheap enough to run after every tile change
+  pub fn check_routes(&self) -> Result<(), MapError> {
+    if !self.map.iter().any(|hex| hex.is_start()) {
+      return Err(MapError::MissingStart);
+    }
+    
+    if !self.map.iter().any(|hex| hex.is_end()) {
+      return Err(MapError::MissingEnd);
+    }
+    
+    Layout::check_routes(&self.map).map_err(|hex_idx| self.disconnected_tile(hex_idx))
+  }
+  
+  fn disconnected_tile(&self, hex_idx: usize) -> MapError {
+    let q = self.map[hex_idx].q();
+    let r = self.map[hex_idx].r();
+    MapError::InvalidTile { q, r, reason: "no path joins this tile to both a start and an end".to_string() }
+  }
+  
+  // Rebuilds the routes after tiles have changed, leaving the map with no
+  // routes if it isn't playable. Returns how many routes were found.
+  pub fn recalculate_routes(&mut self) -> Result<usize, MapError> {
+    self.routes.clear();
+    self.check_routes()?;
+    
+    match Layout::calculate_routes(&self.map) {
+      Ok(routes) => {
+        self.routes = routes;
+        Ok(self.routes.len())
+      },
+      Err(hex_idx) => Err(self.disconnected_tile(hex_idx)),
+    }
+  }
+  
+  pub fn to_map_file(&self) -> MapFile {
+    let mut tiles = Vec::new();
+    
+    for hexagon in &self.map {
+      let kind = {
+        if hexagon.is_start() {
+          TileKind::Start
+        } else if hexagon.is_end() {
+          TileKind::End
+        } else if hexagon.is_path() {
+          TileKind::Path
+        } else if hexagon.is_blocked() {
+          if hexagon.get_model() == "Hexagon" {
+            TileKind::Blocked
+          } else {
+            TileKind::Decoration(hexagon.get_model())
+          }
+        } else {
+          TileKind::Open
+        }
+      };
+      
+      if kind == TileKind::Open && hexagon.height() == 0.0 {
+        continue;
+      }
+      
+      tiles.push(TileFile {
+        q: hexagon.q(),
+        r: hexagon.r(),
+        kind,
+        height: hexagon.height(),
+      });
+    }
+    
+    MapFile {
+      version: MAP_FILE_VERSION,
+      name: self.info.name.to_string(),
+      author: self.info.author.to_string(),
+      starting_money: self.info.starting_money,
+      bin_capacity: self.info.bin_capacity,
+      wave_set: self.info.wave_set.to_string(),
+      route_selection: self.info.route_selection.clone(),
+      radius: self.radius,
+      tiles,
     }
   }
   
@@ -223,6 +555,7 @@ impl Map {
     }
   }
   
+  #[cfg(feature = "graphics")]
   pub fn draw(&self, hexagon_size: Vector3<f32>, cam_pos: Vector2<f32>, draw_calls: &mut Vec<DrawCall>) {
     let cam_hex = self.pixel_to_hex(cam_pos);
     
@@ -263,23 +596,41 @@ impl Map {
       self.resetting = true;
       self.tile_delta = TILE_MIN_HEIGHT;
       for hexagon in &mut self.map {
-        if !hexagon.is_path() {
+        if !hexagon.is_path() && !hexagon.is_blocked() {
           hexagon.set_type(HexagonType::Open);
         }
       }
     }
   }
   
+  pub fn skip_animation(&mut self) {
+    self.tile_delta = TILE_DEFAULT_HEIGHT;
+    self.resetting = false;
+    self.is_ready = true;
+  }
+  
   pub fn is_ready(&self) -> bool {
     self.is_ready
   }
   
+  pub fn get_info(&self) -> &MapInfo {
+    &self.info
+  }
+  
+  pub fn set_name(&mut self, name: String) {
+    self.info.name = name;
+  }
+  
   pub fn get_radius(&self) -> i32 {
     self.radius
   }
   
-  pub fn get_path(&self) -> Vec<u32> {
-    self.path.clone()
+  pub fn get_hexagons(&self) -> &Vec<Hexagon> {
+    &self.map
+  }
+  
+  pub fn get_routes(&self) -> &Vec<Vec<u32>> {
+    &self.routes
   }
   
   pub fn get_layout(&self) -> Layout {
@@ -349,7 +700,7 @@ impl Map {
   
   pub fn highlight_hex(&mut self, light_hex: Hexagon) {
     for hexagon in &mut self.map {
-      if Hexagon::hex_equals(&light_hex, &hexagon) {
+      if Hexagon::hex_equals(&light_hex, &hexagon) && !hexagon.is_blocked() {
         hexagon.highlight();
       }
     }
@@ -0,0 +1,111 @@
+use crate::modules::map::MapError;
+
+use serde::{Serialize, Deserialize};
+
+use ron;
+use ron::ser::PrettyConfig;
+
+use std::fs;
+use std::path::Path;
+
+pub const MAP_FILE_VERSION: u32 = 1;
+pub const MAP_DIRECTORY: &str = "./resources/Maps/";
+
+// Maps saved from the editor are numbered name, name2, name3... These are the
+// ones saved so far, oldest first
+pub fn numbered_map_names(directory: &str, name: &str) -> Vec<String> {
+  let mut names = Vec::new();
+  loop {
+    let candidate = numbered_map_name(name, names.len()+1);
+    if !Path::new(&(directory.to_owned() + &candidate + ".ron")).exists() {
+      return names;
+    }
+    names.push(candidate);
+  }
+}
+
+// The next name in the sequence, which nothing has been saved to yet
+pub fn unused_map_name(directory: &str, name: &str) -> String {
+  numbered_map_name(name, numbered_map_names(directory, name).len()+1)
+}
+
+fn numbered_map_name(name: &str, number: usize) -> String {
+  if number == 1 {
+    name.to_string()
+  } else {
+    name.to_owned() + &number.to_string()
+  }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum TileKind {
+  Start,
+  End,
+  Path,
+  Blocked,
+  // Blocked tile drawn with a different model
+  Decoration(String),
+  // Only present to carry a height for an otherwise open tile
+  Open,
+}
+
+// How each food picks which of the map's routes to walk
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum RouteSelection {
+  Random,
+  RoundRobin,
+  // Every food in a wave takes the same route, the next wave takes the next one
+  PerWave,
+}
+
+impl Default for RouteSelection {
+  fn default() -> RouteSelection {
+    RouteSelection::RoundRobin
+  }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TileFile {
+  pub q: i32,
+  pub r: i32,
+  pub kind: TileKind,
+  #[serde(default)]
+  pub height: f32,
+}
+
+// Any tile not listed is an open tile at height 0
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MapFile {
+  pub version: u32,
+  pub name: String,
+  pub author: String,
+  pub starting_money: i32,
+  pub bin_capacity: i32,
+  pub wave_set: String,
+  #[serde(default)]
+  pub route_selection: RouteSelection,
+  pub radius: i32,
+  pub tiles: Vec<TileFile>,
+}
+
+impl MapFile {
+  pub fn load(path: &str) -> Result<MapFile, MapError> {
+    let text = match fs::read_to_string(path) {
+      Ok(text) => text,
+      Err(_) => return Err(MapError::MissingFile(path.to_string())),
+    };
+    
+    ron::de::from_str(&text).map_err(|e| MapError::BadFormat(e.to_string()))
+  }
+  
+  pub fn save(&self, path: &str) -> Result<(), String> {
+    // Keeps each tile on a single line
+    let config = PrettyConfig {
+      depth_limit: 3,
+      .. PrettyConfig::default()
+    };
+    
+    let text = ron::ser::to_string_pretty(self, config).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| e.to_string())
+  }
+}
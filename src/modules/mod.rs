@@ -1,10 +1,21 @@
+#[cfg(feature = "graphics")]
 pub mod system_interface;
+#[cfg(feature = "graphics")]
 pub mod scenes;
+pub mod simulation;
 pub mod physics;
 pub mod update;
 pub mod map;
+pub mod map_file;
+pub mod wave_file;
+pub mod save_game;
+pub mod replay;
+pub mod command;
 pub mod food;
 pub mod appliances;
 pub mod hexagon;
 pub mod weapons;
 pub mod thefoodstore;
+pub mod food_index;
+pub mod settings;
+pub mod undo;
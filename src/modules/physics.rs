@@ -1,65 +1,48 @@
 use crate::modules::map::Map;
-use crate::modules::food::Food;
+use crate::modules::food::{Food, FoodIds};
 use crate::modules::weapons::Weapon;
-use crate::modules::hexagon::Hexagon;
+use crate::modules::food_index::FoodIndex;
+use crate::modules::simulation::GameStats;
 
 use cgmath::{Vector3};
 
-pub fn collisions(map: &Map, foods: &mut Vec<Box<Food>>, weapons: &mut Vec<Box<Weapon>>, model_sizes: &mut Vec<(String, Vector3<f32>)>, bin: &mut i32, money: &mut i32, _delta_time: f32) {
-  for food in &mut foods.iter_mut() {
-    //let food_tile = food.get_tile_location();
-    //let hex = Hexagon::new(food_tile.x, food_tile.y, "".to_string());
-    let food_pos = food.get_location();
-    let hex = map.pixel_to_hex(food_pos);
+pub fn collisions(map: &Map, foods: &mut Vec<Box<Food>>, weapons: &mut Vec<Box<Weapon>>, model_sizes: &mut Vec<(String, Vector3<f32>)>, bin: &mut i32, money: &mut i32, stats: &mut GameStats, food_ids: &mut FoodIds, _delta_time: f32) {
+  // Each weapon only checks the foods standing on the same hex as it
+  let food_index = FoodIndex::new(foods, map);
+  for weapon in &mut weapons.iter_mut() {
+    let w_hex = weapon.get_hexagon(map);
     
-    for weapon in &mut weapons.iter_mut() {
-      if weapon.hasnt_hit(food.get_id()) {
-        let w_hex = weapon.get_hexagon(map);
-        
-        if Hexagon::hex_equals(&hex, &w_hex) {
-          weapon.hit_target(food);
-        }
-      }
-    }
-    
-    let mut offset = 0;
-    for i in 0..weapons.len() {
-      if offset > i {
+    for idx in food_index.foods_on(&w_hex) {
+      if weapon.is_broken() {
         break;
       }
       
-      if weapons[i-offset].is_broken() {
-        weapons.remove(i-offset);
-        offset += 1;
+      if weapon.can_hit(foods[*idx].get_id()) {
+        weapon.hit_target(&mut foods[*idx]);
       }
     }
   }
   
-  let mut food_children = Vec::new();
+  weapons.retain(|weapon| !weapon.is_broken());
   
-  let mut offset = 0;
-  for i in 0..foods.len() {
-    if offset > i {
-      break;
-    }
-    
-    if foods[i-offset].is_cooked() || foods[i-offset].is_rotten() {
-      let mut rotted = false;
-      if foods[i-offset].is_rotten() {
-        // Do something
-        *bin += foods[i-offset].get_bin_space();
-        rotted = true;
-      } else {
-        *money += foods[i-offset].sell_price();
-      }
+  let mut food_children = Vec::new();
+  foods.retain(|food| {
+    if food.is_rotten() {
+      *bin += food.get_bin_space();
+      stats.foods_binned += 1;
+      false
+    } else if food.is_cooked() {
+      let price = food.sell_price();
+      *money += price;
+      stats.money_earned += price;
+      stats.foods_cooked += 1;
       
-      if !rotted {
-        food_children.append(&mut foods[i-offset].get_children(map));
-      }
-      foods.remove(i-offset);
-      offset += 1;
+      food_children.append(&mut food.get_children(map, food_ids));
+      false
+    } else {
+      true
     }
-  }
+  });
   
   foods.append(&mut food_children);
 }
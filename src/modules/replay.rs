@@ -0,0 +1,150 @@
+use crate::modules::simulation::Simulation;
+use crate::modules::map::{Map, MapError};
+use crate::modules::map_file::MapFile;
+use crate::modules::wave_file::WaveFile;
+use crate::modules::command::{GameCommand, LEGACY_PAUSE_SPEED};
+
+use serde::{Serialize, Deserialize};
+
+use ron;
+use ron::ser::PrettyConfig;
+
+use std::fs;
+use std::path::Path;
+
+pub const REPLAY_FILE_VERSION: u32 = 1;
+pub const REPLAY_FILE: &str = "./replays/last.ron";
+
+// A game from its first tick, the commands are paired with the tick they
+// were made before
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+  pub version: u32,
+  pub map: MapFile,
+  pub waves: WaveFile,
+  pub seed: u64,
+  pub endless: bool,
+  // Undos have to be allowed exactly as long as they were when recorded
+  #[serde(default)]
+  pub undo_window: Option<f32>,
+  pub commands: Vec<(u64, GameCommand)>,
+}
+
+impl Replay {
+  // simulation has to be a fresh game
+  pub fn new(simulation: &Simulation) -> Replay {
+    Replay {
+      version: REPLAY_FILE_VERSION,
+      map: simulation.map.to_map_file(),
+      waves: simulation.waves.clone(),
+      seed: simulation.seed(),
+      endless: simulation.is_endless(),
+      undo_window: Some(simulation.undo_window),
+      commands: Vec::new(),
+    }
+  }
+  
+  pub fn record(&mut self, tick: u64, command: GameCommand) {
+    self.commands.push((tick, command));
+  }
+  
+  pub fn exists() -> bool {
+    Path::new(REPLAY_FILE).exists()
+  }
+  
+  pub fn load() -> Result<Replay, String> {
+    let text = fs::read_to_string(REPLAY_FILE).map_err(|e| e.to_string())?;
+    let replay: Replay = ron::de::from_str(&text).map_err(|e| e.to_string())?;
+    
+    if replay.version > REPLAY_FILE_VERSION {
+      return Err("Replay file version ".to_owned() + &replay.version.to_string() +
+                 " is newer than the supported version " + &REPLAY_FILE_VERSION.to_string());
+    }
+    
+    Ok(replay)
+  }
+  
+  pub fn save(&self) -> Result<(), String> {
+    if let Some(folder) = Path::new(REPLAY_FILE).parent() {
+      fs::create_dir_all(folder).map_err(|e| e.to_string())?;
+    }
+    
+    let config = PrettyConfig {
+      depth_limit: 2,
+      .. PrettyConfig::default()
+    };
+    
+    let text = ron::ser::to_string_pretty(self, config).map_err(|e| e.to_string())?;
+    fs::write(REPLAY_FILE, text).map_err(|e| e.to_string())
+  }
+  
+  pub fn to_simulation(&self) -> Result<Simulation, MapError> {
+    let map = Map::from_map_file(self.map.clone())?;
+    
+    let mut simulation = Simulation::new(map, self.waves.clone(), self.seed);
+    if self.endless {
+      simulation.set_endless();
+    }
+    if let Some(undo_window) = self.undo_window {
+      simulation.undo_window = undo_window;
+    }
+    simulation.skip_map_animation();
+    
+    Ok(simulation)
+  }
+}
+
+// Hands back the recorded commands as the simulation reaches their ticks
+pub struct ReplayPlayer {
+  replay: Replay,
+  next_command: usize,
+  pause_change: Option<bool>,
+}
+
+impl ReplayPlayer {
+  pub fn new(replay: Replay) -> ReplayPlayer {
+    ReplayPlayer {
+      replay,
+      next_command: 0,
+      pause_change: None,
+    }
+  }
+  
+  pub fn due_commands(&mut self, tick: u64) -> Vec<GameCommand> {
+    let mut commands = Vec::new();
+    while self.next_command < self.replay.commands.len() && self.replay.commands[self.next_command].0 <= tick {
+      let command = self.replay.commands[self.next_command].1.clone();
+      self.next_command += 1;
+      
+      // Old replays paused with a speed the simulation no longer takes, so
+      // it is kept back here. Any other speed resumed them
+      match command {
+        GameCommand::Speed(LEGACY_PAUSE_SPEED) => {
+          self.pause_change = Some(true);
+        },
+        GameCommand::Speed(_) => {
+          self.pause_change = Some(false);
+          commands.push(command);
+        },
+        _ => {
+          commands.push(command);
+        },
+      }
+    }
+    
+    commands
+  }
+  
+  // Some(paused) if the recording paused or resumed since this was last asked
+  pub fn take_pause_change(&mut self) -> Option<bool> {
+    self.pause_change.take()
+  }
+  
+  pub fn commands_played(&self) -> usize {
+    self.next_command
+  }
+  
+  pub fn total_commands(&self) -> usize {
+    self.replay.commands.len()
+  }
+}
@@ -0,0 +1,151 @@
+use crate::modules::simulation::{Simulation, GameStats};
+use crate::modules::map::{Map, MapError};
+use crate::modules::map_file::MapFile;
+use crate::modules::wave_file::WaveFile;
+use crate::modules::food::{SavedFood, FoodIds};
+use crate::modules::appliances::traits::SavedAppliance;
+use crate::modules::weapons::SavedWeapon;
+use crate::modules::hexagon::HexagonType;
+use crate::modules::replay::Replay;
+
+use serde::{Serialize, Deserialize};
+
+use ron;
+use ron::ser::PrettyConfig;
+
+use std::fs;
+use std::path::Path;
+
+pub const SAVE_FILE_VERSION: u32 = 1;
+pub const SAVE_FILE: &str = "./saves/game.ron";
+
+// Everything needed to carry on a game where it was left. Waves aren't stored
+// food by food, they are rebuilt from the wave set and seed instead
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+  pub version: u32,
+  pub map: MapFile,
+  pub waves: WaveFile,
+  pub seed: u64,
+  pub endless: bool,
+  pub wave: usize,
+  pub wave_idx: usize,
+  pub wave_delta: f32,
+  pub appliances: Vec<SavedAppliance>,
+  pub foods: Vec<SavedFood>,
+  pub weapons: Vec<SavedWeapon>,
+  pub money: i32,
+  pub bin: i32,
+  pub stats: GameStats,
+  pub game_speed: i32,
+  #[serde(default)]
+  pub ticks: u64,
+  // Older saves didn't keep this, ids carry on from the foods in the save
+  #[serde(default)]
+  pub food_ids: Option<FoodIds>,
+  // The game so far, so a continued game keeps recording where it left off
+  #[serde(default)]
+  pub replay: Option<Replay>,
+}
+
+impl SaveGame {
+  pub fn new(simulation: &Simulation, game_speed: i32, replay: Option<Replay>) -> SaveGame {
+    let (wave, wave_idx, wave_delta) = simulation.the_food_store.progress();
+    let routes = simulation.the_food_store.routes();
+    
+    SaveGame {
+      version: SAVE_FILE_VERSION,
+      map: simulation.map.to_map_file(),
+      waves: simulation.waves.clone(),
+      seed: simulation.seed(),
+      endless: simulation.is_endless(),
+      wave,
+      wave_idx,
+      wave_delta,
+      appliances: simulation.appliances.iter().map(|a| a.save()).collect(),
+      foods: simulation.foods.iter().map(|f| SavedFood::new(&**f, &routes)).collect(),
+      weapons: simulation.weapons.iter().map(|w| w.save()).collect(),
+      money: simulation.money,
+      bin: simulation.bin,
+      stats: simulation.stats.clone(),
+      game_speed,
+      ticks: simulation.ticks,
+      food_ids: Some(simulation.food_ids.clone()),
+      replay,
+    }
+  }
+  
+  pub fn exists() -> bool {
+    Path::new(SAVE_FILE).exists()
+  }
+  
+  pub fn delete() {
+    if SaveGame::exists() {
+      if let Err(e) = fs::remove_file(SAVE_FILE) {
+        println!("Unable to remove {}: {}", SAVE_FILE, e);
+      }
+    }
+  }
+  
+  pub fn load() -> Result<SaveGame, String> {
+    let text = fs::read_to_string(SAVE_FILE).map_err(|e| e.to_string())?;
+    let save: SaveGame = ron::de::from_str(&text).map_err(|e| e.to_string())?;
+    
+    if save.version > SAVE_FILE_VERSION {
+      return Err("Save file version ".to_owned() + &save.version.to_string() +
+                 " is newer than the supported version " + &SAVE_FILE_VERSION.to_string());
+    }
+    
+    Ok(save)
+  }
+  
+  pub fn save(&self) -> Result<(), String> {
+    if let Some(folder) = Path::new(SAVE_FILE).parent() {
+      fs::create_dir_all(folder).map_err(|e| e.to_string())?;
+    }
+    
+    let config = PrettyConfig {
+      depth_limit: 2,
+      .. PrettyConfig::default()
+    };
+    
+    let text = ron::ser::to_string_pretty(self, config).map_err(|e| e.to_string())?;
+    fs::write(SAVE_FILE, text).map_err(|e| e.to_string())
+  }
+  
+  pub fn into_simulation(self) -> Result<Simulation, MapError> {
+    let map = Map::from_map_file(self.map)?;
+    
+    let mut simulation = Simulation::new(map, self.waves, self.seed);
+    if self.endless {
+      simulation.set_endless();
+    }
+    simulation.the_food_store.set_progress(self.wave, self.wave_idx, self.wave_delta);
+    
+    simulation.appliances = self.appliances.into_iter().map(|a| a.restore()).collect();
+    for appliance in &simulation.appliances {
+      let qr = appliance.get_qr_location();
+      simulation.map.set_hexagon_type(qr.x, qr.y, HexagonType::Closed);
+    }
+    
+    let routes = simulation.the_food_store.routes();
+    simulation.foods = self.foods.into_iter().filter_map(|f| {
+      let food = f.restore(&routes);
+      if food.is_none() {
+        println!("Dropped a saved food, its route isn't on the map");
+      }
+      food
+    }).collect();
+    simulation.weapons = self.weapons.into_iter().map(|w| w.restore()).collect();
+    simulation.money = self.money;
+    simulation.bin = self.bin;
+    simulation.stats = self.stats;
+    simulation.ticks = self.ticks;
+    simulation.food_ids = match self.food_ids {
+      Some(food_ids) => food_ids,
+      None => FoodIds::after(&simulation.foods),
+    };
+    
+    Ok(simulation)
+  }
+}
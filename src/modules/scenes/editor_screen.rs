@@ -0,0 +1,406 @@
+use maat_graphics::DrawCall;
+use maat_graphics::camera;
+
+use crate::modules::scenes::Scene;
+use crate::modules::scenes::SceneData;
+use crate::modules::scenes::MenuScreen;
+use crate::modules::scenes::GameScreen;
+
+use crate::modules::system_interface::Widget;
+use crate::modules::map::Map;
+use crate::modules::map_file::{self, TileKind, MAP_DIRECTORY};
+use crate::modules::wave_file::WaveFile;
+use crate::modules::simulation::Simulation;
+use crate::modules::settings::{Settings, KeyBindings, KeyAction};
+
+use rand::{thread_rng, Rng};
+
+use cgmath::{Vector2, Vector3, Vector4};
+
+// Custom maps are saved as CustomMap.ron, CustomMap2.ron and so on
+pub const EDITOR_MAP_NAME: &str = "CustomMap";
+
+const CAMERA_DEFAULT_X: f32 = 83.93359;
+const CAMERA_DEFAULT_Y: f32 = 128.62776;
+const CAMERA_DEFAULT_Z: f32 = 55.85842;
+const CAMERA_DEFAULT_PITCH: f32 = -62.27426;
+const CAMERA_DEFAULT_YAW: f32 = 210.10083;
+const CAMERA_DEFAULT_SPEED: f32 = 50.0;
+
+const TOOLBAR_INDEX: usize = 0;
+
+const TOOLBAR_NAME: &str = "EditorToolbar";
+const BRUSH_NAME: &str = "Brush";
+const STATUS_NAME: &str = "Status";
+const OPEN_BUTTON_NAME: &str = "OpenBrush";
+const PATH_BUTTON_NAME: &str = "PathBrush";
+const BLOCKED_BUTTON_NAME: &str = "BlockedBrush";
+const START_BUTTON_NAME: &str = "StartBrush";
+const END_BUTTON_NAME: &str = "EndBrush";
+const SAVE_BUTTON_NAME: &str = "Save";
+const SAVE_NEW_BUTTON_NAME: &str = "SaveNew";
+const PLAY_BUTTON_NAME: &str = "Play";
+const MENU_BUTTON_NAME: &str = "Menu";
+
+enum NextScene {
+  Menu,
+  Play(WaveFile),
+}
+
+pub struct EditorScreen {
+  data: SceneData,
+  camera: camera::Camera,
+  last_mouse_pos: Vector2<f32>,
+  map: Map,
+  brush: TileKind,
+  valid: bool,
+  widgets: Vec<Widget>,
+  next: NextScene,
+  key_bindings: KeyBindings,
+  // Without the .ron
+  file_name: String,
+}
+
+impl EditorScreen {
+  pub fn new(window_size: Vector2<f32>, model_sizes: Vec<(String, Vector3<f32>)>, map: Map, file_name: String) -> EditorScreen {
+    println!("Editor Screen");
+    
+    let mut camera = camera::Camera::default_vk();
+    camera.set_position(Vector3::new(CAMERA_DEFAULT_X, CAMERA_DEFAULT_Y, CAMERA_DEFAULT_Z));
+    camera.set_pitch(CAMERA_DEFAULT_PITCH);
+    camera.set_yaw(CAMERA_DEFAULT_YAW);
+    camera.set_move_speed(CAMERA_DEFAULT_SPEED);
+    
+    let mut map = map;
+    map.skip_animation();
+    
+    let toolbar_width = window_size.x;
+    let toolbar_height = 96.0;
+    
+    let button_width = 96.0;
+    let button_height = 32.0;
+    let button_spacing = button_width*1.1;
+    
+    let text_colour = Vector4::new(0.0, 0.0, 0.0, 1.0);
+    let button_colour = Vector4::new(0.5019, 0.749, 1.0, 1.0);
+    let pressed_colour = Vector4::new(1.0, 0.0, 0.0, 1.0);
+    let background_colour = Vector4::new(0.0, 0.1411, 0.4, 1.0);
+    
+    let mut toolbar = Widget::new(TOOLBAR_NAME.to_string(), Vector2::new(window_size.x*0.5, toolbar_height*0.5),
+                                  Vector2::new(toolbar_width, toolbar_height),
+                                  background_colour)
+                        .with_text_field(BRUSH_NAME.to_string(),
+                                         Vector2::new(16.0, toolbar_height-32.0),
+                                         Vector2::new(96.0, 96.0),
+                                         Vector4::new(1.0, 1.0, 1.0, 1.0),
+                                         "Brush: Path".to_string(), "Arial".to_string())
+                        .with_text_field(STATUS_NAME.to_string(),
+                                         Vector2::new(16.0, 8.0),
+                                         Vector2::new(96.0, 96.0),
+                                         Vector4::new(1.0, 1.0, 1.0, 1.0),
+                                         "".to_string(), "Arial".to_string());
+    
+    let key_bindings = Settings::load().key_bindings;
+    let brush_text = |action, brush| key_bindings.key(action).to_owned() + " " + brush;
+    
+    let buttons = vec!(
+      (OPEN_BUTTON_NAME, brush_text(KeyAction::OpenBrush, "Open")),
+      (PATH_BUTTON_NAME, brush_text(KeyAction::PathBrush, "Path")),
+      (BLOCKED_BUTTON_NAME, brush_text(KeyAction::BlockedBrush, "Blocked")),
+      (START_BUTTON_NAME, brush_text(KeyAction::StartBrush, "Start")),
+      (END_BUTTON_NAME, brush_text(KeyAction::EndBrush, "End")),
+      (SAVE_BUTTON_NAME, "Save".to_string()),
+      (SAVE_NEW_BUTTON_NAME, "Save New".to_string()),
+      (PLAY_BUTTON_NAME, "Play".to_string()),
+      (MENU_BUTTON_NAME, "Menu".to_string()),
+    );
+    
+    for i in 0..buttons.len() {
+      let (name, ref text) = buttons[i];
+      toolbar = toolbar.with_button(Vector2::new(toolbar_width-button_spacing*(buttons.len()-i) as f32, toolbar_height*0.5),
+                                    Vector2::new(button_width, button_height),
+                                    Vector2::new(button_width*0.5, button_height*0.33),
+                                    Vector2::new(96.0, 96.0),
+                                    name.to_string(),
+                                    text_colour,
+                                    pressed_colour,
+                                    button_colour,
+                                    true, text.to_string(), "Arial".to_string());
+    }
+    
+    let mut editor = EditorScreen {
+      data: SceneData::new(window_size, model_sizes),
+      camera,
+      last_mouse_pos: Vector2::new(-1.0, -1.0),
+      map,
+      brush: TileKind::Path,
+      valid: false,
+      widgets: vec!(toolbar),
+      next: NextScene::Menu,
+      key_bindings,
+      file_name,
+    };
+    
+    editor.validate();
+    
+    editor
+  }
+  
+  // Runs on every painted tile, so only checks the map is connected. Routes
+  // are worked out once it is saved
+  fn validate(&mut self) {
+    let status = match self.map.check_routes() {
+      Ok(()) => {
+        self.valid = true;
+        "Valid map".to_string()
+      },
+      Err(e) => {
+        self.valid = false;
+        e.to_string()
+      },
+    };
+    
+    self.widgets[TOOLBAR_INDEX].update_text_field(&STATUS_NAME.to_string(), status);
+  }
+  
+  fn save(&mut self) -> bool {
+    if !self.valid {
+      return false;
+    }
+    
+    let routes = match self.map.recalculate_routes() {
+      Ok(routes) => routes,
+      Err(e) => {
+        self.valid = false;
+        self.widgets[TOOLBAR_INDEX].update_text_field(&STATUS_NAME.to_string(), e.to_string());
+        return false;
+      },
+    };
+    
+    let file = self.file_name.to_owned() + ".ron";
+    let status = match self.map.to_map_file().save(&(MAP_DIRECTORY.to_owned() + &file)) {
+      Ok(_) => {
+        let routes = if routes == 1 { "1 route".to_string() } else { routes.to_string() + " routes" };
+        "Saved to ".to_owned() + &file + ", " + &routes
+      },
+      Err(e) => "Unable to save: ".to_owned() + &e,
+    };
+    
+    self.widgets[TOOLBAR_INDEX].update_text_field(&STATUS_NAME.to_string(), status);
+    
+    true
+  }
+  
+  // Saves to a file nothing else has been saved to, which later saves go to too
+  fn save_new(&mut self) -> bool {
+    if !self.valid {
+      return false;
+    }
+    
+    self.file_name = map_file::unused_map_name(MAP_DIRECTORY, EDITOR_MAP_NAME);
+    self.map.set_name(self.file_name.clone());
+    self.save()
+  }
+  
+  fn set_brush(&mut self, brush: TileKind, name: &str) {
+    self.brush = brush;
+    self.widgets[TOOLBAR_INDEX].update_text_field(&BRUSH_NAME.to_string(), "Brush: ".to_owned() + name);
+  }
+  
+  fn hex_under_mouse(&self, mouse: Vector2<f32>) -> Option<Vector2<i32>> {
+    let mouse_ray = self.camera.mouse_to_world_ray(mouse, self.data.window_dim);
+    if mouse_ray.y >= 0.0 {
+      return None;
+    }
+    
+    let mut crnt_pos = self.camera.get_position();
+    while crnt_pos.y > 0.0 {
+      crnt_pos += mouse_ray;
+    }
+    crnt_pos -= mouse_ray;
+    
+    let hex = self.map.pixel_to_hex(Vector2::new(crnt_pos.x, crnt_pos.z));
+    if self.map.is_valid_qr(hex.q(), hex.r()) {
+      Some(Vector2::new(hex.q(), hex.r()))
+    } else {
+      None
+    }
+  }
+
+  fn action_pressed(&self, action: KeyAction) -> bool {
+    self.data.key_pressed(self.key_bindings.key(action))
+  }
+}
+
+impl Scene for EditorScreen {
+  fn data(&self) -> &SceneData {
+    &self.data
+  }
+  
+  fn mut_data(&mut self) -> &mut SceneData {
+    &mut self.data
+  }
+  
+  fn future_scene(&mut self, window_size: Vector2<f32>) -> Box<Scene> {
+    if self.data().window_resized {
+      let mut editor = EditorScreen::new(window_size, self.data.model_sizes.clone(), self.map.clone(), self.file_name.clone());
+      editor.camera = self.camera.clone();
+      editor.brush = self.brush.clone();
+      return Box::new(editor);
+    }
+    
+    match self.next {
+      NextScene::Play(ref waves) => {
+        let simulation = Simulation::new(self.map.clone(), waves.clone(), thread_rng().gen::<u64>());
+        Box::new(GameScreen::new(window_size, self.data.model_sizes.clone(), simulation))
+      },
+      NextScene::Menu => {
+        Box::new(MenuScreen::new(window_size, self.data.model_sizes.clone()))
+      },
+    }
+  }
+  
+  fn update(&mut self, delta_time: f32) {
+    let mouse = self.data().mouse_pos;
+    let left_clicked = self.data().left_mouse;
+    let right_clicked = self.data().right_mouse;
+    let scroll_delta = self.data().scroll_delta;
+    let keys_pressed_this_frame = self.get_keys_pressed_this_frame();
+    
+    if self.data().window_resized {
+      self.mut_data().next_scene = true;
+    }
+    
+    for widget in &mut self.widgets {
+      widget.update(delta_time, mouse, left_clicked, &keys_pressed_this_frame, scroll_delta);
+    }
+    
+    let toolbar = &self.widgets[TOOLBAR_INDEX];
+    let brush_buttons = vec!(
+      (toolbar.get_button_state(&OPEN_BUTTON_NAME.to_string()) || self.action_pressed(KeyAction::OpenBrush), TileKind::Open, "Open"),
+      (toolbar.get_button_state(&PATH_BUTTON_NAME.to_string()) || self.action_pressed(KeyAction::PathBrush), TileKind::Path, "Path"),
+      (toolbar.get_button_state(&BLOCKED_BUTTON_NAME.to_string()) || self.action_pressed(KeyAction::BlockedBrush), TileKind::Blocked, "Blocked"),
+      (toolbar.get_button_state(&START_BUTTON_NAME.to_string()) || self.action_pressed(KeyAction::StartBrush), TileKind::Start, "Start"),
+      (toolbar.get_button_state(&END_BUTTON_NAME.to_string()) || self.action_pressed(KeyAction::EndBrush), TileKind::End, "End"),
+    );
+    
+    for (pressed, brush, name) in brush_buttons {
+      if pressed {
+        self.set_brush(brush, name);
+      }
+    }
+    
+    if self.widgets[TOOLBAR_INDEX].get_button_state(&SAVE_BUTTON_NAME.to_string()) {
+      self.save();
+    }
+    
+    if self.widgets[TOOLBAR_INDEX].get_button_state(&SAVE_NEW_BUTTON_NAME.to_string()) {
+      self.save_new();
+    }
+    
+    if self.widgets[TOOLBAR_INDEX].get_button_state(&PLAY_BUTTON_NAME.to_string()) {
+      if self.save() {
+        match WaveFile::load_set(&self.map.get_info().wave_set) {
+          Ok(waves) => {
+            self.next = NextScene::Play(waves);
+            self.mut_data().next_scene = true;
+          },
+          Err(e) => {
+            self.widgets[TOOLBAR_INDEX].update_text_field(&STATUS_NAME.to_string(), e.to_string());
+          },
+        }
+      }
+    }
+    
+    if self.widgets[TOOLBAR_INDEX].get_button_state(&MENU_BUTTON_NAME.to_string()) || self.data().keys.escape_pressed() {
+      self.next = NextScene::Menu;
+      self.mut_data().next_scene = true;
+    }
+    
+    // Paint with the left mouse, look around with the right
+    if left_clicked && !self.widgets[TOOLBAR_INDEX].is_touching(mouse) {
+      if let Some(qr) = self.hex_under_mouse(mouse) {
+        let current = self.map.get_hex_from_qr(qr.x, qr.y).unwrap();
+        let unchanged = match self.brush {
+          TileKind::Open => current.is_open(),
+          TileKind::Path => current.is_path() && !current.is_start() && !current.is_end(),
+          TileKind::Blocked => current.is_blocked(),
+          TileKind::Start => current.is_start(),
+          TileKind::End => current.is_end(),
+          TileKind::Decoration(_) => false,
+        };
+        
+        if !unchanged {
+          let brush = self.brush.clone();
+          self.map.set_tile(qr.x, qr.y, &brush);
+          self.validate();
+        }
+      }
+    }
+    
+    if right_clicked && self.last_mouse_pos != Vector2::new(-1.0, -1.0) {
+      let x_offset = self.last_mouse_pos.x - mouse.x;
+      let y_offset = mouse.y - self.last_mouse_pos.y;
+      self.camera.process_mouse_movement(x_offset, y_offset);
+    }
+    self.last_mouse_pos = mouse;
+    
+    if self.action_pressed(KeyAction::CameraForward) {
+      self.camera.process_movement(camera::Direction::YAlignedForward, delta_time);
+    }
+    if self.action_pressed(KeyAction::CameraLeft) {
+      self.camera.process_movement(camera::Direction::YAlignedLeft, delta_time);
+    }
+    if self.action_pressed(KeyAction::CameraBackward) {
+      self.camera.process_movement(camera::Direction::YAlignedBackward, delta_time);
+    }
+    if self.action_pressed(KeyAction::CameraRight) {
+      self.camera.process_movement(camera::Direction::YAlignedRight, delta_time);
+    }
+    if self.action_pressed(KeyAction::CameraUp) {
+      self.camera.process_movement(camera::Direction::PositiveY, delta_time);
+    }
+    if self.action_pressed(KeyAction::CameraDown) {
+      self.camera.process_movement(camera::Direction::NegativeY, delta_time);
+    }
+    
+    if scroll_delta > 0.0 {
+      self.camera.process_movement(camera::Direction::Forward, 10.0*delta_time);
+    } else if scroll_delta < 0.0 {
+      self.camera.process_movement(camera::Direction::Backward, 10.0*delta_time);
+    }
+  }
+  
+  fn draw(&self, draw_calls: &mut Vec<DrawCall>) {
+    draw_calls.push(DrawCall::set_camera(self.camera.clone()));
+    
+    let cam_pos = self.camera.get_position();
+    
+    let hexagon_name = "Hexagon".to_string();
+    let hexagon_model_size: Vector3<f32> = {
+      let mut model_size = Vector3::new(1.0, 1.0, 1.0);
+      for model in &self.data().model_sizes {
+        if model.0 == hexagon_name {
+            model_size = model.1
+        }
+      }
+      
+      model_size
+    };
+    
+    self.map.draw(hexagon_model_size, Vector2::new(cam_pos.x, cam_pos.z), draw_calls);
+    
+    // Blocked tiles use the plain hexagon model so mark them while editing
+    let layout = self.map.get_layout();
+    for hexagon in self.map.get_hexagons() {
+      if hexagon.is_blocked() {
+        hexagon.draw_hologram_coloured(&self.map, &layout, 1.0, 0.5, Vector3::new(0.3, 0.3, 0.3), draw_calls);
+      }
+    }
+    
+    for widget in &self.widgets {
+      widget.draw(draw_calls);
+    }
+    draw_calls.push(DrawCall::reset_ortho_camera());
+  }
+}
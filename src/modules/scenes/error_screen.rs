@@ -0,0 +1,120 @@
+use maat_graphics::DrawCall;
+
+use crate::modules::scenes::Scene;
+use crate::modules::scenes::SceneData;
+use crate::modules::scenes::MenuScreen;
+
+use crate::modules::system_interface::Widget;
+
+use cgmath::{Vector2, Vector3, Vector4};
+
+const ERROR_WINDOW_INDEX: usize = 0;
+
+const ERROR_WINDOW_NAME: &str = "ErrorWindow";
+const TITLE_NAME: &str = "ErrorTitle";
+const MESSAGE_NAME: &str = "ErrorMessage";
+const RETURN_BUTTON_NAME: &str = "Return";
+
+pub struct ErrorScreen {
+  data: SceneData,
+  message: String,
+  widgets: Vec<Widget>,
+}
+
+impl ErrorScreen {
+  pub fn new(window_size: Vector2<f32>, model_sizes: Vec<(String, Vector3<f32>)>, message: String) -> ErrorScreen {
+    println!("Error Screen");
+    
+    let window_width = 800.0;
+    let window_height = 256.0;
+    
+    let button_width = 128.0;
+    let button_height = 32.0;
+    
+    let text_colour = Vector4::new(0.0, 0.0, 0.0, 1.0);
+    let button_colour = Vector4::new(0.5019, 0.749, 1.0, 1.0);
+    let background_colour = Vector4::new(0.0, 0.1411, 0.4, 1.0);
+    
+    let mut widgets = Vec::new();
+    widgets.push(
+      Widget::new(ERROR_WINDOW_NAME.to_string(), Vector2::new(window_size.x*0.5, window_size.y*0.5),
+                  Vector2::new(window_width, window_height),
+                  background_colour)
+                .with_text_field_centered(TITLE_NAME.to_string(),
+                                          Vector2::new(window_width*0.5, window_height-64.0),
+                                          Vector2::new(192.0, 192.0),
+                                          Vector4::new(1.0, 0.0, 0.0, 1.0),
+                                          "Unable to load map".to_string(), "Arial".to_string())
+                .with_text_field_centered(MESSAGE_NAME.to_string(),
+                                          Vector2::new(window_width*0.5, window_height*0.5),
+                                          Vector2::new(128.0, 128.0),
+                                          Vector4::new(1.0, 1.0, 1.0, 1.0),
+                                          message.clone(), "Arial".to_string())
+                .with_button(Vector2::new(window_width*0.5, 48.0),
+                             Vector2::new(button_width, button_height),
+                             Vector2::new(button_width*0.5, button_height*0.33),
+                             Vector2::new(128.0, 128.0),
+                             RETURN_BUTTON_NAME.to_string(),
+                             text_colour,
+                             Vector4::new(1.0, 0.0, 0.0, 1.0),
+                             button_colour,
+                             true, "Return".to_string(), "Arial".to_string())
+    );
+    
+    ErrorScreen {
+      data: SceneData::new(window_size, model_sizes),
+      message,
+      widgets,
+    }
+  }
+}
+
+impl Scene for ErrorScreen {
+  fn data(&self) -> &SceneData {
+    &self.data
+  }
+  
+  fn mut_data(&mut self) -> &mut SceneData {
+    &mut self.data
+  }
+  
+  fn future_scene(&mut self, window_size: Vector2<f32>) -> Box<Scene> {
+    if self.data().window_resized {
+      Box::new(ErrorScreen::new(window_size, self.data.model_sizes.clone(), self.message.clone()))
+    } else {
+      Box::new(MenuScreen::new(window_size, self.data.model_sizes.clone()))
+    }
+  }
+  
+  fn update(&mut self, delta_time: f32) {
+    let mouse = self.data().mouse_pos;
+    let left_clicked = self.data().left_mouse;
+    let scroll_delta = self.data().scroll_delta;
+    let keys_pressed_this_frame = self.get_keys_pressed_this_frame();
+    
+    for widget in &mut self.widgets {
+      widget.update(delta_time, mouse, left_clicked, &keys_pressed_this_frame, scroll_delta);
+    }
+    
+    if self.widgets[ERROR_WINDOW_INDEX].get_button_state(&RETURN_BUTTON_NAME.to_string()) || self.data().window_resized {
+      self.mut_data().next_scene = true;
+    }
+  }
+  
+  fn draw(&self, draw_calls: &mut Vec<DrawCall>) {
+    let dim = self.data().window_dim;
+    let (width, height) = (dim.x as f32, dim.y as f32);
+    
+    draw_calls.push(
+        DrawCall::draw_coloured(Vector2::new(width*0.5, height*0.5),
+                                Vector2::new(width*5.0, height*5.0),
+                                Vector4::new(0.0, 0.0, 0.0, 1.0),
+                                90.0)
+    );
+    
+    for widget in &self.widgets {
+      widget.draw(draw_calls);
+    }
+    draw_calls.push(DrawCall::reset_ortho_camera());
+  }
+}
@@ -4,30 +4,26 @@ use maat_graphics::camera;
 use crate::modules::scenes::Scene;
 use crate::modules::scenes::SceneData;
 use crate::modules::scenes::MenuScreen;
+use crate::modules::scenes::ResultsScreen;
 
 use crate::modules::food::Food;
-use crate::modules::appliances::{Dishwasher, Fridge, MeatTenderizer, CoffeeMachine, SaltGrinder};
-use crate::modules::appliances::traits::{Appliance, TargetPriority};
+use crate::modules::food_index::FoodIndex;
+use crate::modules::appliances::traits::{Appliance, ApplianceKind, TargetPriority};
 use crate::modules::weapons::{Weapon};
-use crate::modules::hexagon::{Layout, Hexagon, HexagonType, HexDirection};
-use crate::modules::thefoodstore::FoodStore;
-
-use crate::modules::update::update_game;
-use crate::modules::physics::collisions;
-use crate::modules::map::Map;
-
-use rand;
-use rand::{thread_rng};
+use crate::modules::hexagon::{Layout, Hexagon, HexDirection};
+use crate::modules::simulation::{Simulation, BIN_CLEAN_COST};
+use crate::modules::save_game::SaveGame;
+use crate::modules::replay::{Replay, ReplayPlayer};
+use crate::modules::command::{GameCommand, CommandOutcome, CommandError, GAME_SPEEDS, LEGACY_PAUSE_SPEED};
+use crate::modules::map::MapError;
+use crate::modules::settings::{Settings, KeyBindings, KeyAction};
+use crate::modules::system_interface::{PauseUi, ShopUi};
 
 use cgmath::{InnerSpace, SquareMatrix, Matrix4, Point3, Deg, Vector2, Vector3, Vector4, PerspectiveFov};
 
 const DEV: bool = false;
 
 const DEFAULT_ZOOM: f32 = 1.0;
-const DELTA_STEP: f32 = 0.01;
-
-const START_MONEY: i32 = 300;
-const BIN_CLEAN_COST: i32 = 700;
 
 const CAMERA_DEFAULT_X: f32 = 83.93359;
 const CAMERA_DEFAULT_Y: f32 = 128.62776;
@@ -36,6 +32,9 @@ const CAMERA_DEFAULT_PITCH: f32 = -62.27426;
 const CAMERA_DEFAULT_YAW: f32 = 210.10083;
 const CAMERA_DEFAULT_SPEED: f32 = 50.0;
 
+// Seconds a failed undo's reason stays on screen
+const UNDO_MESSAGE_TIME: f32 = 3.0;
+
 enum MouseState {
   World,
   Ui,
@@ -46,35 +45,42 @@ pub struct GameScreen {
   data: SceneData,
   zoom: f32,
   escaped_pressed_last_frame: bool,
-  space_pressed_last_frame: bool,
-  t_pressed_last_frame: bool,
+  speed_pressed_last_frame: bool,
+  targeting_pressed_last_frame: bool,
+  upgrade_first_pressed_last_frame: bool,
+  upgrade_second_pressed_last_frame: bool,
   f1_pressed_last_frame: bool,
   f2_pressed_last_frame: bool,
-  f10_pressed_last_frame: bool,
-  p_pressed_last_frame: bool,
+  minimal_ui_pressed_last_frame: bool,
+  pause_pressed_last_frame: bool,
+  undo_pressed_last_frame: bool,
   screen_offset: Vector2<f32>,
   camera: camera::Camera,
-  rng: rand::prelude::ThreadRng,
   last_mouse_pos: Vector2<f32>,
-  total_delta: f32,
-  map: Map,
-  appliances: Vec<Box<Appliance>>,
-  foods: Vec<Box<Food>>,
-  weapons: Vec<Box<Weapon>>,
+  simulation: Simulation,
   ray_position: Vector2<f32>,
   game_speed: i32,
+  // What a restart goes back to
+  default_game_speed: i32,
+  // Stops the game without losing the speed it was running at
+  paused: bool,
+  pause_menu: PauseUi,
+  shop: ShopUi,
   mouse_state: MouseState,
-  bin: i32,
   placing_appliance: Option<Box<Appliance>>,
+  placing_kind: Option<ApplianceKind>,
   selected_appliance: Option<usize>,
   valid_place: bool,
-  the_food_store: FoodStore,
-  money: i32,
   minimal_ui: bool,
+  recording: Option<Replay>,
+  playback: Option<ReplayPlayer>,
+  key_bindings: KeyBindings,
+  // Why the last undo didn't happen, and for how much longer to say so
+  undo_message: Option<(String, f32)>,
 }
 
 impl GameScreen {
-  pub fn new(window_size: Vector2<f32>, model_sizes: Vec<(String, Vector3<f32>)>, map_name: String) -> GameScreen {
+  pub fn new(window_size: Vector2<f32>, model_sizes: Vec<(String, Vector3<f32>)>, mut simulation: Simulation) -> GameScreen {
     println!("Game Screen");
     
     let mut camera = camera::Camera::default_vk();
@@ -83,83 +89,209 @@ impl GameScreen {
     camera.set_yaw(CAMERA_DEFAULT_YAW);
     camera.set_move_speed(CAMERA_DEFAULT_SPEED);
     
-    let mut rng =  thread_rng();
-    
-    let map = Map::new_random_map(5, &mut rng);
-    //let map = Map::new(map_name.to_string());
-    
-    let path = map.get_path();
-    let food_pos = map.tile_position_from_index(path[0] as usize);
-    let tile_loc = map.get_qr_from_index(path[0] as usize);
-    let store = FoodStore::new(&map);
+    let settings = Settings::load();
+    simulation.undo_window = settings.undo_window;
+    let recording = Replay::new(&simulation);
+    let shop = ShopUi::new(window_size, &simulation.map, &settings.key_bindings);
     
     GameScreen {
       data: SceneData::new(window_size, model_sizes),
       zoom: 1.0, // 0.5 to 2.0
       escaped_pressed_last_frame: false,
-      space_pressed_last_frame: false,
-      t_pressed_last_frame: false,
+      speed_pressed_last_frame: false,
+      targeting_pressed_last_frame: false,
+      upgrade_first_pressed_last_frame: false,
+      upgrade_second_pressed_last_frame: false,
       f1_pressed_last_frame: false,
       f2_pressed_last_frame: false,
-      f10_pressed_last_frame: false,
-      p_pressed_last_frame: false,
+      minimal_ui_pressed_last_frame: false,
+      pause_pressed_last_frame: false,
+      undo_pressed_last_frame: false,
       screen_offset: Vector2::new(0.0, 0.0),
       camera,
-      rng,
       last_mouse_pos: Vector2::new(-1.0, -1.0),
-      total_delta: 0.0,
-      map,
-      appliances: Vec::new(),
-      foods: vec!(),
-      weapons: Vec::new(),
+      simulation,
       ray_position: Vector2::new(0.0, 0.0),
-      game_speed: 1,
+      game_speed: settings.default_game_speed,
+      default_game_speed: settings.default_game_speed,
+      paused: false,
+      pause_menu: PauseUi::new(window_size),
+      shop,
       mouse_state: MouseState::World,
-      bin: 0,
       placing_appliance: None,
+      placing_kind: None,
       selected_appliance: None,
       valid_place: false,
-      the_food_store: store,
-      money: 300,
       minimal_ui: false,
+      recording: Some(recording),
+      playback: None,
+      key_bindings: settings.key_bindings,
+      undo_message: None,
+    }
+  }
+  
+  pub fn continue_game(window_size: Vector2<f32>, model_sizes: Vec<(String, Vector3<f32>)>, save: SaveGame) -> Result<GameScreen, MapError> {
+    let game_speed = save.game_speed;
+    let recording = save.replay.clone();
+    let mut simulation = save.into_simulation()?;
+    simulation.skip_map_animation();
+    
+    let mut game = GameScreen::new(window_size, model_sizes, simulation);
+    if GAME_SPEEDS.contains(&game_speed) {
+      game.game_speed = game_speed;
+    } else {
+      // Saved while paused, from before pausing had its own flag
+      game.paused = game_speed == LEGACY_PAUSE_SPEED;
+    }
+    if let Some(undo_window) = recording.as_ref().and_then(|r| r.undo_window) {
+      game.simulation.undo_window = undo_window;
+    }
+    game.recording = recording;
+    
+    Ok(game)
+  }
+  
+  pub fn play_replay(window_size: Vector2<f32>, model_sizes: Vec<(String, Vector3<f32>)>, replay: Replay) -> Result<GameScreen, MapError> {
+    let simulation = replay.to_simulation()?;
+    let undo_window = simulation.undo_window;
+    
+    let mut game = GameScreen::new(window_size, model_sizes, simulation);
+    game.simulation.undo_window = undo_window;
+    game.recording = None;
+    game.playback = Some(ReplayPlayer::new(replay));
+    
+    Ok(game)
+  }
+  
+  pub fn save_game(&self) {
+    match SaveGame::new(&self.simulation, self.game_speed, self.recording.clone()).save() {
+      Ok(_) => println!("Game saved"),
+      Err(e) => println!("Unable to save game: {}", e),
+    }
+  }
+  
+  pub fn save_replay(&self) {
+    if let Some(recording) = &self.recording {
+      if let Err(e) = recording.save() {
+        println!("Unable to save replay: {}", e);
+      }
+    }
+  }
+  
+  // Keys, clicks and replays all change the game through here. Whatever the
+  // player does gets recorded unless a replay is being watched
+  fn apply_command(&mut self, command: GameCommand) -> Result<CommandOutcome, CommandError> {
+    let tick = self.simulation.ticks;
+    let result = self.simulation.apply_command(&command, &mut self.data.model_sizes);
+    
+    match &result {
+      Ok(CommandOutcome::SpeedChanged(speed)) => {
+        self.game_speed = *speed;
+      },
+      Ok(CommandOutcome::Restarted) => {
+        self.placing_appliance = None;
+        self.selected_appliance = None;
+        self.mouse_state = MouseState::World;
+        self.game_speed = self.default_game_speed;
+      },
+      Ok(CommandOutcome::Undone { .. }) => {
+        // Whatever was selected may have moved or gone
+        self.selected_appliance = None;
+      },
+      Ok(_) => {},
+      Err(e) => {
+        println!("{}", e);
+      },
+    }
+    
+    if result.is_ok() && self.playback.is_none() {
+      if let Ok(CommandOutcome::Restarted) = result {
+        // Restart repeats while held, only a run that got going is worth keeping
+        let played = tick > 0 || self.recording.as_ref().map(|r| !r.commands.is_empty()).unwrap_or(false);
+        if played {
+          self.save_replay();
+        }
+        self.recording = Some(Replay::new(&self.simulation));
+      } else if let Some(recording) = &mut self.recording {
+        recording.record(tick, command);
+      }
     }
+    
+    result
   }
   
-  pub fn new_with_data(window_size: Vector2<f32>, rng: rand::prelude::ThreadRng, camera: camera::Camera, screen_offset: Vector2<f32>, appliances: Vec<Box<Appliance>>, foods: Vec<Box<Food>>, map: Map, model_sizes: Vec<(String, Vector3<f32>)>, weapons: Vec<Box<Weapon>>, the_food_store: FoodStore, money: i32, game_speed: i32, bin: i32) -> GameScreen {
+  fn play_recorded_commands(&mut self) {
+    let commands = {
+      match &mut self.playback {
+        Some(player) => player.due_commands(self.simulation.ticks),
+        None => Vec::new(),
+      }
+    };
+    
+    if let Some(paused) = self.playback.as_mut().and_then(|player| player.take_pause_change()) {
+      self.paused = paused;
+    }
+    
+    for command in commands {
+      if self.apply_command(command).is_ok() {
+        // Indices may have shifted under whatever is being looked at
+        self.selected_appliance = None;
+      }
+    }
+  }
+  
+  pub fn new_with_data(window_size: Vector2<f32>, camera: camera::Camera, screen_offset: Vector2<f32>, simulation: Simulation, model_sizes: Vec<(String, Vector3<f32>)>, game_speed: i32) -> GameScreen {
+    let settings = Settings::load();
+    let shop = ShopUi::new(window_size, &simulation.map, &settings.key_bindings);
     
     GameScreen {
       data: SceneData::new(window_size, model_sizes),
       zoom: 1.0, // 0.5 to 2.0
       escaped_pressed_last_frame: false,
-      space_pressed_last_frame: false,
-      t_pressed_last_frame: false,
+      speed_pressed_last_frame: false,
+      targeting_pressed_last_frame: false,
+      upgrade_first_pressed_last_frame: false,
+      upgrade_second_pressed_last_frame: false,
       f1_pressed_last_frame: false,
       f2_pressed_last_frame: false,
-      f10_pressed_last_frame: false,
-      p_pressed_last_frame: false,
+      minimal_ui_pressed_last_frame: false,
+      pause_pressed_last_frame: false,
+      undo_pressed_last_frame: false,
       screen_offset,
       camera,
-      rng,
       last_mouse_pos: Vector2::new(-1.0, -1.0),
-      total_delta: 0.0,
-      map,
-      appliances,
-      foods,
-      weapons,
+      simulation,
       ray_position: Vector2::new(0.0, 0.0),
       game_speed,
+      default_game_speed: settings.default_game_speed,
+      paused: false,
+      pause_menu: PauseUi::new(window_size),
+      shop,
       mouse_state: MouseState::World,
-      bin,
       placing_appliance: None,
+      placing_kind: None,
       selected_appliance: None,
       valid_place: false,
-      the_food_store,
-      money,
       minimal_ui: false,
+      recording: None,
+      playback: None,
+      key_bindings: settings.key_bindings,
+      undo_message: None,
     }
   }
   
-  fn start_placing_tower(&mut self, mouse: Vector2<f32>, appliance: Box<Appliance>) {
+  fn reset_camera(&mut self) {
+    self.camera.set_position(Vector3::new(CAMERA_DEFAULT_X, CAMERA_DEFAULT_Y, CAMERA_DEFAULT_Z));
+    self.camera.set_pitch(CAMERA_DEFAULT_PITCH);
+    self.camera.set_yaw(CAMERA_DEFAULT_YAW);
+    self.camera.set_move_speed(CAMERA_DEFAULT_SPEED);
+  }
+  
+  fn action_pressed(&self, action: KeyAction) -> bool {
+    self.data.key_pressed(self.key_bindings.key(action))
+  }
+  
+  fn start_placing_tower(&mut self, mouse: Vector2<f32>, kind: ApplianceKind) {
     let mouse_ray = self.camera.mouse_to_world_ray(mouse, self.data.window_dim);
     
     let mut q = 0;
@@ -173,27 +305,29 @@ impl GameScreen {
       
       let pix_x = crnt_pos.x;
       let pix_y = crnt_pos.z;
-      let clicked_hex = self.map.pixel_to_hex(Vector2::new(pix_x, pix_y));
+      let clicked_hex = self.simulation.map.pixel_to_hex(Vector2::new(pix_x, pix_y));
       q = clicked_hex.q();
       r =  clicked_hex.r();
-      self.valid_place = self.map.is_valid_qr(q,r);
+      self.valid_place = self.simulation.map.is_valid_qr(q,r);
     } else {
       self.valid_place = false;
     }
     
-    let mut appliance = appliance;
-    appliance.set_qr_location(q,r, &self.map);
+    let mut appliance = kind.create(Vector2::new(0,0), &self.simulation.map);
+    appliance.set_qr_location(q,r, &self.simulation.map);
     self.placing_appliance = Some(appliance);
+    self.placing_kind = Some(kind);
     if let Some(appliance) = &mut self.placing_appliance {
-      let foods = &mut self.foods;
-      let weapons = &mut self.weapons;
+      let foods = &mut self.simulation.foods;
+      let weapons = &mut self.simulation.weapons;
       let m_sizes = &mut self.data.model_sizes;
-      let map = &self.map;
+      let map = &self.simulation.map;
+      let food_index = FoodIndex::new(foods, map);
       
-      appliance.update(foods, weapons, m_sizes, map, 0.0);
+      appliance.update(foods, &food_index, weapons, m_sizes, map, 0.0);
       appliance.should_draw_range(true);
       if self.selected_appliance.is_some() {
-        self.appliances[self.selected_appliance.unwrap()].should_draw_range(false);
+        self.simulation.appliances[self.selected_appliance.unwrap()].should_draw_range(false);
       }
       self.selected_appliance = None;
     }
@@ -203,217 +337,190 @@ impl GameScreen {
   pub fn update_keypresses(&mut self, delta_time: f32) {
     let mouse = self.data.mouse_pos;
     
-    let escape_pressed = self.data().keys.escape_pressed();
-    let one_pressed = self.data.keys.one_pressed();
-    let two_pressed = self.data.keys.two_pressed();
-    let three_pressed = self.data.keys.three_pressed();
-    let four_pressed = self.data.keys.four_pressed();
-    let five_pressed = self.data.keys.five_pressed();
-    let mut w_pressed = self.data.keys.w_pressed();
-    let mut a_pressed = self.data.keys.a_pressed();
-    let mut s_pressed = self.data.keys.s_pressed();
-    let mut d_pressed = self.data.keys.d_pressed();
-    let c_pressed = self.data().keys.c_pressed();
-    let r_pressed = self.data().keys.r_pressed();
-    let f_pressed = self.data().keys.f_pressed();
-    let m_pressed = self.data().keys.m_pressed();
-    let p_pressed = self.data().keys.p_pressed();
-    let x_pressed = self.data().keys.x_pressed();
-    let k_pressed = self.data().keys.k_pressed();
-    let b_pressed = self.data().keys.b_pressed();
-    let v_pressed = self.data().keys.v_pressed();
-    let f10_pressed = self.data().keys.f10_pressed();
-    
-    let t_pressed = self.data().keys.t_pressed();
+    let dishwasher_pressed = self.action_pressed(KeyAction::PlaceDishwasher);
+    let fridge_pressed = self.action_pressed(KeyAction::PlaceFridge);
+    let tenderizer_pressed = self.action_pressed(KeyAction::PlaceMeatTenderizer);
+    let coffee_pressed = self.action_pressed(KeyAction::PlaceCoffeeMachine);
+    let grinder_pressed = self.action_pressed(KeyAction::PlaceSaltGrinder);
+    let drawer_pressed = self.action_pressed(KeyAction::PlaceCutleryDrawer);
+    let mut forward_pressed = self.action_pressed(KeyAction::CameraForward);
+    let mut left_pressed = self.action_pressed(KeyAction::CameraLeft);
+    let mut backward_pressed = self.action_pressed(KeyAction::CameraBackward);
+    let mut right_pressed = self.action_pressed(KeyAction::CameraRight);
+    let up_pressed = self.action_pressed(KeyAction::CameraUp);
+    let down_pressed = self.action_pressed(KeyAction::CameraDown);
+    let reset_camera_pressed = self.action_pressed(KeyAction::ResetCamera);
+    let clean_pressed = self.action_pressed(KeyAction::Clean);
+    let move_pressed = self.action_pressed(KeyAction::Move);
+    let pause_pressed = self.action_pressed(KeyAction::TogglePause);
+    let sell_pressed = self.action_pressed(KeyAction::Sell);
+    let restart_pressed = self.action_pressed(KeyAction::Restart);
+    let empty_bin_pressed = self.action_pressed(KeyAction::EmptyBin);
+    let minimal_ui_pressed = self.action_pressed(KeyAction::ToggleMinimalUi);
+    let undo_pressed = self.action_pressed(KeyAction::Undo);
+    
+    let targeting_pressed = self.action_pressed(KeyAction::CycleTargeting);
+    let upgrade_first_pressed = self.action_pressed(KeyAction::UpgradeFirstPath);
+    let upgrade_second_pressed = self.action_pressed(KeyAction::UpgradeSecondPath);
     
     match self.update_controller_input() {
       (w, a, s, d, x_offset, y_offset) => {
         // First person setup
-        if w { w_pressed = w; }
-        if a { a_pressed = a; }
-        if s { s_pressed = s; }
-        if d { d_pressed = d; }
+        if w { forward_pressed = w; }
+        if a { left_pressed = a; }
+        if s { backward_pressed = s; }
+        if d { right_pressed = d; }
         self.camera.process_mouse_movement(x_offset, y_offset);
       }
     }
     
     self.last_mouse_pos = mouse;
     
-    if p_pressed && !self.p_pressed_last_frame {
-      if self.game_speed < 2 {
-        self.game_speed = (self.game_speed+1)%2;
-      } else {
-        self.game_speed = 0;
-      }
-    }
-    
-    if w_pressed {
+    if forward_pressed {
       self.camera.process_movement(camera::Direction::YAlignedForward, delta_time);
     }
-    if a_pressed {
+    if left_pressed {
       self.camera.process_movement(camera::Direction::YAlignedLeft, delta_time);
     }
-    if s_pressed {
+    if backward_pressed {
       self.camera.process_movement(camera::Direction::YAlignedBackward, delta_time);
     }
-    if d_pressed {
+    if right_pressed {
       self.camera.process_movement(camera::Direction::YAlignedRight, delta_time);
     }
-    if r_pressed {
+    if up_pressed {
       self.camera.process_movement(camera::Direction::PositiveY, delta_time);
     }
-    if f_pressed {
+    if down_pressed {
       self.camera.process_movement(camera::Direction::NegativeY, delta_time);
     }
-    if one_pressed {
-      self.start_placing_tower(mouse,
-                               Box::new(Dishwasher::new(Vector2::new(0,0), Vector3::new(2.0, 2.0, 2.0), Vector3::new(0.0, 0.0, 0.0), &self.map))
-                              );
-    }
-    if two_pressed {
-      self.start_placing_tower(mouse, 
-                               Box::new(Fridge::new(Vector2::new(0,0), Vector3::new(3.0, 3.0, 3.0), Vector3::new(0.0, 0.0, 0.0), &self.map))
-                              );
+    
+    if minimal_ui_pressed && !self.minimal_ui_pressed_last_frame {
+      self.minimal_ui = !self.minimal_ui;
     }
-    if three_pressed {
-      self.start_placing_tower(mouse, 
-                               Box::new(MeatTenderizer::new(Vector2::new(0,0), Vector3::new(3.0, 3.0, 3.0), Vector3::new(0.0, 0.0, 0.0), &self.map))
-                              );
+    
+    if reset_camera_pressed || (restart_pressed && self.playback.is_none()) {
+      self.reset_camera();
     }
-    if four_pressed {
-      self.start_placing_tower(mouse, 
-                               Box::new(CoffeeMachine::new(Vector2::new(0,0), Vector3::new(0.3, 0.3, 0.3), Vector3::new(0.0, 0.0, 0.0), &self.map))
-                              );
+    
+    let pause_pressed_last_frame = self.pause_pressed_last_frame;
+    let undo_pressed_last_frame = self.undo_pressed_last_frame;
+    let targeting_pressed_last_frame = self.targeting_pressed_last_frame;
+    let upgrade_first_pressed_last_frame = self.upgrade_first_pressed_last_frame;
+    let upgrade_second_pressed_last_frame = self.upgrade_second_pressed_last_frame;
+    self.targeting_pressed_last_frame = targeting_pressed;
+    self.upgrade_first_pressed_last_frame = upgrade_first_pressed;
+    self.upgrade_second_pressed_last_frame = upgrade_second_pressed;
+    self.minimal_ui_pressed_last_frame = minimal_ui_pressed;
+    self.pause_pressed_last_frame = pause_pressed;
+    self.undo_pressed_last_frame = undo_pressed;
+    
+    // Pausing never touches the simulation, so it works in replays too
+    if pause_pressed && !pause_pressed_last_frame {
+      self.paused = !self.paused;
     }
-    if five_pressed {
-      self.start_placing_tower(mouse, 
-                               Box::new(SaltGrinder::new(Vector2::new(0,0), Vector3::new(2.0, 2.0, 2.0), Vector3::new(0.0, 0.0, -90.0), &self.map))
-                              );
+    
+    // While watching a replay only the camera is ours to move
+    if self.playback.is_some() {
+      return;
     }
     
-    if b_pressed && self.bin > 0 {
-      if self.money >= BIN_CLEAN_COST {
-        self.money -= BIN_CLEAN_COST;
-        self.bin = 0;
-      }
+    if dishwasher_pressed {
+      self.start_placing_tower(mouse, ApplianceKind::Dishwasher);
+    }
+    if fridge_pressed {
+      self.start_placing_tower(mouse, ApplianceKind::Fridge);
+    }
+    if tenderizer_pressed {
+      self.start_placing_tower(mouse, ApplianceKind::MeatTenderizer);
+    }
+    if coffee_pressed {
+      self.start_placing_tower(mouse, ApplianceKind::CoffeeMachine);
+    }
+    if grinder_pressed {
+      self.start_placing_tower(mouse, ApplianceKind::SaltGrinder);
+    }
+    if drawer_pressed {
+      self.start_placing_tower(mouse, ApplianceKind::CutleryDrawer);
     }
     
-    if f10_pressed && !self.f10_pressed_last_frame {
-      self.minimal_ui = !self.minimal_ui;
+    if undo_pressed && !undo_pressed_last_frame {
+      self.undo_message = match self.apply_command(GameCommand::Undo) {
+        Ok(_) => None,
+        Err(e) => Some((e.to_string(), UNDO_MESSAGE_TIME)),
+      };
     }
     
-    // reseting
-    if k_pressed {
-      self.map.reset();
-      self.foods.clear();
-      self.appliances.clear();
-      self.weapons.clear();
-      self.money = START_MONEY;
-      self.placing_appliance = None;
-      self.selected_appliance = None;
-      self.bin = 0;
-      self.game_speed = 1;
-      self.total_delta = 0.0;
-      self.the_food_store = FoodStore::new(&self.map);
+    if empty_bin_pressed && self.simulation.bin > 0 {
+      let _ = self.apply_command(GameCommand::EmptyBin);
     }
     
-    if v_pressed || k_pressed {
-      self.camera.set_position(Vector3::new(CAMERA_DEFAULT_X, CAMERA_DEFAULT_Y, CAMERA_DEFAULT_Z));
-      self.camera.set_pitch(CAMERA_DEFAULT_PITCH);
-      self.camera.set_yaw(CAMERA_DEFAULT_YAW);
-      self.camera.set_move_speed(CAMERA_DEFAULT_SPEED);
+    // reseting
+    if restart_pressed {
+      let _ = self.apply_command(GameCommand::Restart);
     }
     
     if let Some(idx) = self.selected_appliance {
       // Change target priority for selected appliance
-      if t_pressed && !self.t_pressed_last_frame {
-        match self.appliances[idx].get_targeting() {
-          TargetPriority::First => {
-            self.appliances[idx].set_targeting(TargetPriority::Last);
-          },
-          TargetPriority::Last => {
-            self.appliances[idx].set_targeting(TargetPriority::Close);
-          },
-          TargetPriority::Close => {
-            self.appliances[idx].set_targeting(TargetPriority::Far);
-          },
-          TargetPriority::Far => {
-            self.appliances[idx].set_targeting(TargetPriority::Strong);
-          },
-          TargetPriority::Strong => {
-            self.appliances[idx].set_targeting(TargetPriority::Weak);
-          },
-          TargetPriority::Weak => {
-            self.appliances[idx].set_targeting(TargetPriority::First);
-          },
-        }
+      if targeting_pressed && !targeting_pressed_last_frame {
+        let target = {
+          match self.simulation.appliances[idx].get_targeting() {
+            TargetPriority::First => TargetPriority::Last,
+            TargetPriority::Last => TargetPriority::Close,
+            TargetPriority::Close => TargetPriority::Far,
+            TargetPriority::Far => TargetPriority::Strong,
+            TargetPriority::Strong => TargetPriority::Weak,
+            TargetPriority::Weak => TargetPriority::First,
+          }
+        };
+        let _ = self.apply_command(GameCommand::Targeting { appliance: idx, target });
+      }
+      
+      // Upgrade along the first or second path
+      if upgrade_first_pressed && !upgrade_first_pressed_last_frame {
+        let _ = self.apply_command(GameCommand::Upgrade { appliance: idx, path: 0 });
+      }
+      if upgrade_second_pressed && !upgrade_second_pressed_last_frame {
+        let _ = self.apply_command(GameCommand::Upgrade { appliance: idx, path: 1 });
       }
       
       // Sell tower
-      if x_pressed {
-        self.money += self.appliances[idx].sell_price();
-        let hex_location = self.appliances[idx].get_qr_location();
-        let range = self.appliances[idx].get_range();
-        self.map.set_hexagon_type(hex_location.x, hex_location.y, HexagonType::Open);
-        
-        let appliance_hex = Hexagon::new(hex_location.x, hex_location.y, "".to_string());
-        let buffs = self.appliances[idx].update(&mut self.foods, &mut self.weapons, &mut self.data.model_sizes, &self.map, delta_time);
-        
-        let hexs = Hexagon::generate_hexagon_range(range as i32, "".to_string());
-        for hex in &hexs {
-          let t_hex = Hexagon::hex_add(&appliance_hex, &hex);
-          for appliance in &mut self.appliances {
-            let qr = appliance.get_qr_location();
-            if qr.x == t_hex.q() && qr.y == t_hex.r() {
-              for (buff, _, _) in &buffs {
-                appliance.remove_buff(buff);
-              }
-            }
-          }
-        }
-        
-        self.appliances.remove(idx);
+      if sell_pressed {
+        let _ = self.apply_command(GameCommand::Sell { appliance: idx });
         self.selected_appliance = None;
+        return;
       }
       
       // move tower
-      if m_pressed {
-        let mut appliance = self.appliances[idx].clone();
+      if move_pressed {
+        let mut appliance = self.simulation.appliances[idx].clone();
         appliance.should_draw_range(true);
         self.placing_appliance = Some(appliance);
+        self.placing_kind = None;
         self.mouse_state = MouseState::Placing;
         
-        let life = self.appliances[idx].current_life_expectancy();
+        let life = self.simulation.appliances[idx].current_life_expectancy();
         
-        let mut hexagons: Vec<Hexagon> = Vec::new();
         let radius = life-1;
         let hexagons = Hexagon::generate_hexagon_range(radius, "PurpleHexagon".to_string());
         
-        let qr = self.appliances[idx].get_qr_location();
+        let qr = self.simulation.appliances[idx].get_qr_location();
         let appliance_hex = Hexagon::new(qr.x,qr.y, "".to_string());
         
         for hexagon in &hexagons {
           let hex = Hexagon::hex_add(&appliance_hex, hexagon);
           let q = hex.q();
           let r = hex.r();
-          if self.map.is_valid_qr(q,r) {
-            self.map.highlight_hex(hex);
+          if self.simulation.map.is_valid_qr(q,r) {
+            self.simulation.map.highlight_hex(hex);
           }
         }
       }
       // Clean tower
-      if c_pressed {
-        if self.money >= self.appliances[idx].clean_cost() {
-          self.money -= self.appliances[idx].clean_cost();
-          self.appliances[idx].clean();
-        }
+      if clean_pressed && self.simulation.appliances[idx].clean_cost() > 0 {
+        let _ = self.apply_command(GameCommand::Clean { appliance: idx });
       }
     }
-    
-    self.escaped_pressed_last_frame = escape_pressed;
-    self.t_pressed_last_frame = t_pressed;
-    self.f10_pressed_last_frame = f10_pressed;
-    self.p_pressed_last_frame = p_pressed;
   }
   
   pub fn update_controller_input(&mut self) -> (bool, bool, bool, bool, f32, f32) {
@@ -458,12 +565,6 @@ impl GameScreen {
     let left_clicked = self.data.left_mouse;
     let right_clicked = self.data.right_mouse;
     let mouse = self.data.mouse_pos;
-    let escape_pressed = self.data().keys.escape_pressed();
-    
-    if self.escaped_pressed_last_frame && !escape_pressed {
-      self.escaped_pressed_last_frame = false;
-      self.mut_data().next_scene = true;
-    }
     
     if right_clicked {
       if self.selected_appliance.is_some() {
@@ -471,6 +572,11 @@ impl GameScreen {
       }
     }
     
+    // Clicks on the shop are for the shop
+    if self.shop.is_touching(mouse) {
+      return;
+    }
+    
     if left_clicked {
       if self.last_mouse_pos != Vector2::new(-1.0, -1.0) {
         let x_offset = self.last_mouse_pos.x - mouse.x;
@@ -488,18 +594,18 @@ impl GameScreen {
         
         let pix_x = crnt_pos.x;
         let pix_y = crnt_pos.z;
-        let clicked_hex = self.map.pixel_to_hex(Vector2::new(pix_x, pix_y));
+        let clicked_hex = self.simulation.map.pixel_to_hex(Vector2::new(pix_x, pix_y));
         let q = clicked_hex.q();
         let r =  clicked_hex.r();
         
         let mut found_appliance = false;
         
-        if self.map.is_valid_qr(q,r) {
-          let some_hex = self.map.get_hex_from_qr(q, r);
+        if self.simulation.map.is_valid_qr(q,r) {
+          let some_hex = self.simulation.map.get_hex_from_qr(q, r);
           if let Some(hex) = some_hex {
             if !hex.is_open() {
-              for i in 0..self.appliances.len() {
-                let loc = self.appliances[i].get_qr_location();
+              for i in 0..self.simulation.appliances.len() {
+                let loc = self.simulation.appliances[i].get_qr_location();
                 if q == loc.x && r == loc.y {
                   // Select appliance
                   found_appliance = true;
@@ -508,7 +614,7 @@ impl GameScreen {
                       break;
                     }
                     
-                    self.appliances[self.selected_appliance.unwrap()].should_draw_range(false);
+                    self.simulation.appliances[self.selected_appliance.unwrap()].should_draw_range(false);
                   }
                   
                   self.selected_appliance = Some(i);
@@ -521,7 +627,7 @@ impl GameScreen {
         
         if !found_appliance {
           if self.selected_appliance.is_some() {
-            self.appliances[self.selected_appliance.unwrap()].should_draw_range(false);
+            self.simulation.appliances[self.selected_appliance.unwrap()].should_draw_range(false);
           }
           self.selected_appliance = None;
         }
@@ -533,16 +639,36 @@ impl GameScreen {
     
   }
   
+  pub fn update_shop(&mut self, delta_time: f32) {
+    let mouse = self.data.mouse_pos;
+    let left_clicked = self.data.left_mouse;
+    let placing = {
+      match self.mouse_state {
+        MouseState::Placing => self.placing_kind,
+        _ => None,
+      }
+    };
+    
+    let bought = self.shop.update(delta_time, mouse, left_clicked, self.simulation.money, placing);
+    if let Some(kind) = bought {
+      if self.playback.is_none() {
+        self.start_placing_tower(mouse, kind);
+      }
+    }
+  }
+  
   pub fn update_placing(&mut self, delta_time: f32) {
     let left_clicked = self.data.left_mouse;
     let right_clicked = self.data.right_mouse;
     let mouse = self.data.mouse_pos;
-    let escape_pressed = self.data().keys.escape_pressed();
     
     if right_clicked {
-      self.escaped_pressed_last_frame = false;
       self.mouse_state = MouseState::World;
-      self.map.unhighlight_all_hexs();
+      self.simulation.map.unhighlight_all_hexs();
+      return;
+    }
+    
+    if self.shop.is_touching(mouse) {
       return;
     }
     
@@ -556,15 +682,15 @@ impl GameScreen {
       
       let pix_x = crnt_pos.x;
       let pix_y = crnt_pos.z;
-      let clicked_hex = self.map.pixel_to_hex(Vector2::new(pix_x, pix_y));
+      let clicked_hex = self.simulation.map.pixel_to_hex(Vector2::new(pix_x, pix_y));
       let q = clicked_hex.q();
       let r =  clicked_hex.r();
       
       if let Some(appliance) = &mut self.placing_appliance {
-        appliance.set_qr_location(q,r, &self.map);
-        self.valid_place = self.map.is_valid_qr(q,r);
+        appliance.set_qr_location(q,r, &self.simulation.map);
+        self.valid_place = self.simulation.map.is_valid_qr(q,r);
         if self.selected_appliance.is_some() {
-          let some_hex = self.map.get_hex_from_qr(q,r);
+          let some_hex = self.simulation.map.get_hex_from_qr(q,r);
           if let Some(hex) = some_hex {
             if !hex.is_highlighted() {
               self.valid_place = false;
@@ -574,16 +700,14 @@ impl GameScreen {
       }
       
       if self.placing_appliance.is_some() {
-        let mut appliance = self.placing_appliance.clone().unwrap();
-        
         if left_clicked {
           self.ray_position = Vector2::new(pix_x, pix_y);
           
-          let opt_hex = self.map.get_hex_from_qr(q,r);
+          let opt_hex = self.simulation.map.get_hex_from_qr(q,r);
           if let Some(hex) = opt_hex {
             if hex.is_open() {
               if self.selected_appliance.is_some() {
-                let some_hex = self.map.get_hex_from_qr(q,r);
+                let some_hex = self.simulation.map.get_hex_from_qr(q,r);
                 if let Some(hex) = some_hex {
                   if !hex.is_highlighted() {
                     self.valid_place = false;
@@ -594,26 +718,25 @@ impl GameScreen {
               
               // if moving tower
               if let Some(idx) = self.selected_appliance {
-                let qr = self.appliances[idx].get_qr_location();
-                self.map.set_hexagon_type(qr.x, qr.y, HexagonType::Open);
-                self.appliances.remove(idx);
-                self.selected_appliance = Some(self.appliances.len());
-                let dist = Hexagon::hex_distance(&Hexagon::new(q,r, "".to_string()), &Hexagon::new(qr.x, qr.y, "".to_string()));
-                appliance.moved_tiles(dist);
-                self.map.unhighlight_all_hexs();
-              } else { 
-                if appliance.buy_cost() > self.money {
+                match self.apply_command(GameCommand::Move { appliance: idx, q, r }) {
+                  Ok(CommandOutcome::Moved { appliance, .. }) => {
+                    self.selected_appliance = Some(appliance);
+                  },
+                  _ => {
+                    return;
+                  },
+                }
+                self.simulation.map.unhighlight_all_hexs();
+              } else if let Some(kind) = self.placing_kind {
+                if self.apply_command(GameCommand::Place { kind, q, r }).is_err() {
                   return;
                 }
-                self.money -= appliance.buy_cost();
               }
               
-              appliance.should_draw_range(false);
               self.valid_place = false;
               self.placing_appliance = None;
+              self.placing_kind = None;
               self.mouse_state = MouseState::World;
-              self.map.set_hexagon_type(q,r,HexagonType::Closed);
-              self.appliances.push(appliance);
             }
           }
         }
@@ -623,35 +746,78 @@ impl GameScreen {
     }
   }
   
+  fn open_pause_menu(&mut self) {
+    self.paused = true;
+    self.pause_menu.show();
+  }
+  
+  fn resume(&mut self) {
+    self.pause_menu.hide();
+    self.paused = false;
+    // The controls may have been changed from the options menu
+    self.key_bindings = self.pause_menu.settings().key_bindings.clone();
+    self.default_game_speed = self.pause_menu.settings().default_game_speed;
+    self.shop.set_key_bindings(&self.key_bindings);
+  }
+  
+  pub fn update_pause_menu(&mut self, delta_time: f32) {
+    let mouse = self.data.mouse_pos;
+    let left_clicked = self.data.left_mouse;
+    let scroll_delta = self.data.scroll_delta;
+    let keys_pressed_this_frame = self.get_keys_pressed_this_frame();
+    
+    self.pause_menu.update(delta_time, mouse, left_clicked, &keys_pressed_this_frame, scroll_delta);
+    
+    if self.pause_menu.resume_button_pressed() {
+      self.resume();
+    }
+    
+    if self.pause_menu.restart_button_pressed() {
+      if self.playback.is_none() {
+        let _ = self.apply_command(GameCommand::Restart);
+        self.reset_camera();
+      }
+      self.resume();
+    }
+    
+    if self.pause_menu.quit_button_pressed() {
+      if self.playback.is_none() {
+        self.save_game();
+      }
+      self.data.next_scene = true;
+    }
+  }
+  
   pub fn update_neutral(&mut self, real_delta: f32, delta_time: f32) {
-    let space_pressed = self.data().keys.space_pressed();
+    let speed_pressed = self.action_pressed(KeyAction::CycleSpeed);
     let scroll_delta = self.data().scroll_delta;
     
-    if self.data.window_resized || self.bin >= 100 {
+    if self.data.window_resized || self.simulation.game_over().is_some() {
       self.data.next_scene = true;
     }
     
-    if self.space_pressed_last_frame && !space_pressed {
-      self.space_pressed_last_frame = false;
+    // Watching a replay the speed is whatever the player had it at
+    if self.speed_pressed_last_frame && !speed_pressed && self.playback.is_none() {
+      self.speed_pressed_last_frame = false;
       match self.game_speed {
         1 => {
-          self.game_speed = 2;
+          let _ = self.apply_command(GameCommand::Speed(2));
         },
         2 => {
-          self.game_speed = 4;
+          let _ = self.apply_command(GameCommand::Speed(4));
         },
         4 => {
-          self.game_speed = 8;
+          let _ = self.apply_command(GameCommand::Speed(8));
         },
         8 => {
-          self.game_speed = 16;
+          let _ = self.apply_command(GameCommand::Speed(16));
         },
         _ => {
-          self.game_speed = 1;
+          let _ = self.apply_command(GameCommand::Speed(1));
         }
       }
     }
-    self.space_pressed_last_frame = space_pressed;
+    self.speed_pressed_last_frame = speed_pressed;
     
     if scroll_delta > 0.0 {
       self.camera.process_movement(camera::Direction::Forward, 10.0*real_delta);
@@ -666,41 +832,20 @@ impl GameScreen {
   }
   
   pub fn update_objects(&mut self, real_delta: f32, delta_time: f32) {
-    let delta_steps = (self.total_delta / DELTA_STEP).floor() as usize;
-    
-    self.map.update(real_delta);
+    self.simulation.update_map(real_delta);
     
-    for _ in 0..delta_steps {
-      let mut some_food = None;
-      if self.map.is_ready() {
-        some_food = self.the_food_store.update(DELTA_STEP);
+    if self.playback.is_some() {
+      // Recorded commands have to land on the same tick they were made on
+      for _ in 0..self.simulation.steps_due(delta_time) {
+        self.play_recorded_commands();
+        self.simulation.step(&mut self.selected_appliance, &mut self.data.model_sizes);
       }
-      
-      if let Some(food) = some_food {
-        self.foods.push(food);
-      }
-      
-      let appliances = &mut self.appliances;
-      let foods = &mut self.foods;
-      let weapons = &mut self.weapons;
-      let m_sizes = &mut self.data.model_sizes;
-      let map = &mut self.map;
-      let bin = &mut self.bin;
-      let money = &mut self.money;
+      self.play_recorded_commands();
+    } else {
       let selected_appliance = &mut self.selected_appliance;
+      let m_sizes = &mut self.data.model_sizes;
       
-      update_game(map, appliances, foods, weapons, selected_appliance, m_sizes, DELTA_STEP);
-      collisions(map, foods, weapons, m_sizes, bin, money, DELTA_STEP);
-      
-      if self.foods.len() == 0 {
-        if self.the_food_store.next_wave() {
-          for appliance in &mut self.appliances {
-            appliance.decrease_life_expectancy();
-          }
-        }
-      }
-      
-      self.total_delta -= DELTA_STEP;
+      self.simulation.update(selected_appliance, m_sizes, delta_time);
     }
   }
   
@@ -709,12 +854,12 @@ impl GameScreen {
     let f2_pressed = self.data.keys.f2_pressed();
     
     if f1_pressed && !self.f1_pressed_last_frame {
-      self.foods.clear();
-      self.the_food_store.skip_wave();
+      self.simulation.foods.clear();
+      self.simulation.the_food_store.skip_wave();
     }
     
     if f2_pressed && !self.f2_pressed_last_frame {
-      self.money += 1000;
+      self.simulation.money += 1000;
     }
     
     
@@ -734,41 +879,76 @@ impl Scene for GameScreen {
   
   fn future_scene(&mut self, window_size: Vector2<f32>) -> Box<Scene> {
     if self.data().window_resized {
-      Box::new(GameScreen::new_with_data(window_size, self.rng.clone(), self.camera.clone(), self.screen_offset, self.appliances.clone(), self.foods.clone(), self.map.clone(), self.data.model_sizes.clone(), self.weapons.clone(), self.the_food_store.clone(), self.money, self.game_speed, self.bin))
+      let mut game = GameScreen::new_with_data(window_size, self.camera.clone(), self.screen_offset, self.simulation.clone(), self.data.model_sizes.clone(), self.game_speed);
+      game.paused = self.paused;
+      game.recording = self.recording.take();
+      game.playback = self.playback.take();
+      Box::new(game)
+    } else if let Some(outcome) = self.simulation.game_over() {
+      if self.playback.is_none() {
+        SaveGame::delete();
+        self.save_replay();
+      }
+      Box::new(ResultsScreen::new(window_size, self.data.model_sizes.clone(), self.simulation.clone(), outcome))
     } else {
+      if self.playback.is_none() {
+        self.save_replay();
+      }
       Box::new(MenuScreen::new(window_size, self.data.model_sizes.clone()))
     }
   }
   
   fn update(&mut self, delta_time: f32) {
     let real_delta = delta_time;
-    let delta_time = delta_time * self.game_speed as f32;
+    let delta_time = if self.paused { 0.0 } else { delta_time * self.game_speed as f32 };
     self.mut_data().controller.update();
-    self.total_delta += delta_time;
     
-    match &mut self.mouse_state {
-      MouseState::Ui => {
-        self.update_ui(delta_time);
-      },
-      MouseState::World => {
-        self.update_world(delta_time);
-      },
-      MouseState::Placing => {
-        self.update_placing(delta_time);
+    if let Some((_, time_left)) = &mut self.undo_message {
+      *time_left -= real_delta;
+      if *time_left <= 0.0 {
+        self.undo_message = None;
       }
     }
     
-    self.update_keypresses(real_delta);
+    let escape_pressed = self.data().keys.escape_pressed();
+    if self.escaped_pressed_last_frame && !escape_pressed {
+      if self.pause_menu.is_hidden() {
+        self.open_pause_menu();
+      } else {
+        self.resume();
+      }
+    }
+    self.escaped_pressed_last_frame = escape_pressed;
+    
+    if self.pause_menu.is_hidden() {
+      self.update_shop(real_delta);
+      
+      match &mut self.mouse_state {
+        MouseState::Ui => {
+          self.update_ui(delta_time);
+        },
+        MouseState::World => {
+          self.update_world(delta_time);
+        },
+        MouseState::Placing => {
+          self.update_placing(delta_time);
+        }
+      }
+      
+      self.update_keypresses(real_delta);
+    } else {
+      self.update_pause_menu(real_delta);
+    }
     
     self.update_objects(real_delta, delta_time);
     
     self.update_neutral(real_delta, delta_time);
     
-    if !self.map.is_ready() {
+    if !self.simulation.map.is_ready() {
       self.placing_appliance = None;
       self.selected_appliance = None;
       self.mouse_state = MouseState::World;
-      self.game_speed = 0;
+      self.paused = true;
     }
     
     if DEV {
@@ -779,16 +959,16 @@ impl Scene for GameScreen {
   fn draw(&self, draw_calls: &mut Vec<DrawCall>) {
     draw_calls.push(DrawCall::set_camera(self.camera.clone()));
     
-    for food in &self.foods {
+    for food in &self.simulation.foods {
       food.draw(draw_calls);
     }
     
-    for appliance in &self.appliances {
-      let map = &self.map;
+    for appliance in &self.simulation.appliances {
+      let map = &self.simulation.map;
       appliance.draw(map, &self.camera, Vector2::new(self.data.window_dim.x as f32, self.data.window_dim.y as f32), draw_calls);
     }
     
-    for weapon in &self.weapons {
+    for weapon in &self.simulation.weapons {
       weapon.draw(draw_calls);
     }
     
@@ -806,7 +986,7 @@ impl Scene for GameScreen {
       model_size
     };
     
-    self.map.draw(hexagon_model_size, cam_pos.xz(), draw_calls);
+    self.simulation.map.draw(hexagon_model_size, cam_pos.xz(), draw_calls);
     
     let offset = 32.0;
     
@@ -814,10 +994,10 @@ impl Scene for GameScreen {
       MouseState::Placing => {
         if let Some(appliance) = &self.placing_appliance {
           if self.valid_place {
-            let map = &self.map;
-            let some_hex = self.map.get_hex_from_qr(appliance.get_qr_location().x, appliance.get_qr_location().y);
+            let map = &self.simulation.map;
+            let some_hex = self.simulation.map.get_hex_from_qr(appliance.get_qr_location().x, appliance.get_qr_location().y);
             if let Some(hex) = some_hex {
-              if hex.is_path() || !hex.is_open()|| self.money < appliance.buy_cost() {
+              if hex.is_path() || !hex.is_open()|| self.simulation.money < appliance.buy_cost() {
                 appliance.draw_hologram_invalid(map, draw_calls);
               } else {
                 appliance.draw_hologram(map, draw_calls);
@@ -828,38 +1008,63 @@ impl Scene for GameScreen {
       },
       _ => {
         if let Some(idx) = self.selected_appliance {
-          let map = &self.map;
+          let map = &self.simulation.map;
           
-          self.appliances[idx].draw_range_coloured(map, Vector3::new(0.0, 0.0, 1.0), draw_calls);
+          self.simulation.appliances[idx].draw_range_coloured(map, Vector3::new(0.0, 0.0, 1.0), draw_calls);
           
-          let clean_price = self.appliances[idx].clean_cost();
-          let sell_price = self.appliances[idx].sell_price();
+          let clean_price = self.simulation.appliances[idx].clean_cost();
+          let sell_price = self.simulation.appliances[idx].sell_price();
           
           // UI 
+          let upgrade_keys = [self.key_bindings.key(KeyAction::UpgradeFirstPath), self.key_bindings.key(KeyAction::UpgradeSecondPath)];
+          let paths = self.simulation.appliances[idx].upgrade_paths();
+          for i in 0..paths.len().min(upgrade_keys.len()) {
+            let tier = self.simulation.appliances[idx].upgrade_tier(i);
+            let progress = paths[i].name.to_owned() + " " + &tier.to_string() + "/" + &paths[i].tiers.len().to_string();
+            
+            let (text, colour) = {
+              match self.simulation.appliances[idx].next_upgrade(i) {
+                Some(next) => {
+                  let colour = if next.cost > self.simulation.money { Vector4::new(1.0, 0.0, 0.0, 1.0) } else { Vector4::new(0.7, 1.0, 1.0, 1.0) };
+                  ("Key ".to_owned() + upgrade_keys[i] + ": " + &progress + ", " + next.name + " $" + &next.cost.to_string(), colour)
+                },
+                None => {
+                  (progress + " maxed", Vector4::new(0.7, 0.7, 0.7, 1.0))
+                },
+              }
+            };
+            
+            draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, self.data.window_dim.y*0.5+offset*(8.0-i as f32)), 
+                                           Vector2::new(64.0, 64.0), 
+                                           colour, 
+                                           text, 
+                                           "Arial".to_string()));
+          }
+          
           draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, self.data.window_dim.y*0.5+offset*6.0), 
                                            Vector2::new(64.0, 64.0), 
                                            Vector4::new(0.7, 1.0, 1.0, 1.0), 
-                                           "Key t: Cycle targeting priority".to_string(), 
+                                           "Key ".to_owned() + self.key_bindings.key(KeyAction::CycleTargeting) + ": Cycle targeting priority", 
                                            "Arial".to_string()));
           
           draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, self.data.window_dim.y*0.5+offset*5.0), 
                                            Vector2::new(64.0, 64.0), 
                                            Vector4::new(0.7, 1.0, 1.0, 1.0), 
-                                           "Key X: Sell appliance $".to_owned() + &(sell_price).to_string(), 
+                                           "Key ".to_owned() + self.key_bindings.key(KeyAction::Sell) + ": Sell appliance $" + &(sell_price).to_string(), 
                                            "Arial".to_string()));
           let mut colour = Vector4::new(0.7, 1.0, 1.0, 1.0);
-          if clean_price > self.money {
+          if clean_price > self.simulation.money {
             colour = Vector4::new(1.0, 0.0, 0.0, 1.0);
           }
           draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, self.data.window_dim.y*0.5+offset*4.0), 
                                            Vector2::new(64.0, 64.0), 
                                            colour, 
-                                           "Key C: Cleans appliance $".to_owned() + &(clean_price).to_string(), 
+                                           "Key ".to_owned() + self.key_bindings.key(KeyAction::Clean) + ": Cleans appliance $" + &(clean_price).to_string(), 
                                            "Arial".to_string()));
           draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, self.data.window_dim.y*0.5+offset*3.0), 
                                            Vector2::new(64.0, 64.0), 
                                            Vector4::new(0.7, 1.0, 1.0, 1.0), 
-                                           "Key M: Moves selected appliance".to_string(), 
+                                           "Key ".to_owned() + self.key_bindings.key(KeyAction::Move) + ": Moves selected appliance", 
                                            "Arial".to_string()));
         }
       },
@@ -868,128 +1073,51 @@ impl Scene for GameScreen {
     /* 
     ** UI
     */
+    let bin_full = self.simulation.bin as f32/self.simulation.bin_capacity() as f32;
     draw_calls.push(DrawCall::draw_text_basic(Vector2::new(self.data.window_dim.x-264.0, self.data.window_dim.y-offset), 
                                            Vector2::new(96.0, 96.0), 
-                                           Vector4::new((self.bin as f32/100.0), 1.0-(self.bin as f32/100.0), 0.0, 1.0), 
-                                           "The Bin is ".to_owned() + &(self.bin).to_string() + "% full", 
+                                           Vector4::new(bin_full, 1.0-bin_full, 0.0, 1.0), 
+                                           "The Bin is ".to_owned() + &((bin_full*100.0) as i32).to_string() + "% full", 
                                            "Arial".to_string()));
     draw_calls.push(DrawCall::draw_text_basic(Vector2::new(self.data.window_dim.x-196.0, self.data.window_dim.y-offset*2.0), 
                                            Vector2::new(96.0, 96.0), 
                                            Vector4::new(1.0, 1.0, 1.0, 1.0), 
-                                           "Money $".to_owned() + &(self.money).to_string(), 
+                                           "Money $".to_owned() + &(self.simulation.money).to_string(), 
                                            "Arial".to_string()));
     draw_calls.push(DrawCall::draw_text_basic_centered(Vector2::new(self.data.window_dim.x-160.0, self.data.window_dim.y-offset*3.0), 
                                            Vector2::new(96.0, 96.0), 
                                            Vector4::new(1.0, 1.0, 1.0, 1.0), 
-                                           "Key B: Empty Bin $".to_owned() + &(BIN_CLEAN_COST).to_string(), 
-                                           "Arial".to_string()));
-    draw_calls.push(DrawCall::draw_text_basic_centered(Vector2::new(self.data.window_dim.x*0.5, self.data.window_dim.y-32.0), 
-                                           Vector2::new(132.0, 132.0), 
-                                           Vector4::new(1.0, 1.0, 1.0, 1.0), 
-                                           "Wave: ".to_owned() + &(self.the_food_store.wave_number() + 1).to_string(), 
-                                           "Arial".to_string()));
-                                           
-    let t_dishwasher = Dishwasher::new(Vector2::new(0,0), Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0), &self.map);
-    let t_fridge = Fridge::new(Vector2::new(0,0), Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0), &self.map);
-    let t_tenderiser = MeatTenderizer::new(Vector2::new(0,0), Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0), &self.map);
-    let t_coffee = CoffeeMachine::new(Vector2::new(0,0), Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0), &self.map);
-    let t_salt_grinder = SaltGrinder::new(Vector2::new(0,0), Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0), &self.map);
-    
-    let dishwasher_cost = t_dishwasher.buy_cost();
-    let fridge_cost = t_fridge.buy_cost();
-    let tenderiser_cost = t_tenderiser.buy_cost();
-    let coffee_cost = t_coffee.buy_cost();
-    let salt_grinder_cost = t_salt_grinder.buy_cost();
-    
-    let mut colour = Vector4::new(1.0, 1.0, 1.0, 1.0);
-    if dishwasher_cost > self.money {
-      colour = Vector4::new(1.0, 0.0, 0.0, 1.0);
-    }
-    draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, self.data.window_dim.y*0.5+offset*2.0), 
-                                           Vector2::new(64.0, 64.0), 
-                                           colour, 
-                                           "Key 1: Buy Dishwasher $".to_owned() + &(dishwasher_cost).to_string(), 
-                                           "Arial".to_string()));
-   
-   if !self.minimal_ui {
-     draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, self.data.window_dim.y*0.5+offset*1.0), 
-                                           Vector2::new(64.0, 64.0), 
-                                           colour, 
-                                           "  (Range: 3, LE: 2, Single Shot, Medium firing)".to_owned(), 
+                                           "Key ".to_owned() + self.key_bindings.key(KeyAction::EmptyBin) + ": Empty Bin $" + &(BIN_CLEAN_COST).to_string(),
                                            "Arial".to_string()));
-    }
-    
-    colour = Vector4::new(1.0, 1.0, 1.0, 1.0);
-    if fridge_cost > self.money {
-      colour = Vector4::new(1.0, 0.0, 0.0, 1.0);
-    }
-    draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, self.data.window_dim.y*0.5+offset*0.0), 
-                                           Vector2::new(64.0, 64.0), 
-                                           colour, 
-                                           "Key 2: Buy Fridge $".to_owned() + &(fridge_cost).to_string(),
-                                           "Arial".to_string()));
-    
-    if !self.minimal_ui {
-      draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, self.data.window_dim.y*0.5-offset*1.0), 
-                                           Vector2::new(64.0, 64.0), 
-                                           colour, 
-                                           "  (Range: 1, LE: 3, All Hex, Slow firing)".to_string(),
+    if let (Some(entry), None) = (self.simulation.next_undo(), &self.playback) {
+      draw_calls.push(DrawCall::draw_text_basic_centered(Vector2::new(self.data.window_dim.x-160.0, self.data.window_dim.y-offset*4.0),
+                                           Vector2::new(96.0, 96.0),
+                                           Vector4::new(0.7, 1.0, 1.0, 1.0),
+                                           "Key ".to_owned() + self.key_bindings.key(KeyAction::Undo) + ": Undo " + entry.action.name(),
                                            "Arial".to_string()));
     }
-    
-    colour = Vector4::new(1.0, 1.0, 1.0, 1.0);
-    if tenderiser_cost > self.money {
-      colour = Vector4::new(1.0, 0.0, 0.0, 1.0);
-    }
-    draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, self.data.window_dim.y*0.5-offset*2.0), 
-                                           Vector2::new(64.0, 64.0), 
-                                           colour, 
-                                           "Key 3: Buy MeatTenderizer $".to_owned() + &(tenderiser_cost).to_string(),
-                                           "Arial".to_string()));
-    
-    if !self.minimal_ui {
-      draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, self.data.window_dim.y*0.5-offset*3.0), 
-                                           Vector2::new(64.0, 64.0), 
-                                           colour, 
-                                           "  (Range: 1, LE: 4, Hex in directions of hex faces, Very Slow firing)".to_string(),
-                                           "Arial".to_string()));
-    }
-    
-    colour = Vector4::new(1.0, 1.0, 1.0, 1.0);
-    if coffee_cost > self.money {
-      colour = Vector4::new(1.0, 0.0, 0.0, 1.0);
-    }
-    draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, self.data.window_dim.y*0.5-offset*4.0), 
-                                           Vector2::new(64.0, 64.0), 
-                                           colour, 
-                                           "Key 4: Buy Coffee Machine $".to_owned() + &(coffee_cost).to_string(),
-                                           "Arial".to_string()));
-    
-    if !self.minimal_ui {
-      draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, self.data.window_dim.y*0.5-offset*5.0), 
-                                           Vector2::new(64.0, 64.0), 
-                                           colour, 
-                                           "  (Range: 2, LE: 5, Buffs: Range up, LE up, AS up, Sell price down)".to_string(),
+    if let Some((message, _)) = &self.undo_message {
+      draw_calls.push(DrawCall::draw_text_basic_centered(Vector2::new(self.data.window_dim.x-160.0, self.data.window_dim.y-offset*5.0),
+                                           Vector2::new(96.0, 96.0),
+                                           Vector4::new(1.0, 0.4, 0.4, 1.0),
+                                           message.to_string(),
                                            "Arial".to_string()));
     }
-    
-    colour = Vector4::new(1.0, 1.0, 1.0, 1.0);
-    if salt_grinder_cost > self.money {
-      colour = Vector4::new(1.0, 0.0, 0.0, 1.0);
-    }
-    draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, self.data.window_dim.y*0.5-offset*6.0), 
-                                           Vector2::new(64.0, 64.0), 
-                                           colour, 
-                                           "Key 5: Buy Salt Grinder $".to_owned() + &(salt_grinder_cost).to_string(),
-                                           "Arial".to_string()));
-    
-    if !self.minimal_ui {
-      draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, self.data.window_dim.y*0.5-offset*7.0), 
-                                           Vector2::new(64.0, 64.0), 
-                                           colour, 
-                                           "  (Range: 2, LE: 3, multi Shot, fast firing)".to_string(),
+    let wave_number = (self.simulation.the_food_store.wave_number() + 1).to_string();
+    let wave_text = {
+      if self.simulation.the_food_store.is_endless() {
+        "Endless Wave: ".to_owned() + &wave_number
+      } else {
+        "Wave: ".to_owned() + &wave_number
+      }
+    };
+    draw_calls.push(DrawCall::draw_text_basic_centered(Vector2::new(self.data.window_dim.x*0.5, self.data.window_dim.y-32.0), 
+                                           Vector2::new(132.0, 132.0), 
+                                           Vector4::new(1.0, 1.0, 1.0, 1.0), 
+                                           wave_text, 
                                            "Arial".to_string()));
-    }
+                                           
+    self.shop.draw(draw_calls);
     
     // Game Speed
     
@@ -1002,27 +1130,27 @@ impl Scene for GameScreen {
       draw_calls.push(DrawCall::draw_text_basic_centered(Vector2::new(128.0, 80.0), 
                                            Vector2::new(96.0, 96.0), 
                                            Vector4::new(1.0, 1.0, 1.0, 1.0),
-                                           "Key v: resets camera".to_string(), 
+                                           "Key ".to_owned() + self.key_bindings.key(KeyAction::ResetCamera) + ": resets camera", 
                                            "Arial".to_string()));
       draw_calls.push(DrawCall::draw_text_basic_centered(Vector2::new(164.0, 48.0), 
                                            Vector2::new(96.0, 96.0), 
                                            Vector4::new(1.0, 1.0, 1.0, 1.0),
-                                           "Key k: resets current map".to_string(), 
+                                           "Key ".to_owned() + self.key_bindings.key(KeyAction::Restart) + ": resets current map", 
                                            "Arial".to_string()));
       draw_calls.push(DrawCall::draw_text_basic_centered(Vector2::new(self.data.window_dim.x - 160.0, 128.0), 
                                            Vector2::new(96.0, 96.0), 
                                            Vector4::new(1.0, 1.0, 1.0, 1.0), 
-                                           "Key F10 for minimal ui".to_string(), 
+                                           "Key ".to_owned() + self.key_bindings.key(KeyAction::ToggleMinimalUi) + " for minimal ui", 
                                            "Arial".to_string()));
       draw_calls.push(DrawCall::draw_text_basic_centered(Vector2::new(self.data.window_dim.x - 160.0, 96.0), 
                                            Vector2::new(96.0, 96.0), 
                                            Vector4::new(1.0, 1.0, 1.0, 1.0), 
-                                           "Key P to pause".to_string(), 
+                                           "Key ".to_owned() + self.key_bindings.key(KeyAction::TogglePause) + " to pause, Esc for menu", 
                                            "Arial".to_string()));
       draw_calls.push(DrawCall::draw_text_basic_centered(Vector2::new(self.data.window_dim.x - 216.0, 64.0), 
                                            Vector2::new(96.0, 96.0), 
                                            Vector4::new(1.0, 1.0, 1.0, 1.0), 
-                                           "Key Space for 1x/2x/.../16x speed".to_string(), 
+                                           "Key ".to_owned() + self.key_bindings.key(KeyAction::CycleSpeed) + " for 1x/2x/.../16x speed", 
                                            "Arial".to_string()));
     }
     
@@ -1031,8 +1159,20 @@ impl Scene for GameScreen {
                                            Vector4::new(1.0, 1.0, 1.0, 1.0), 
                                            "Speed: x".to_owned() + &(self.game_speed).to_string(), 
                                            "Arial".to_string()));
+    draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, 40.0), 
+                                           Vector2::new(64.0, 64.0), 
+                                           Vector4::new(0.7, 0.7, 0.7, 1.0), 
+                                           "Seed: ".to_owned() + &self.simulation.seed().to_string(), 
+                                           "Arial".to_string()));
+    if let Some(player) = &self.playback {
+      draw_calls.push(DrawCall::draw_text_basic(Vector2::new(16.0, 64.0), 
+                                           Vector2::new(64.0, 64.0), 
+                                           Vector4::new(1.0, 1.0, 0.0, 1.0), 
+                                           "Replay: ".to_owned() + &player.commands_played().to_string() + "/" + &player.total_commands().to_string() + " commands", 
+                                           "Arial".to_string()));
+    }
     
-    if self.game_speed == 0 && self.map.is_ready() {
+    if self.paused && self.simulation.map.is_ready() && self.pause_menu.is_hidden() {
       draw_calls.push(DrawCall::draw_text_basic_centered(Vector2::new(self.data.window_dim.x*0.5, self.data.window_dim.y*0.5),
                                            Vector2::new(196.0, 196.0), 
                                            Vector4::new(1.0, 0.0, 1.0, 1.0), 
@@ -1051,6 +1191,7 @@ impl Scene for GameScreen {
     draw_calls.push(DrawCall::draw_instanced_model("MeatTenderizer".to_string()));
     draw_calls.push(DrawCall::draw_instanced_model("CoffeeMachine".to_string()));
     draw_calls.push(DrawCall::draw_instanced_model("SaltGrinder".to_string()));
+    draw_calls.push(DrawCall::draw_instanced_model("CutleryDrawer".to_string()));
     
     draw_calls.push(DrawCall::draw_instanced_model("Spoon".to_string()));
     draw_calls.push(DrawCall::draw_instanced_model("Plate".to_string()));
@@ -1061,5 +1202,7 @@ impl Scene for GameScreen {
     draw_calls.push(DrawCall::draw_instanced_model("Cake".to_string()));
     draw_calls.push(DrawCall::draw_instanced_model("Pineapple".to_string()));
     draw_calls.push(DrawCall::draw_instanced_model("Mushroom".to_string()));
+    
+    self.pause_menu.draw(draw_calls);
   }
 }
@@ -87,6 +87,7 @@ impl Scene for LoadScreen {
       draw_calls.push(DrawCall::load_model("CoffeeMachine".to_string()));
       draw_calls.push(DrawCall::load_model("Mushroom".to_string()));
       draw_calls.push(DrawCall::load_model("SaltGrinder".to_string()));
+      draw_calls.push(DrawCall::load_model("CutleryDrawer".to_string()));
       draw_calls.push(DrawCall::load_model("Salt".to_string()));
     }
     
@@ -3,8 +3,21 @@ use maat_graphics::DrawCall;
 use crate::modules::scenes::Scene;
 use crate::modules::scenes::SceneData;
 use crate::modules::scenes::GameScreen;
+use crate::modules::scenes::ErrorScreen;
+use crate::modules::scenes::EditorScreen;
+use crate::modules::scenes::editor_screen::EDITOR_MAP_NAME;
 
 use crate::modules::system_interface::MainMenuUserInterface;
+use crate::modules::map::{Map, MapInfo, MapError};
+use crate::modules::map_file::{self, MAP_DIRECTORY};
+use crate::modules::wave_file::WaveFile;
+
+use crate::modules::simulation::Simulation;
+use crate::modules::save_game::SaveGame;
+use crate::modules::replay::Replay;
+
+use rand::{thread_rng, Rng, SeedableRng};
+use rand::rngs::StdRng;
 
 use cgmath::{Vector2, Vector3};
 
@@ -12,12 +25,16 @@ enum MapName {
   Easy,
   Medium,
   Hard,
+  Random(i32),
 }
 
 pub struct MenuScreen {
   data: SceneData,
   ui: MainMenuUserInterface,
   map_name: MapName,
+  open_editor: bool,
+  continue_game: bool,
+  watch_replay: bool,
 }
 
 impl MenuScreen {
@@ -28,6 +45,9 @@ impl MenuScreen {
       data: SceneData::new(window_size, model_sizes),
       ui: MainMenuUserInterface::new(window_size),
       map_name: MapName::Medium,
+      open_editor: false,
+      continue_game: false,
+      watch_replay: false,
     }
   }
 }
@@ -44,22 +64,90 @@ impl Scene for MenuScreen {
   fn future_scene(&mut self, window_size: Vector2<f32>) -> Box<Scene> {
     if self.data().window_resized {
       Box::new(MenuScreen::new(window_size, self.data.model_sizes.clone()))
+    } else if self.open_editor {
+      // Carry on with the last saved custom map, or start a blank one
+      let file_name = map_file::numbered_map_names(MAP_DIRECTORY, EDITOR_MAP_NAME).pop().unwrap_or(EDITOR_MAP_NAME.to_string());
+      let map = match Map::new(file_name.to_owned() + ".ron") {
+        Ok(map) => map,
+        Err(MapError::MissingFile(_)) => {
+          Map::new_empty(self.ui.random_radius(), MapInfo::new(file_name.clone()))
+        },
+        Err(e) => {
+          println!("Failed to load map: {}", e);
+          return Box::new(ErrorScreen::new(window_size, self.data.model_sizes.clone(), e.to_string()));
+        },
+      };
+      
+      Box::new(EditorScreen::new(window_size, self.data.model_sizes.clone(), map, file_name))
+    } else if self.continue_game {
+      let game = SaveGame::load().map_err(|e| "Unable to read save: ".to_owned() + &e)
+                   .and_then(|save| GameScreen::continue_game(window_size, self.data.model_sizes.clone(), save)
+                                      .map_err(|e| "Unable to load saved map: ".to_owned() + &e.to_string()));
+      match game {
+        Ok(game) => {
+          Box::new(game)
+        },
+        Err(e) => {
+          println!("{}", e);
+          Box::new(ErrorScreen::new(window_size, self.data.model_sizes.clone(), e))
+        },
+      }
+    } else if self.watch_replay {
+      let game = Replay::load().map_err(|e| "Unable to read replay: ".to_owned() + &e)
+                   .and_then(|replay| GameScreen::play_replay(window_size, self.data.model_sizes.clone(), replay)
+                                        .map_err(|e| "Unable to load replay map: ".to_owned() + &e.to_string()));
+      match game {
+        Ok(game) => {
+          Box::new(game)
+        },
+        Err(e) => {
+          println!("{}", e);
+          Box::new(ErrorScreen::new(window_size, self.data.model_sizes.clone(), e))
+        },
+      }
     } else {
-      let map_name = {
+      let seed = self.ui.seed().unwrap_or(thread_rng().gen::<u64>());
+      println!("Seed: {}", seed);
+      
+      let loaded_map = {
         match self.map_name {
           MapName::Easy => {
-            "EasyMap.ini".to_string()
+            Map::new("EasyMap.ron".to_string())
           },
           MapName::Medium => {
-            "MediumMap.ini".to_string()
+            Map::new("MediumMap.ron".to_string())
           },
           MapName::Hard => {
-            "HardMap.ini".to_string()
+            Map::new("HardMap.ron".to_string())
+          },
+          MapName::Random(radius) => {
+            Ok(Map::new_random_map(radius, &mut StdRng::seed_from_u64(seed)))
           },
         }
       };
       
-      Box::new(GameScreen::new(window_size, self.data.model_sizes.clone(), map_name))
+      match loaded_map {
+        Ok(map) => {
+          match WaveFile::load_set(&map.get_info().wave_set) {
+            Ok(waves) => {
+              let mut simulation = Simulation::new(map, waves, seed);
+              if self.ui.endless() {
+                simulation.set_endless();
+              }
+              
+              Box::new(GameScreen::new(window_size, self.data.model_sizes.clone(), simulation))
+            },
+            Err(e) => {
+              println!("Failed to load waves: {}", e);
+              Box::new(ErrorScreen::new(window_size, self.data.model_sizes.clone(), e.to_string()))
+            },
+          }
+        },
+        Err(e) => {
+          println!("Failed to load map: {}", e);
+          Box::new(ErrorScreen::new(window_size, self.data.model_sizes.clone(), e.to_string()))
+        },
+      }
     }
   }
   
@@ -91,6 +179,34 @@ impl Scene for MenuScreen {
       self.mut_data().next_scene = true;
     }
     
+    if self.ui.random_button_pressed() {
+      self.map_name = MapName::Random(self.ui.random_radius());
+      self.mut_data().next_scene = true;
+    }
+    
+    if self.ui.editor_button_pressed() {
+      self.open_editor = true;
+      self.mut_data().next_scene = true;
+    }
+    
+    if self.ui.continue_button_pressed() {
+      if SaveGame::exists() {
+        self.continue_game = true;
+        self.mut_data().next_scene = true;
+      } else {
+        println!("No saved game to continue");
+      }
+    }
+    
+    if self.ui.replay_button_pressed() {
+      if Replay::exists() {
+        self.watch_replay = true;
+        self.mut_data().next_scene = true;
+      } else {
+        println!("No replay to watch");
+      }
+    }
+    
     if self.ui.options_button_pressed() {
       println!("optins button pressed");
       self.ui.show_options_menu();
@@ -14,10 +14,16 @@ use cgmath::{Vector2, Vector3};
 pub use self::load_screen::LoadScreen;
 pub use self::menu_screen::MenuScreen;
 pub use self::game_screen::GameScreen;
+pub use self::error_screen::ErrorScreen;
+pub use self::editor_screen::EditorScreen;
+pub use self::results_screen::ResultsScreen;
 
 mod load_screen;
 mod menu_screen;
 mod game_screen;
+mod error_screen;
+pub mod editor_screen;
+mod results_screen;
 
 pub struct SceneData {
   pub should_close: bool,
@@ -88,6 +94,60 @@ impl SceneData {
     self.mouse_pos = mouse_position;
   }
   
+  // Whether a key is down, going by the names used for key bindings
+  pub fn key_pressed(&self, key: &str) -> bool {
+    match key {
+      "A" => self.keys.a_pressed(),
+      "B" => self.keys.b_pressed(),
+      "C" => self.keys.c_pressed(),
+      "D" => self.keys.d_pressed(),
+      "E" => self.keys.e_pressed(),
+      "F" => self.keys.f_pressed(),
+      "G" => self.keys.g_pressed(),
+      "H" => self.keys.h_pressed(),
+      "I" => self.keys.i_pressed(),
+      "J" => self.keys.j_pressed(),
+      "K" => self.keys.k_pressed(),
+      "L" => self.keys.l_pressed(),
+      "M" => self.keys.m_pressed(),
+      "N" => self.keys.n_pressed(),
+      "O" => self.keys.o_pressed(),
+      "P" => self.keys.p_pressed(),
+      "Q" => self.keys.q_pressed(),
+      "R" => self.keys.r_pressed(),
+      "S" => self.keys.s_pressed(),
+      "T" => self.keys.t_pressed(),
+      "U" => self.keys.u_pressed(),
+      "V" => self.keys.v_pressed(),
+      "W" => self.keys.w_pressed(),
+      "X" => self.keys.x_pressed(),
+      "Y" => self.keys.y_pressed(),
+      "Z" => self.keys.z_pressed(),
+      "0" => self.keys.zero_pressed(),
+      "1" => self.keys.one_pressed(),
+      "2" => self.keys.two_pressed(),
+      "3" => self.keys.three_pressed(),
+      "4" => self.keys.four_pressed(),
+      "5" => self.keys.five_pressed(),
+      "6" => self.keys.six_pressed(),
+      "7" => self.keys.seven_pressed(),
+      "8" => self.keys.eight_pressed(),
+      "9" => self.keys.nine_pressed(),
+      "Space" => self.keys.space_pressed(),
+      "F3" => self.keys.f3_pressed(),
+      "F4" => self.keys.f4_pressed(),
+      "F5" => self.keys.f5_pressed(),
+      "F6" => self.keys.f6_pressed(),
+      "F7" => self.keys.f7_pressed(),
+      "F8" => self.keys.f8_pressed(),
+      "F9" => self.keys.f9_pressed(),
+      "F10" => self.keys.f10_pressed(),
+      "F11" => self.keys.f11_pressed(),
+      "F12" => self.keys.f12_pressed(),
+      _ => false,
+    }
+  }
+  
   pub fn update_window_dim(&mut self, dim: Vector2<f32>) {
     if self.window_dim != dim {
       self.window_resized = true;
@@ -0,0 +1,211 @@
+use maat_graphics::DrawCall;
+
+use crate::modules::scenes::Scene;
+use crate::modules::scenes::SceneData;
+use crate::modules::scenes::MenuScreen;
+use crate::modules::scenes::GameScreen;
+
+use crate::modules::system_interface::Widget;
+use crate::modules::simulation::{Simulation, GameOver};
+
+use rand::{thread_rng, Rng};
+
+use cgmath::{Vector2, Vector3, Vector4};
+
+const RESULTS_WINDOW_INDEX: usize = 0;
+
+const RESULTS_WINDOW_NAME: &str = "ResultsWindow";
+const TITLE_NAME: &str = "ResultsTitle";
+const WAVES_NAME: &str = "WavesCleared";
+const MONEY_NAME: &str = "MoneyEarned";
+const FOODS_NAME: &str = "FoodsCooked";
+const APPLIANCES_NAME: &str = "AppliancesBought";
+const RETRY_BUTTON_NAME: &str = "Retry";
+const SAME_MAP_BUTTON_NAME: &str = "SameMap";
+const MENU_BUTTON_NAME: &str = "Menu";
+
+enum NextScene {
+  Results,
+  Retry,
+  SameMap,
+  Menu,
+}
+
+pub struct ResultsScreen {
+  data: SceneData,
+  simulation: Simulation,
+  outcome: GameOver,
+  widgets: Vec<Widget>,
+  next: NextScene,
+}
+
+impl ResultsScreen {
+  pub fn new(window_size: Vector2<f32>, model_sizes: Vec<(String, Vector3<f32>)>, simulation: Simulation, outcome: GameOver) -> ResultsScreen {
+    println!("Results Screen");
+    
+    let window_width = 640.0;
+    let window_height = 400.0;
+    
+    let button_width = 128.0;
+    let button_height = 32.0;
+    let line_height = 40.0;
+    
+    let text_colour = Vector4::new(0.0, 0.0, 0.0, 1.0);
+    let button_colour = Vector4::new(0.5019, 0.749, 1.0, 1.0);
+    let background_colour = Vector4::new(0.0, 0.1411, 0.4, 1.0);
+    let white = Vector4::new(1.0, 1.0, 1.0, 1.0);
+    
+    let (title, title_colour) = {
+      match outcome {
+        GameOver::Victory => ("Victory!", Vector4::new(0.0, 1.0, 0.0, 1.0)),
+        GameOver::Defeat => ("The bin is full", Vector4::new(1.0, 0.0, 0.0, 1.0)),
+      }
+    };
+    
+    let waves_text = {
+      if simulation.the_food_store.is_endless() {
+        "Reached wave ".to_owned() + &(simulation.the_food_store.wave_number()+1).to_string()
+      } else {
+        "Waves cleared: ".to_owned() + &simulation.waves_cleared().to_string()
+      }
+    };
+    
+    let stats = simulation.stats.clone();
+    let stat_lines = vec!(
+      (WAVES_NAME, waves_text),
+      (MONEY_NAME, "Money earned: $".to_owned() + &stats.money_earned.to_string()),
+      (FOODS_NAME, "Foods cooked: ".to_owned() + &stats.foods_cooked.to_string() +
+                   "  binned: " + &stats.foods_binned.to_string()),
+      (APPLIANCES_NAME, "Appliances bought: ".to_owned() + &stats.appliances_bought.to_string()),
+    );
+    
+    let mut results = Widget::new(RESULTS_WINDOW_NAME.to_string(), Vector2::new(window_size.x*0.5, window_size.y*0.5),
+                                  Vector2::new(window_width, window_height),
+                                  background_colour)
+                        .with_text_field_centered(TITLE_NAME.to_string(),
+                                                  Vector2::new(window_width*0.5, window_height-64.0),
+                                                  Vector2::new(192.0, 192.0),
+                                                  title_colour,
+                                                  title.to_string(), "Arial".to_string());
+    
+    for i in 0..stat_lines.len() {
+      let (name, text) = stat_lines[i].clone();
+      results = results.with_text_field_centered(name.to_string(),
+                                                 Vector2::new(window_width*0.5, window_height-128.0-line_height*i as f32),
+                                                 Vector2::new(128.0, 128.0),
+                                                 white,
+                                                 text, "Arial".to_string());
+    }
+    
+    let buttons = vec!(
+      (RETRY_BUTTON_NAME, "Retry"),
+      (SAME_MAP_BUTTON_NAME, "Same Map"),
+      (MENU_BUTTON_NAME, "Menu"),
+    );
+    
+    for i in 0..buttons.len() {
+      let (name, text) = buttons[i];
+      results = results.with_button(Vector2::new(window_width*0.5+button_width*1.2*(i as f32-1.0), 48.0),
+                                    Vector2::new(button_width, button_height),
+                                    Vector2::new(button_width*0.5, button_height*0.33),
+                                    Vector2::new(128.0, 128.0),
+                                    name.to_string(),
+                                    text_colour,
+                                    Vector4::new(1.0, 0.0, 0.0, 1.0),
+                                    button_colour,
+                                    true, text.to_string(), "Arial".to_string());
+    }
+    
+    ResultsScreen {
+      data: SceneData::new(window_size, model_sizes),
+      simulation,
+      outcome,
+      widgets: vec!(results),
+      next: NextScene::Results,
+    }
+  }
+}
+
+impl Scene for ResultsScreen {
+  fn data(&self) -> &SceneData {
+    &self.data
+  }
+  
+  fn mut_data(&mut self) -> &mut SceneData {
+    &mut self.data
+  }
+  
+  fn future_scene(&mut self, window_size: Vector2<f32>) -> Box<Scene> {
+    match self.next {
+      NextScene::Results => {
+        Box::new(ResultsScreen::new(window_size, self.data.model_sizes.clone(), self.simulation.clone(), self.outcome))
+      },
+      NextScene::Retry => {
+        // Same map, same waves and the same seed
+        let mut simulation = self.simulation.clone();
+        simulation.reset();
+        Box::new(GameScreen::new(window_size, self.data.model_sizes.clone(), simulation))
+      },
+      NextScene::SameMap => {
+        // Same map with a new seed
+        let mut simulation = self.simulation.clone();
+        let seed = thread_rng().gen::<u64>();
+        println!("Seed: {}", seed);
+        simulation.set_seed(seed);
+        simulation.reset();
+        Box::new(GameScreen::new(window_size, self.data.model_sizes.clone(), simulation))
+      },
+      NextScene::Menu => {
+        Box::new(MenuScreen::new(window_size, self.data.model_sizes.clone()))
+      },
+    }
+  }
+  
+  fn update(&mut self, delta_time: f32) {
+    let mouse = self.data().mouse_pos;
+    let left_clicked = self.data().left_mouse;
+    let scroll_delta = self.data().scroll_delta;
+    let keys_pressed_this_frame = self.get_keys_pressed_this_frame();
+    
+    for widget in &mut self.widgets {
+      widget.update(delta_time, mouse, left_clicked, &keys_pressed_this_frame, scroll_delta);
+    }
+    
+    if self.data().window_resized {
+      self.next = NextScene::Results;
+      self.mut_data().next_scene = true;
+    }
+    
+    if self.widgets[RESULTS_WINDOW_INDEX].get_button_state(&RETRY_BUTTON_NAME.to_string()) {
+      self.next = NextScene::Retry;
+      self.mut_data().next_scene = true;
+    }
+    
+    if self.widgets[RESULTS_WINDOW_INDEX].get_button_state(&SAME_MAP_BUTTON_NAME.to_string()) {
+      self.next = NextScene::SameMap;
+      self.mut_data().next_scene = true;
+    }
+    
+    if self.widgets[RESULTS_WINDOW_INDEX].get_button_state(&MENU_BUTTON_NAME.to_string()) || self.data().keys.escape_pressed() {
+      self.next = NextScene::Menu;
+      self.mut_data().next_scene = true;
+    }
+  }
+  
+  fn draw(&self, draw_calls: &mut Vec<DrawCall>) {
+    let dim = self.data().window_dim;
+    let (width, height) = (dim.x as f32, dim.y as f32);
+    
+    draw_calls.push(
+        DrawCall::draw_coloured(Vector2::new(width*0.5, height*0.5),
+                                Vector2::new(width*5.0, height*5.0),
+                                Vector4::new(0.0, 0.0, 0.0, 1.0),
+                                90.0)
+    );
+    
+    for widget in &self.widgets {
+      widget.draw(draw_calls);
+    }
+    draw_calls.push(DrawCall::reset_ortho_camera());
+  }
+}
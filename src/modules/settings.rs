@@ -0,0 +1,371 @@
+use crate::modules::undo::UNDO_WINDOW;
+use crate::modules::command::GAME_SPEEDS;
+
+use serde::{Serialize, Deserialize};
+
+use ron;
+use ron::ser::PrettyConfig;
+
+use cgmath::Vector2;
+
+use std::fs;
+use std::path::Path;
+use std::collections::BTreeMap;
+
+pub const SETTINGS_FILE_VERSION: u32 = 1;
+pub const SETTINGS_FILE: &str = "./settings.ron";
+
+// Escape always opens the menu and F1/F2 are dev hacks, so they can't be bound
+pub const BINDABLE_KEYS: [&str; 47] = ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M",
+                                       "N", "O", "P", "Q", "R", "S", "T", "U", "V", "W", "X", "Y", "Z",
+                                       "0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "Space",
+                                       "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12"];
+
+// Turns a key as typed into the name it is bound by, if it can be bound at all
+pub fn binding_name(key: &str) -> Option<String> {
+  let name = if key == " " { "Space".to_string() } else { key.to_uppercase() };
+  if BINDABLE_KEYS.contains(&name.as_str()) {
+    Some(name)
+  } else {
+    None
+  }
+}
+
+// Everything the player can bind a key to in game or in the map editor
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum KeyAction {
+  CameraForward,
+  CameraLeft,
+  CameraBackward,
+  CameraRight,
+  CameraUp,
+  CameraDown,
+  ResetCamera,
+  PlaceDishwasher,
+  PlaceFridge,
+  PlaceMeatTenderizer,
+  PlaceCoffeeMachine,
+  PlaceSaltGrinder,
+  PlaceCutleryDrawer,
+  TogglePause,
+  CycleSpeed,
+  Restart,
+  EmptyBin,
+  CycleTargeting,
+  UpgradeFirstPath,
+  UpgradeSecondPath,
+  Sell,
+  Move,
+  Clean,
+  ToggleMinimalUi,
+  Undo,
+  OpenBrush,
+  PathBrush,
+  BlockedBrush,
+  StartBrush,
+  EndBrush,
+}
+
+impl KeyAction {
+  pub fn all() -> Vec<KeyAction> {
+    vec!(KeyAction::CameraForward, KeyAction::CameraLeft, KeyAction::CameraBackward, KeyAction::CameraRight,
+         KeyAction::CameraUp, KeyAction::CameraDown, KeyAction::ResetCamera,
+         KeyAction::PlaceDishwasher, KeyAction::PlaceFridge, KeyAction::PlaceMeatTenderizer,
+         KeyAction::PlaceCoffeeMachine, KeyAction::PlaceSaltGrinder, KeyAction::PlaceCutleryDrawer,
+         KeyAction::TogglePause, KeyAction::CycleSpeed, KeyAction::Restart, KeyAction::EmptyBin,
+         KeyAction::CycleTargeting, KeyAction::UpgradeFirstPath, KeyAction::UpgradeSecondPath,
+         KeyAction::Sell, KeyAction::Move, KeyAction::Clean, KeyAction::ToggleMinimalUi, KeyAction::Undo,
+         KeyAction::OpenBrush, KeyAction::PathBrush, KeyAction::BlockedBrush, KeyAction::StartBrush,
+         KeyAction::EndBrush)
+  }
+  
+  // The camera moves the same way in the game and the editor, everything
+  // else only does something on one of them
+  pub fn in_game(&self) -> bool {
+    !self.is_brush()
+  }
+  
+  pub fn in_editor(&self) -> bool {
+    self.is_brush() || self.is_camera()
+  }
+  
+  fn is_brush(&self) -> bool {
+    [KeyAction::OpenBrush, KeyAction::PathBrush, KeyAction::BlockedBrush,
+     KeyAction::StartBrush, KeyAction::EndBrush].contains(self)
+  }
+  
+  fn is_camera(&self) -> bool {
+    [KeyAction::CameraForward, KeyAction::CameraLeft, KeyAction::CameraBackward,
+     KeyAction::CameraRight, KeyAction::CameraUp, KeyAction::CameraDown].contains(self)
+  }
+  
+  // Whether both can be pressed on the same screen, so can't share a key
+  fn shares_screen(&self, other: &KeyAction) -> bool {
+    (self.in_game() && other.in_game()) || (self.in_editor() && other.in_editor())
+  }
+  
+  pub fn name(&self) -> &'static str {
+    match self {
+      KeyAction::CameraForward => "Camera forward",
+      KeyAction::CameraLeft => "Camera left",
+      KeyAction::CameraBackward => "Camera back",
+      KeyAction::CameraRight => "Camera right",
+      KeyAction::CameraUp => "Camera up",
+      KeyAction::CameraDown => "Camera down",
+      KeyAction::ResetCamera => "Reset camera",
+      KeyAction::PlaceDishwasher => "Dishwasher",
+      KeyAction::PlaceFridge => "Fridge",
+      KeyAction::PlaceMeatTenderizer => "Meat tenderizer",
+      KeyAction::PlaceCoffeeMachine => "Coffee machine",
+      KeyAction::PlaceSaltGrinder => "Salt grinder",
+      KeyAction::PlaceCutleryDrawer => "Cutlery drawer",
+      KeyAction::TogglePause => "Pause",
+      KeyAction::CycleSpeed => "Game speed",
+      KeyAction::Restart => "Restart",
+      KeyAction::EmptyBin => "Empty bin",
+      KeyAction::CycleTargeting => "Targeting",
+      KeyAction::UpgradeFirstPath => "Upgrade path 1",
+      KeyAction::UpgradeSecondPath => "Upgrade path 2",
+      KeyAction::Sell => "Sell",
+      KeyAction::Move => "Move",
+      KeyAction::Clean => "Clean",
+      KeyAction::ToggleMinimalUi => "Minimal UI",
+      KeyAction::Undo => "Undo",
+      KeyAction::OpenBrush => "Open brush",
+      KeyAction::PathBrush => "Path brush",
+      KeyAction::BlockedBrush => "Blocked brush",
+      KeyAction::StartBrush => "Start brush",
+      KeyAction::EndBrush => "End brush",
+    }
+  }
+  
+  pub fn default_key(&self) -> &'static str {
+    match self {
+      KeyAction::CameraForward => "W",
+      KeyAction::CameraLeft => "A",
+      KeyAction::CameraBackward => "S",
+      KeyAction::CameraRight => "D",
+      KeyAction::CameraUp => "R",
+      KeyAction::CameraDown => "F",
+      KeyAction::ResetCamera => "V",
+      KeyAction::PlaceDishwasher => "1",
+      KeyAction::PlaceFridge => "2",
+      KeyAction::PlaceMeatTenderizer => "3",
+      KeyAction::PlaceCoffeeMachine => "4",
+      KeyAction::PlaceSaltGrinder => "5",
+      KeyAction::PlaceCutleryDrawer => "6",
+      KeyAction::TogglePause => "P",
+      KeyAction::CycleSpeed => "Space",
+      KeyAction::Restart => "K",
+      KeyAction::EmptyBin => "B",
+      KeyAction::CycleTargeting => "T",
+      KeyAction::UpgradeFirstPath => "U",
+      KeyAction::UpgradeSecondPath => "I",
+      KeyAction::Sell => "X",
+      KeyAction::Move => "M",
+      KeyAction::Clean => "C",
+      KeyAction::ToggleMinimalUi => "F10",
+      KeyAction::Undo => "Y",
+      KeyAction::OpenBrush => "1",
+      KeyAction::PathBrush => "2",
+      KeyAction::BlockedBrush => "3",
+      KeyAction::StartBrush => "4",
+      KeyAction::EndBrush => "5",
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KeyBindings {
+  pub keys: BTreeMap<KeyAction, String>,
+}
+
+impl KeyBindings {
+  pub fn new() -> KeyBindings {
+    let mut keys = BTreeMap::new();
+    for action in KeyAction::all() {
+      keys.insert(action, action.default_key().to_string());
+    }
+    
+    KeyBindings {
+      keys,
+    }
+  }
+  
+  // Actions added since the file was written get their default key, as do
+  // actions bound to something that can't be bound
+  pub fn fill_missing(&mut self) {
+    for action in KeyAction::all() {
+      let key = self.keys.entry(action).or_insert_with(|| action.default_key().to_string());
+      if !BINDABLE_KEYS.contains(&key.as_str()) {
+        println!("{} can't be bound to {}, using {}", action.name(), key, action.default_key());
+        *key = action.default_key().to_string();
+      }
+    }
+  }
+  
+  pub fn set(&mut self, action: KeyAction, key: String) {
+    self.keys.insert(action, key);
+  }
+  
+  // Every key that more than one action on the same screen is bound to, with
+  // those actions. Editor brushes can share keys with game only actions
+  pub fn conflicts(&self) -> Vec<(String, Vec<KeyAction>)> {
+    let mut by_key: BTreeMap<&str, Vec<KeyAction>> = BTreeMap::new();
+    for (action, key) in &self.keys {
+      by_key.entry(key).or_default().push(*action);
+    }
+    
+    by_key.into_iter()
+          .map(|(key, actions)| {
+            let clashing = actions.iter()
+                                  .filter(|a| actions.iter().any(|b| b != *a && a.shares_screen(b)))
+                                  .cloned()
+                                  .collect();
+            (key.to_string(), clashing)
+          })
+          .filter(|(_, actions): &(String, Vec<KeyAction>)| !actions.is_empty())
+          .collect()
+  }
+  
+  pub fn key(&self, action: KeyAction) -> &str {
+    match self.keys.get(&action) {
+      Some(key) => key,
+      None => action.default_key(),
+    }
+  }
+}
+
+impl Default for KeyBindings {
+  fn default() -> KeyBindings {
+    KeyBindings::new()
+  }
+}
+
+// Player preferences, kept between runs. Anything missing from the file,
+// say because it was written by an older version, falls back to the default
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+  // Files from before the version was written count as version 0
+  #[serde(default)]
+  pub version: u32,
+  pub resolution: Vector2<i32>,
+  pub fullscreen: bool,
+  pub vsync: bool,
+  pub force_dpi: bool,
+  pub dpi: f32,
+  pub key_bindings: KeyBindings,
+  pub default_game_speed: i32,
+  // Seconds after buying, selling, moving, cleaning or emptying the bin that
+  // it can still be undone
+  pub undo_window: f32,
+}
+
+impl Default for Settings {
+  fn default() -> Settings {
+    Settings {
+      version: SETTINGS_FILE_VERSION,
+      resolution: Vector2::new(1280, 720),
+      fullscreen: false,
+      vsync: true,
+      force_dpi: false,
+      dpi: 1.0,
+      key_bindings: KeyBindings::new(),
+      default_game_speed: 1,
+      undo_window: UNDO_WINDOW,
+    }
+  }
+}
+
+impl Settings {
+  pub fn exists() -> bool {
+    Path::new(SETTINGS_FILE).exists()
+  }
+  
+  // Never fails, a missing or broken file just means the defaults
+  pub fn load() -> Settings {
+    if !Settings::exists() {
+      return Settings::default();
+    }
+    
+    let text = match fs::read_to_string(SETTINGS_FILE) {
+      Ok(text) => text,
+      Err(e) => {
+        println!("Unable to read {}: {}", SETTINGS_FILE, e);
+        return Settings::default();
+      }
+    };
+    
+    match Settings::parse(&text) {
+      Ok((settings, migrated)) => {
+        if migrated {
+          if let Err(e) = settings.save() {
+            println!("Unable to save migrated settings: {}", e);
+          }
+        }
+        settings
+      },
+      Err(e) => {
+        println!("Unable to load {}: {}", SETTINGS_FILE, e);
+        Settings::default()
+      }
+    }
+  }
+  
+  // Also says whether the settings had to be brought up to the current version
+  pub fn parse(text: &str) -> Result<(Settings, bool), String> {
+    let mut settings: Settings = ron::de::from_str(text).map_err(|e| e.to_string())?;
+    
+    if settings.version > SETTINGS_FILE_VERSION {
+      println!("Settings file version {} is newer than the supported version {}", settings.version, SETTINGS_FILE_VERSION);
+    }
+    
+    let migrated = settings.version < SETTINGS_FILE_VERSION;
+    if migrated {
+      settings.version = SETTINGS_FILE_VERSION;
+    }
+    
+    settings.key_bindings.fill_missing();
+    settings.validate();
+    
+    for (key, actions) in settings.key_bindings.conflicts() {
+      let names: Vec<&str> = actions.iter().map(|a| a.name()).collect();
+      println!("{} is bound to more than one action: {}", key, names.join(", "));
+    }
+    
+    Ok((settings, migrated))
+  }
+  
+  // Hand edited files can hold values the game can't use
+  fn validate(&mut self) {
+    let defaults = Settings::default();
+    
+    if self.resolution.x <= 0 || self.resolution.y <= 0 {
+      self.resolution = defaults.resolution;
+    }
+    if self.dpi <= 0.0 {
+      self.dpi = defaults.dpi;
+    }
+    if self.undo_window < 0.0 {
+      self.undo_window = defaults.undo_window;
+    }
+    if !GAME_SPEEDS.contains(&self.default_game_speed) {
+      self.default_game_speed = defaults.default_game_speed;
+    }
+  }
+  
+  pub fn to_ron(&self) -> Result<String, String> {
+    let config = PrettyConfig {
+      depth_limit: 3,
+      .. PrettyConfig::default()
+    };
+    
+    ron::ser::to_string_pretty(self, config).map_err(|e| e.to_string())
+  }
+  
+  pub fn save(&self) -> Result<(), String> {
+    let text = self.to_ron()?;
+    fs::write(SETTINGS_FILE, text).map_err(|e| e.to_string())
+  }
+}
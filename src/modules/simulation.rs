@@ -0,0 +1,550 @@
+use crate::modules::food::{Food, FoodIds};
+use crate::modules::appliances::traits::Appliance;
+use crate::modules::weapons::Weapon;
+use crate::modules::hexagon::{Hexagon, HexagonType};
+use crate::modules::thefoodstore::FoodStore;
+use crate::modules::food_index::FoodIndex;
+
+use crate::modules::update::update_game;
+use crate::modules::physics::collisions;
+use crate::modules::map::Map;
+use crate::modules::wave_file::WaveFile;
+use crate::modules::command::{GameCommand, CommandOutcome, CommandError, GAME_SPEEDS};
+use crate::modules::undo::{UndoStack, UndoAction, UndoEntry, LostBuff, UNDO_WINDOW};
+
+use serde::{Serialize, Deserialize};
+
+use cgmath::{Vector2, Vector3};
+
+pub const DELTA_STEP: f32 = 0.01;
+
+pub const BIN_CLEAN_COST: i32 = 700;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameOver {
+  Victory,
+  Defeat,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct GameStats {
+  pub money_earned: i32,
+  pub foods_cooked: u32,
+  pub foods_binned: u32,
+  pub appliances_bought: u32,
+}
+
+// All of the game rules with none of the rendering, GameScreen drives one of
+// these and headless tools can step it directly.
+#[derive(Clone)]
+pub struct Simulation {
+  pub map: Map,
+  pub waves: WaveFile,
+  pub appliances: Vec<Box<Appliance>>,
+  pub foods: Vec<Box<Food>>,
+  pub weapons: Vec<Box<Weapon>>,
+  pub the_food_store: FoodStore,
+  pub money: i32,
+  pub bin: i32,
+  pub stats: GameStats,
+  pub ticks: u64,
+  pub food_ids: FoodIds,
+  // How long after a command it can still be undone, in seconds
+  pub undo_window: f32,
+  undo: UndoStack,
+  seed: u64,
+  endless: bool,
+  total_delta: f32,
+}
+
+impl Simulation {
+  // Every random choice made during the game comes from seed
+  pub fn new(map: Map, waves: WaveFile, seed: u64) -> Simulation {
+    let store = FoodStore::new(&map, &waves, seed);
+    let money = map.get_info().starting_money;
+    
+    Simulation {
+      map,
+      waves,
+      appliances: Vec::new(),
+      foods: Vec::new(),
+      weapons: Vec::new(),
+      the_food_store: store,
+      money,
+      bin: 0,
+      stats: GameStats::default(),
+      ticks: 0,
+      food_ids: FoodIds::new(),
+      undo_window: UNDO_WINDOW,
+      undo: UndoStack::new(),
+      seed,
+      endless: false,
+      total_delta: 0.0,
+    }
+  }
+  
+  pub fn reset(&mut self) {
+    self.map.reset();
+    self.foods.clear();
+    self.appliances.clear();
+    self.weapons.clear();
+    self.money = self.map.get_info().starting_money;
+    self.bin = 0;
+    self.stats = GameStats::default();
+    self.ticks = 0;
+    self.food_ids = FoodIds::new();
+    self.undo.clear();
+    self.total_delta = 0.0;
+    self.the_food_store = FoodStore::new(&self.map, &self.waves, self.seed);
+    if self.endless {
+      self.the_food_store.set_endless();
+    }
+  }
+  
+  pub fn set_endless(&mut self) {
+    self.endless = true;
+    self.the_food_store.set_endless();
+  }
+  
+  pub fn is_endless(&self) -> bool {
+    self.endless
+  }
+  
+  pub fn seed(&self) -> u64 {
+    self.seed
+  }
+  
+  // Takes effect from the next reset
+  pub fn set_seed(&mut self, seed: u64) {
+    self.seed = seed;
+  }
+  
+  pub fn bin_capacity(&self) -> i32 {
+    self.map.get_info().bin_capacity
+  }
+  
+  pub fn is_bin_full(&self) -> bool {
+    self.bin >= self.bin_capacity()
+  }
+  
+  pub fn is_finished(&self) -> bool {
+    self.game_over().is_some()
+  }
+  
+  pub fn game_over(&self) -> Option<GameOver> {
+    if self.is_bin_full() {
+      Some(GameOver::Defeat)
+    } else if self.the_food_store.is_finished() && self.foods.len() == 0 {
+      Some(GameOver::Victory)
+    } else {
+      None
+    }
+  }
+  
+  // The current wave only counts once all of its food has been dealt with
+  pub fn waves_cleared(&self) -> usize {
+    self.the_food_store.wave_number()
+  }
+  
+  pub fn update_map(&mut self, delta_time: f32) {
+    self.map.update(delta_time);
+  }
+  
+  pub fn skip_map_animation(&mut self) {
+    self.map.skip_animation();
+  }
+  
+  // The one way the player changes a game, every rule about what they are
+  // allowed to do is checked here
+  pub fn apply_command(&mut self, command: &GameCommand, model_sizes: &mut Vec<(String, Vector3<f32>)>) -> Result<CommandOutcome, CommandError> {
+    match command {
+      GameCommand::Place { kind, q, r } => {
+        let appliance = kind.create(Vector2::new(*q, *r), &self.map);
+        let cost = appliance.buy_cost();
+        let outcome = self.buy_appliance(appliance)?;
+        
+        self.record_undo(UndoAction::Place { q: *q, r: *r, cost });
+        Ok(outcome)
+      },
+      GameCommand::Move { appliance, q, r } => {
+        self.check_appliance(*appliance)?;
+        self.check_open_hex(*q, *r)?;
+        
+        let qr = self.appliances[*appliance].get_qr_location();
+        let distance = Hexagon::hex_distance(&Hexagon::new(*q, *r, "".to_string()), &Hexagon::new(qr.x, qr.y, "".to_string()));
+        let max = self.appliances[*appliance].current_life_expectancy()-1;
+        if distance > max {
+          return Err(CommandError::OutOfMoveRange { distance, max });
+        }
+        
+        self.move_appliance(*appliance, *q, *r);
+        self.record_undo(UndoAction::Move { index: *appliance, from: qr, to: Vector2::new(*q, *r), tiles: distance });
+        Ok(CommandOutcome::Moved { appliance: self.appliances.len()-1, tiles: distance })
+      },
+      GameCommand::Sell { appliance } => {
+        self.check_appliance(*appliance)?;
+        
+        let refund = self.appliances[*appliance].sell_price();
+        let sold = self.appliances[*appliance].clone();
+        let lost_buffs = self.sell_appliance(*appliance, model_sizes);
+        self.record_undo(UndoAction::Sell { index: *appliance, appliance: sold, refund, lost_buffs });
+        Ok(CommandOutcome::Sold { refund })
+      },
+      GameCommand::Clean { appliance } => {
+        self.check_appliance(*appliance)?;
+        
+        let cost = self.appliances[*appliance].clean_cost();
+        if self.appliances[*appliance].current_life_expectancy() >= self.appliances[*appliance].max_life_expectancy() {
+          return Err(CommandError::NothingToClean);
+        }
+        self.check_money(cost)?;
+        
+        let qr = self.appliances[*appliance].get_qr_location();
+        let life_expectancy = self.appliances[*appliance].current_life_expectancy();
+        self.money -= cost;
+        self.appliances[*appliance].clean();
+        self.record_undo(UndoAction::Clean { q: qr.x, r: qr.y, life_expectancy, cost });
+        Ok(CommandOutcome::Cleaned { cost })
+      },
+      GameCommand::Upgrade { appliance, path } => {
+        self.check_appliance(*appliance)?;
+        
+        if *path >= self.appliances[*appliance].upgrade_paths().len() {
+          return Err(CommandError::NoSuchUpgrade { path: *path });
+        }
+        let cost = {
+          match self.appliances[*appliance].upgrade_cost(*path) {
+            Some(cost) => cost,
+            None => return Err(CommandError::FullyUpgraded { path: *path }),
+          }
+        };
+        self.check_money(cost)?;
+        
+        self.money -= cost;
+        self.appliances[*appliance].upgrade(*path);
+        // Upgrades can't be undone, and undoing anything from before one could
+        // throw the upgrade away with it
+        self.undo.clear();
+        Ok(CommandOutcome::Upgraded { appliance: *appliance, path: *path, cost })
+      },
+      GameCommand::Targeting { appliance, target } => {
+        self.check_appliance(*appliance)?;
+        
+        self.appliances[*appliance].set_targeting(target.clone());
+        Ok(CommandOutcome::TargetingChanged)
+      },
+      GameCommand::Speed(speed) => {
+        if !GAME_SPEEDS.contains(speed) {
+          return Err(CommandError::InvalidSpeed(*speed));
+        }
+        
+        Ok(CommandOutcome::SpeedChanged(*speed))
+      },
+      GameCommand::EmptyBin => {
+        if self.bin == 0 {
+          return Err(CommandError::BinEmpty);
+        }
+        self.check_money(BIN_CLEAN_COST)?;
+        
+        let emptied = self.bin;
+        self.money -= BIN_CLEAN_COST;
+        self.bin = 0;
+        self.record_undo(UndoAction::EmptyBin { emptied, cost: BIN_CLEAN_COST });
+        Ok(CommandOutcome::BinEmptied { cost: BIN_CLEAN_COST })
+      },
+      GameCommand::Restart => {
+        self.reset();
+        Ok(CommandOutcome::Restarted)
+      },
+      GameCommand::Undo => {
+        self.undo_last(model_sizes)
+      },
+    }
+  }
+  
+  fn record_undo(&mut self, action: UndoAction) {
+    let wave = self.the_food_store.wave_number();
+    self.undo.expire(wave, self.ticks, self.undo_window);
+    self.undo.push(action, wave, self.ticks);
+  }
+  
+  // The command the next undo would take back, if there is one still in time
+  pub fn next_undo(&self) -> Option<&UndoEntry> {
+    match self.undo.last() {
+      Some(entry) => {
+        let in_time = (self.ticks - entry.tick) as f32 * DELTA_STEP <= self.undo_window;
+        if entry.wave == self.the_food_store.wave_number() && in_time {
+          Some(entry)
+        } else {
+          None
+        }
+      },
+      None => None,
+    }
+  }
+  
+  // Everything comes back at full price. If the game has moved on so the
+  // command can't be undone, the stack is left alone, unless the hex it needs
+  // has been built on again, in which case the command is dropped
+  fn undo_last(&mut self, model_sizes: &mut Vec<(String, Vector3<f32>)>) -> Result<CommandOutcome, CommandError> {
+    self.undo.expire(self.the_food_store.wave_number(), self.ticks, self.undo_window);
+    
+    let action = {
+      match self.undo.last() {
+        Some(entry) => entry.action.clone(),
+        None => return Err(CommandError::NothingToUndo),
+      }
+    };
+    
+    let refund = {
+      match action {
+        UndoAction::Place { q, r, cost } => {
+          let idx = self.undo_appliance_at(q, r)?;
+          
+          let _ = self.remove_appliance(idx, model_sizes);
+          self.money += cost;
+          self.stats.appliances_bought -= 1;
+          cost
+        },
+        UndoAction::Move { index, from, to, tiles } => {
+          let idx = self.undo_appliance_at(to.x, to.y)?;
+          self.undo_open_hex(from.x, from.y)?;
+          
+          let mut appliance = self.appliances.remove(idx);
+          self.map.set_hexagon_type(to.x, to.y, HexagonType::Open);
+          appliance.set_qr_location(from.x, from.y, &self.map);
+          appliance.moved_tiles(-tiles);
+          self.put_back_appliance(index, appliance);
+          0
+        },
+        UndoAction::Sell { index, appliance, refund, lost_buffs } => {
+          let qr = appliance.get_qr_location();
+          self.undo_open_hex(qr.x, qr.y)?;
+          self.check_money(refund)?;
+          
+          self.money -= refund;
+          self.put_back_appliance(index, appliance);
+          for lost in lost_buffs {
+            if let Some(idx) = self.appliance_at(lost.q, lost.r) {
+              self.appliances[idx].restore_buff(lost.buff, lost.life);
+            }
+          }
+          -refund
+        },
+        UndoAction::Clean { q, r, life_expectancy, cost } => {
+          let idx = self.undo_appliance_at(q, r)?;
+          
+          self.appliances[idx].mut_data().life_expectancy = life_expectancy;
+          self.money += cost;
+          cost
+        },
+        UndoAction::EmptyBin { emptied, cost } => {
+          if self.bin + emptied >= self.bin_capacity() {
+            return Err(CommandError::UndoWouldFillBin);
+          }
+          
+          self.bin += emptied;
+          self.money += cost;
+          cost
+        },
+      }
+    };
+    
+    self.undo.pop();
+    Ok(CommandOutcome::Undone { refund })
+  }
+  
+  // An appliance that wore out since can never be undone, so it's dropped
+  fn undo_appliance_at(&mut self, q: i32, r: i32) -> Result<usize, CommandError> {
+    match self.appliance_at(q, r) {
+      Some(idx) => Ok(idx),
+      None => {
+        self.undo.pop();
+        Err(CommandError::ApplianceGone { q, r })
+      }
+    }
+  }
+  
+  // Once something else is built where an appliance has to go back it can't
+  // be undone, so it's dropped and whatever is under it can be undone instead
+  fn undo_open_hex(&mut self, q: i32, r: i32) -> Result<(), CommandError> {
+    match self.check_open_hex(q, r) {
+      Err(CommandError::HexNotOpen { .. }) => {
+        self.undo.pop();
+        Err(CommandError::HexBuiltOn { q, r })
+      },
+      result => result,
+    }
+  }
+  
+  pub fn appliance_at(&self, q: i32, r: i32) -> Option<usize> {
+    self.appliances.iter().position(|appliance| {
+      let qr = appliance.get_qr_location();
+      qr.x == q && qr.y == r
+    })
+  }
+  
+  fn put_back_appliance(&mut self, index: usize, appliance: Box<Appliance>) {
+    let qr = appliance.get_qr_location();
+    self.map.set_hexagon_type(qr.x, qr.y, HexagonType::Closed);
+    
+    let index = index.min(self.appliances.len());
+    self.appliances.insert(index, appliance);
+  }
+  
+  fn check_appliance(&self, idx: usize) -> Result<(), CommandError> {
+    if idx >= self.appliances.len() {
+      return Err(CommandError::NoSuchAppliance(idx));
+    }
+    
+    Ok(())
+  }
+  
+  fn check_open_hex(&self, q: i32, r: i32) -> Result<(), CommandError> {
+    match self.map.get_hex_from_qr(q, r) {
+      Some(hex) => {
+        if hex.is_open() {
+          Ok(())
+        } else {
+          Err(CommandError::HexNotOpen { q, r })
+        }
+      },
+      None => {
+        Err(CommandError::InvalidHex { q, r })
+      },
+    }
+  }
+  
+  fn check_money(&self, cost: i32) -> Result<(), CommandError> {
+    if cost > self.money {
+      return Err(CommandError::NotEnoughMoney { cost, money: self.money });
+    }
+    
+    Ok(())
+  }
+  
+  fn buy_appliance(&mut self, appliance: Box<Appliance>) -> Result<CommandOutcome, CommandError> {
+    let qr = appliance.get_qr_location();
+    let cost = appliance.buy_cost();
+    self.check_open_hex(qr.x, qr.y)?;
+    self.check_money(cost)?;
+    
+    self.money -= cost;
+    self.stats.appliances_bought += 1;
+    self.map.set_hexagon_type(qr.x, qr.y, HexagonType::Closed);
+    self.appliances.push(appliance);
+    
+    Ok(CommandOutcome::Placed { appliance: self.appliances.len()-1, cost })
+  }
+  
+  fn sell_appliance(&mut self, idx: usize, model_sizes: &mut Vec<(String, Vector3<f32>)>) -> Vec<LostBuff> {
+    self.money += self.appliances[idx].sell_price();
+    self.remove_appliance(idx, model_sizes)
+  }
+  
+  // Takes the appliance off the map along with any buffs it was handing out,
+  // which are returned so an undo can give them back
+  fn remove_appliance(&mut self, idx: usize, model_sizes: &mut Vec<(String, Vector3<f32>)>) -> Vec<LostBuff> {
+    let hex_location = self.appliances[idx].get_qr_location();
+    let range = self.appliances[idx].get_range();
+    self.map.set_hexagon_type(hex_location.x, hex_location.y, HexagonType::Open);
+    
+    // Updating without any time passing just collects the buffs it hands out
+    let appliance_hex = Hexagon::new(hex_location.x, hex_location.y, "".to_string());
+    let food_index = FoodIndex::new(&self.foods, &self.map);
+    let buffs = self.appliances[idx].update(&mut self.foods, &food_index, &mut self.weapons, model_sizes, &self.map, 0.0);
+    
+    let mut lost_buffs = Vec::new();
+    let hexs = Hexagon::generate_hexagon_range(range as i32, "".to_string());
+    for hex in &hexs {
+      let t_hex = Hexagon::hex_add(&appliance_hex, &hex);
+      for appliance in &mut self.appliances {
+        let qr = appliance.get_qr_location();
+        if qr.x == t_hex.q() && qr.y == t_hex.r() {
+          for (buff, _, _) in &buffs {
+            if !appliance.data().buffs.contains(buff) {
+              continue;
+            }
+            
+            let life = appliance.current_life_expectancy();
+            appliance.remove_buff(buff);
+            lost_buffs.push(LostBuff { q: qr.x, r: qr.y, buff: *buff, life: life - appliance.current_life_expectancy() });
+          }
+        }
+      }
+    }
+    
+    self.appliances.remove(idx);
+    lost_buffs
+  }
+  
+  // The moved appliance ends up last in the list
+  fn move_appliance(&mut self, idx: usize, q: i32, r: i32) {
+    let mut appliance = self.appliances.remove(idx);
+    let qr = appliance.get_qr_location();
+    self.map.set_hexagon_type(qr.x, qr.y, HexagonType::Open);
+    
+    let dist = Hexagon::hex_distance(&Hexagon::new(q,r, "".to_string()), &Hexagon::new(qr.x, qr.y, "".to_string()));
+    appliance.set_qr_location(q, r, &self.map);
+    appliance.moved_tiles(dist);
+    appliance.should_draw_range(false);
+    
+    self.map.set_hexagon_type(q, r, HexagonType::Closed);
+    self.appliances.push(appliance);
+  }
+  
+  // Runs as many fixed DELTA_STEP ticks as delta_time covers, carrying the
+  // remainder over to the next call.
+  pub fn update(&mut self, selected_appliance: &mut Option<usize>, model_sizes: &mut Vec<(String, Vector3<f32>)>, delta_time: f32) {
+    for _ in 0..self.steps_due(delta_time) {
+      self.step(selected_appliance, model_sizes);
+    }
+  }
+  
+  // How many ticks delta_time is worth, for callers that need to do
+  // something between ticks
+  pub fn steps_due(&mut self, delta_time: f32) -> usize {
+    self.total_delta += delta_time;
+    
+    let delta_steps = (self.total_delta / DELTA_STEP).floor() as usize;
+    for _ in 0..delta_steps {
+      self.total_delta -= DELTA_STEP;
+    }
+    
+    delta_steps
+  }
+  
+  pub fn step(&mut self, selected_appliance: &mut Option<usize>, model_sizes: &mut Vec<(String, Vector3<f32>)>) {
+    // Nothing can happen until the map has finished building, and not
+    // counting those ticks keeps them out of replays
+    if !self.map.is_ready() {
+      return;
+    }
+    
+    self.ticks += 1;
+    
+    if let Some(food) = self.the_food_store.update(DELTA_STEP, &mut self.food_ids) {
+      self.foods.push(food);
+    }
+    
+    let appliances = &mut self.appliances;
+    let foods = &mut self.foods;
+    let weapons = &mut self.weapons;
+    let map = &mut self.map;
+    let bin = &mut self.bin;
+    let money = &mut self.money;
+    let stats = &mut self.stats;
+    let food_ids = &mut self.food_ids;
+    
+    update_game(map, appliances, foods, weapons, selected_appliance, model_sizes, DELTA_STEP);
+    collisions(map, foods, weapons, model_sizes, bin, money, stats, food_ids, DELTA_STEP);
+    
+    if self.foods.len() == 0 {
+      if self.the_food_store.next_wave() {
+        for appliance in &mut self.appliances {
+          appliance.decrease_life_expectancy();
+        }
+      }
+    }
+  }
+}
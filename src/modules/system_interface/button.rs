@@ -472,7 +472,7 @@ impl Button {
     self.button_type._get_colours()
   }
   
-  pub fn _set_colour(&mut self, pressed: Vector4<f32>, unpressed: Vector4<f32>) {
+  pub fn set_colour(&mut self, pressed: Vector4<f32>, unpressed: Vector4<f32>) {
     asserts::check_colour_range_vec4(&pressed);
     asserts::check_colour_range_vec4(&unpressed);
     
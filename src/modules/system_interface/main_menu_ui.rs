@@ -15,12 +15,30 @@ const START_GAME_NAME: &str = "StartGame";
 const EASY_GAME_NAME: &str = "EasyGame";
 const MEDIUM_GAME_NAME: &str = "MediumGame";
 const HARD_GAME_NAME: &str = "HardGame";
+const RANDOM_GAME_NAME: &str = "RandomGame";
+const EDITOR_NAME: &str = "Editor";
+const ENDLESS_BUTTON_NAME: &str = "Endless";
+const ENDLESS_MODE_NAME: &str = "EndlessMode";
+const RANDOM_RADIUS_NAME: &str = "RandomRadius";
+const LOWER_RADIUS_BUTTON: &str = "LowerRadius";
+const HIGHER_RADIUS_BUTTON: &str = "HigherRadius";
+const CONTINUE_BUTTON_NAME: &str = "Continue";
+const REPLAY_BUTTON_NAME: &str = "Replay";
 const OPTION_BUTTON_NAME: &str = "Options";
+const SEED_LABEL_NAME: &str = "SeedLabel";
+const SEED_NAME: &str = "Seed";
+const RANDOM_SEED_TEXT: &str = "random";
 const EXIT_BUTTON_NAME: &str = "ExitGameButton";
 
+const MIN_RANDOM_RADIUS: i32 = 2;
+const MAX_RANDOM_RADIUS: i32 = 9;
+const DEFAULT_RANDOM_RADIUS: i32 = 5;
+
 pub struct UserInterface {
   options_menu: OptionsUi,
   widgets: Vec<Widget>,
+  random_radius: i32,
+  endless: bool,
 }
 
 impl UserInterface {
@@ -29,15 +47,17 @@ impl UserInterface {
     
     let menu_width = 512.0;
     
-    let button_location = 192.0;
+    let button_location = 256.0;
     
     let button_width = 128.0;
     let button_height =32.0;
     let button_offset = button_height + button_height*0.5;
+    let radius_button_size = Vector2::new(20.0, 32.0);
     
     let text_colour = Vector4::new(0.0, 0.0, 0.0, 1.0);
     let button_colour = Vector4::new(0.5019, 0.749, 1.0, 1.0);
     let background_colour = Vector4::new(0.0, 0.1411, 0.4, 1.0);
+    let no_colour = Vector4::new(0.0, 0.0, 0.0, 0.0);
     
     widgets.push(
       Widget::new(BACKGROUND_NAME.to_string(), Vector2::new(window_size.x*0.5, window_size.y*0.5), 
@@ -47,7 +67,7 @@ impl UserInterface {
     
     widgets.push(
       Widget::new(MENU_OPTIONS_NAME.to_string(), Vector2::new(window_size.x*0.5, window_size.y*0.5), 
-                  Vector2::new(menu_width, 256.0),
+                  Vector2::new(menu_width, 320.0),
                   background_colour)
                 .with_button(Vector2::new(menu_width*0.5-button_width*1.2, button_location),
                              Vector2::new(button_width, button_height),
@@ -76,7 +96,62 @@ impl UserInterface {
                              Vector4::new(1.0, 0.0, 0.0, 1.0),
                              button_colour,
                              true, "Hard".to_string(), "Arial".to_string())
-                .with_button(Vector2::new(menu_width*0.5, button_location-button_offset*1.0),
+                .with_button(Vector2::new(menu_width*0.5-button_width*1.2, button_location-button_offset*1.0),
+                             Vector2::new(button_width, button_height),
+                             Vector2::new(button_width*0.5, button_height*0.33), 
+                             Vector2::new(128.0, 128.0),
+                             RANDOM_GAME_NAME.to_string(), 
+                             text_colour, 
+                             Vector4::new(1.0, 0.0, 0.0, 1.0),
+                             button_colour,
+                             true, "Random".to_string(), "Arial".to_string())
+                .with_button(Vector2::new(menu_width*0.5-button_width*0.3, button_location-button_offset*1.0),
+                             radius_button_size,
+                             Vector2::new(radius_button_size.x*0.5, radius_button_size.y*0.1), 
+                             Vector2::new(128.0, 128.0),
+                             LOWER_RADIUS_BUTTON.to_string(), 
+                             Vector4::new(1.0, 1.0, 1.0, 1.0), 
+                             no_colour,
+                             no_colour,
+                             true, "<".to_string(), "Arial".to_string())
+                .with_text_field_centered(RANDOM_RADIUS_NAME.to_string(), 
+                                          Vector2::new(menu_width*0.5, button_location-button_offset*1.0-button_height*0.33), 
+                                          Vector2::new(128.0, 128.0), 
+                                          Vector4::new(1.0, 1.0, 1.0, 1.0), 
+                                          DEFAULT_RANDOM_RADIUS.to_string(), "Arial".to_string())
+                .with_button(Vector2::new(menu_width*0.5+button_width*0.3, button_location-button_offset*1.0),
+                             radius_button_size,
+                             Vector2::new(radius_button_size.x*0.5, radius_button_size.y*0.1), 
+                             Vector2::new(128.0, 128.0),
+                             HIGHER_RADIUS_BUTTON.to_string(), 
+                             Vector4::new(1.0, 1.0, 1.0, 1.0), 
+                             no_colour,
+                             no_colour,
+                             true, ">".to_string(), "Arial".to_string())
+                .with_button(Vector2::new(menu_width*0.5+button_width*1.2, button_location-button_offset*1.0),
+                             Vector2::new(button_width, button_height),
+                             Vector2::new(button_width*0.5, button_height*0.33), 
+                             Vector2::new(128.0, 128.0),
+                             EDITOR_NAME.to_string(), 
+                             text_colour, 
+                             Vector4::new(1.0, 0.0, 0.0, 1.0),
+                             button_colour,
+                             true, "Editor".to_string(), "Arial".to_string())
+                .with_button(Vector2::new(menu_width*0.5-button_width*1.2, button_location-button_offset*2.0),
+                             Vector2::new(button_width, button_height),
+                             Vector2::new(button_width*0.5, button_height*0.33), 
+                             Vector2::new(128.0, 128.0),
+                             ENDLESS_BUTTON_NAME.to_string(), 
+                             text_colour, 
+                             Vector4::new(1.0, 0.0, 0.0, 1.0),
+                             button_colour,
+                             true, "Endless".to_string(), "Arial".to_string())
+                .with_text_field_centered(ENDLESS_MODE_NAME.to_string(), 
+                                          Vector2::new(menu_width*0.5+button_width*1.2, button_location-button_offset*2.0-button_height*0.33), 
+                                          Vector2::new(128.0, 128.0), 
+                                          Vector4::new(1.0, 1.0, 1.0, 1.0), 
+                                          "Endless Off".to_string(), "Arial".to_string())
+                .with_button(Vector2::new(menu_width*0.5, button_location-button_offset*2.0),
                              Vector2::new(button_width, button_height),
                              Vector2::new(button_width*0.5, button_height*0.33), 
                              Vector2::new(128.0, 128.0),
@@ -85,7 +160,26 @@ impl UserInterface {
                              Vector4::new(0.0, 0.0, 0.0, 1.0),
                              button_colour,
                              true, "Options".to_string(), "Arial".to_string())
-                .with_button(Vector2::new(menu_width*0.5, button_location-button_offset*2.0),
+                .with_button(Vector2::new(menu_width*0.5-button_width*1.2, button_location-button_offset*3.0),
+                             Vector2::new(button_width, button_height),
+                             Vector2::new(button_width*0.5, button_height*0.33), 
+                             Vector2::new(128.0, 128.0),
+                             CONTINUE_BUTTON_NAME.to_string(), 
+                             text_colour, 
+                             Vector4::new(1.0, 0.0, 0.0, 1.0),
+                             button_colour,
+                             true, "Continue".to_string(), "Arial".to_string())
+                .with_text_field(SEED_LABEL_NAME.to_string(), 
+                                 Vector2::new(menu_width*0.5+button_width*0.6, button_location-button_offset*3.0-button_height*0.33), 
+                                 Vector2::new(128.0, 128.0), 
+                                 Vector4::new(1.0, 1.0, 1.0, 1.0), 
+                                 "Seed:".to_string(), "Arial".to_string())
+                .with_text_field_editable(SEED_NAME.to_string(), 
+                                          Vector2::new(menu_width*0.5+button_width*1.0, button_location-button_offset*3.0-button_height*0.33), 
+                                          Vector2::new(128.0, 128.0), 
+                                          Vector4::new(1.0, 1.0, 0.0, 1.0), 
+                                          RANDOM_SEED_TEXT.to_string(), "Arial".to_string())
+                .with_button(Vector2::new(menu_width*0.5, button_location-button_offset*3.0),
                              Vector2::new(button_width, button_height),
                              Vector2::new(button_width*0.5, button_height*0.33), 
                              Vector2::new(128.0, 128.0),
@@ -94,6 +188,15 @@ impl UserInterface {
                              Vector4::new(0.0, 0.0, 0.0, 1.0),
                              button_colour,
                              true, "Exit".to_string(), "Arial".to_string())
+                .with_button(Vector2::new(menu_width*0.5-button_width*1.2, button_location-button_offset*4.0),
+                             Vector2::new(button_width, button_height),
+                             Vector2::new(button_width*0.5, button_height*0.33), 
+                             Vector2::new(128.0, 128.0),
+                             REPLAY_BUTTON_NAME.to_string(), 
+                             text_colour, 
+                             Vector4::new(1.0, 0.0, 0.0, 1.0),
+                             button_colour,
+                             true, "Replay".to_string(), "Arial".to_string())
     );
     
     let options = OptionsUi::new(window_size);
@@ -101,6 +204,8 @@ impl UserInterface {
     UserInterface {
       options_menu: options,
       widgets: widgets,
+      random_radius: DEFAULT_RANDOM_RADIUS,
+      endless: false,
     }
   }
   
@@ -132,10 +237,39 @@ impl UserInterface {
     self.widgets[MENU_OPTIONS_INDEX].get_button_state(&HARD_GAME_NAME.to_string())
   }
   
+  pub fn random_button_pressed(&self) -> bool {
+    self.widgets[MENU_OPTIONS_INDEX].get_button_state(&RANDOM_GAME_NAME.to_string())
+  }
+  
+  pub fn editor_button_pressed(&self) -> bool {
+    self.widgets[MENU_OPTIONS_INDEX].get_button_state(&EDITOR_NAME.to_string())
+  }
+  
+  pub fn random_radius(&self) -> i32 {
+    self.random_radius
+  }
+  
+  // None when the player hasn't typed in a seed
+  pub fn seed(&self) -> Option<u64> {
+    self.widgets[MENU_OPTIONS_INDEX].get_text(&SEED_NAME.to_string()).trim().parse::<u64>().ok()
+  }
+  
+  pub fn endless(&self) -> bool {
+    self.endless
+  }
+  
   pub fn start_button_pressed(&self) -> bool {
     self.widgets[MENU_OPTIONS_INDEX].get_button_state(&START_GAME_NAME.to_string())
   }
   
+  pub fn continue_button_pressed(&self) -> bool {
+    self.widgets[MENU_OPTIONS_INDEX].get_button_state(&CONTINUE_BUTTON_NAME.to_string())
+  }
+  
+  pub fn replay_button_pressed(&self) -> bool {
+    self.widgets[MENU_OPTIONS_INDEX].get_button_state(&REPLAY_BUTTON_NAME.to_string())
+  }
+  
   pub fn options_button_pressed(&self) -> bool {
     self.widgets[MENU_OPTIONS_INDEX].get_button_state(&OPTION_BUTTON_NAME.to_string())
   }
@@ -159,6 +293,26 @@ impl UserInterface {
         }
       }
     
+    if self.widgets[MENU_OPTIONS_INDEX].get_button_state(&LOWER_RADIUS_BUTTON.to_string()) {
+      if self.random_radius > MIN_RANDOM_RADIUS {
+        self.random_radius -= 1;
+      }
+      self.widgets[MENU_OPTIONS_INDEX].update_text_field(&RANDOM_RADIUS_NAME.to_string(), self.random_radius.to_string());
+    }
+    
+    if self.widgets[MENU_OPTIONS_INDEX].get_button_state(&HIGHER_RADIUS_BUTTON.to_string()) {
+      if self.random_radius < MAX_RANDOM_RADIUS {
+        self.random_radius += 1;
+      }
+      self.widgets[MENU_OPTIONS_INDEX].update_text_field(&RANDOM_RADIUS_NAME.to_string(), self.random_radius.to_string());
+    }
+    
+    if self.widgets[MENU_OPTIONS_INDEX].get_button_state(&ENDLESS_BUTTON_NAME.to_string()) {
+      self.endless = !self.endless;
+      let mode = if self.endless { "Endless On" } else { "Endless Off" };
+      self.widgets[MENU_OPTIONS_INDEX].update_text_field(&ENDLESS_MODE_NAME.to_string(), mode.to_string());
+    }
+    
     self.options_menu.update(delta_time, mouse_pos, left_mouse, keys_pressed_this_frame, scroll_delta);
   }
   
@@ -1,5 +1,7 @@
 pub use self::widget::Widget;
 pub use self::options_ui::OptionsUi;
+pub use self::pause_ui::PauseUi;
+pub use self::shop_ui::ShopUi;
 pub use self::main_menu_ui::UserInterface as MainMenuUserInterface;
 pub use self::selection::Selection;
 pub use self::button::Button;
@@ -9,6 +11,8 @@ pub use self::dropdown_box::DropdownBox;
 
 pub mod asserts;
 mod options_ui;
+mod pause_ui;
+mod shop_ui;
 mod main_menu_ui;
 mod widget;
 mod selection;
@@ -1,11 +1,13 @@
 use maat_graphics::DrawCall;
 
 use crate::modules::system_interface::Widget;
+use crate::modules::settings::{self, Settings, KeyBindings, KeyAction};
 
 use cgmath::Vector2;
 use cgmath::Vector4;
 
 const MAIN_WINDOW_INDEX: usize = 0;
+const CONTROLS_WINDOW_INDEX: usize = 1;
 
 const MAIN_WINDOW_NAME: &str = "MainWindow"; 
 const APPLY_BUTTON_NAME: &str = "Apply";
@@ -19,12 +21,27 @@ const CURRENT_DPI_NAME: &str = "CurrentDpi";
 const LOWER_DPI_BUTTON: &str = "LowerDpi";
 const HIGHER_DPI_BUTTON: &str = "HigherDpi";
 const FULLSCREEN_BUTTON: &str = "Fullscreen";
+const CONTROLS_BUTTON: &str = "Controls";
+
+const CONTROLS_WINDOW_NAME: &str = "ControlsWindow";
+const CONTROLS_BACK_BUTTON: &str = "ControlsBack";
+const CONTROLS_DEFAULTS_BUTTON: &str = "ControlsDefaults";
+const CONTROLS_SAVE_BUTTON: &str = "ControlsSave";
+const CONTROLS_STATUS_NAME: &str = "ControlsStatus";
+const BIND_BUTTON_PREFIX: &str = "Bind";
+const BOUND_KEY_PREFIX: &str = "BoundKey";
+const ACTIONS_PER_COLUMN: usize = 15;
 
 pub struct OptionsUi {
   apply_settings: bool,
   hidden: bool,
   available_resolutions: Vec<Vector2<i32>>,
   current_resolution_index: usize,
+  settings: Settings,
+  showing_controls: bool,
+  // Bindings as edited on the controls page, only kept once saved
+  key_bindings: KeyBindings,
+  rebinding: Option<KeyAction>,
   widgets: Vec<Widget>
 }
 
@@ -32,6 +49,8 @@ impl OptionsUi {
   pub fn new(window_size: Vector2<f32>) -> OptionsUi {
     let mut widgets = Vec::new();
     
+    let settings = Settings::load();
+    
     let mut resolutions = vec!(Vector2::new(800, 600), // 4:3
                            Vector2::new(960, 720), // 4:3
                            Vector2::new(1024, 768), // 4:3
@@ -162,7 +181,7 @@ impl OptionsUi {
                                   Vector2::new(280.0, text_location-text_offset*3.0-15.0), 
                                   Vector2::new(256.0, 256.0), 
                                   Vector4::new(0.0, 0.0, 0.0, 1.0), 
-                                  "1".to_string(), "Arial".to_string())
+                                  settings.dpi.to_string(), "Arial".to_string())
                  .with_button(Vector2::new(250.0, text_location-text_offset*3.0),
                              res_up_down_size,
                              Vector2::new(res_up_down_size.x*0.5,
@@ -183,39 +202,171 @@ impl OptionsUi {
                              no_colour,
                              no_colour,
                              true, ">".to_string(), "Arial".to_string())
+                 .with_button(Vector2::new(widget_width*0.5, text_location-text_offset*4.0+10.0),
+                             Vector2::new(button_width, button_height),
+                             Vector2::new(button_width*0.5, button_height*0.33), 
+                             Vector2::new(128.0, 128.0),
+                             CONTROLS_BUTTON.to_string(), 
+                             text_colour, 
+                             Vector4::new(1.0, 0.0, 0.0, 1.0),
+                             button_colour,
+                             true, "Controls".to_string(), "Arial".to_string())
                 );
     
+    let controls_width = 720.0;
+    let controls_height = 680.0;
+    let column_width = controls_width*0.5;
+    let row_top = controls_height-90.0;
+    let row_height = 32.0;
+    let key_button_size = Vector2::new(100.0, 28.0);
+    
+    let mut controls = Widget::new(CONTROLS_WINDOW_NAME.to_string(), window_size * 0.5, Vector2::new(controls_width, controls_height), Vector4::new(0.337254902, 0.662745098, 0.788235294, 1.0))
+                 .start_hidden()
+                 .with_text_field("PlainTextControls".to_string(),
+                                  Vector2::new(25.0, controls_height-50.0), 
+                                  Vector2::new(256.0, 256.0), 
+                                  text_colour, 
+                                  "Controls".to_string(), "Arial".to_string())
+                 .with_text_field(CONTROLS_STATUS_NAME.to_string(),
+                                  Vector2::new(25.0, button_height*2.5), 
+                                  Vector2::new(192.0, 192.0), 
+                                  text_colour, 
+                                  "".to_string(), "Arial".to_string())
+                 .with_button(Vector2::new(25.0+button_width*0.5, button_height*1.5),
+                             Vector2::new(button_width, button_height),
+                             Vector2::new(button_width*0.5, button_height*0.33), 
+                             Vector2::new(128.0, 128.0),
+                             CONTROLS_BACK_BUTTON.to_string(), 
+                             text_colour, 
+                             Vector4::new(1.0, 0.0, 0.0, 1.0),
+                             button_colour,
+                             true, "Back".to_string(), "Arial".to_string())
+                 .with_button(Vector2::new(controls_width*0.5, button_height*1.5),
+                             Vector2::new(button_width, button_height),
+                             Vector2::new(button_width*0.5, button_height*0.33), 
+                             Vector2::new(128.0, 128.0),
+                             CONTROLS_DEFAULTS_BUTTON.to_string(), 
+                             text_colour, 
+                             Vector4::new(1.0, 0.0, 0.0, 1.0),
+                             button_colour,
+                             true, "Defaults".to_string(), "Arial".to_string())
+                 .with_button(Vector2::new(controls_width-25.0-button_width*0.5, button_height*1.5),
+                             Vector2::new(button_width, button_height),
+                             Vector2::new(button_width*0.5, button_height*0.33), 
+                             Vector2::new(128.0, 128.0),
+                             CONTROLS_SAVE_BUTTON.to_string(), 
+                             text_colour, 
+                             Vector4::new(1.0, 0.0, 0.0, 1.0),
+                             button_colour,
+                             true, "Save".to_string(), "Arial".to_string());
+    
+    // One row per action, the key it is bound to doubles as the button to rebind it
+    for (i, action) in KeyAction::all().iter().enumerate() {
+      let x = 25.0 + column_width*(i / ACTIONS_PER_COLUMN) as f32;
+      let y = row_top - row_height*(i % ACTIONS_PER_COLUMN) as f32;
+      
+      controls = controls.with_text_field("PlainText".to_owned() + action.name(),
+                                          Vector2::new(x, y), 
+                                          Vector2::new(192.0, 192.0), 
+                                          text_colour, 
+                                          action.name().to_string(), "Arial".to_string())
+                         .with_button(Vector2::new(x+250.0, y+10.0),
+                                      key_button_size,
+                                      Vector2::new(key_button_size.x*0.5, key_button_size.y*0.1), 
+                                      Vector2::new(192.0, 192.0),
+                                      BIND_BUTTON_PREFIX.to_owned() + action.name(), 
+                                      text_colour, 
+                                      Vector4::new(1.0, 0.0, 0.0, 1.0),
+                                      button_colour,
+                                      true, "".to_string(), "Arial".to_string())
+                         .with_text_field_centered(BOUND_KEY_PREFIX.to_owned() + action.name(), 
+                                                   Vector2::new(x+250.0, y-5.0), 
+                                                   Vector2::new(192.0, 192.0), 
+                                                   text_colour, 
+                                                   settings.key_bindings.key(*action).to_string(), 
+                                                   "Arial".to_string());
+    }
+    widgets.push(controls);
+    
+    widgets[MAIN_WINDOW_INDEX].set_button_state(&FULLSCREEN_BUTTON.to_string(), settings.fullscreen);
+    widgets[MAIN_WINDOW_INDEX].set_button_state(&VSYNC_BUTTON.to_string(), settings.vsync);
+    widgets[MAIN_WINDOW_INDEX].set_button_state(&DPI_BUTTON.to_string(), settings.force_dpi);
+    
     OptionsUi {
       apply_settings: false,
       hidden: true,
       available_resolutions: resolutions,
       current_resolution_index: res_index as usize,
+      key_bindings: settings.key_bindings.clone(),
+      settings,
+      showing_controls: false,
+      rebinding: None,
       widgets: widgets,
     }
   }
   
-  pub fn show(&mut self) {
-    for widget in &mut self.widgets {
-      widget.show();
+  // Puts the window in the state the settings ask for
+  pub fn apply(settings: &Settings, draw_calls: &mut Vec<DrawCall>) {
+    draw_calls.push(DrawCall::change_dpi(settings.dpi));
+    draw_calls.push(DrawCall::enable_dpi(settings.force_dpi));
+    draw_calls.push(DrawCall::enable_vsync(settings.vsync));
+    draw_calls.push(DrawCall::enable_fullscreen(settings.fullscreen));
+    if !settings.fullscreen {
+      draw_calls.push(DrawCall::change_resolution(settings.resolution));
     }
+  }
+  
+  pub fn show(&mut self) {
+    self.widgets[MAIN_WINDOW_INDEX].show();
+    self.widgets[CONTROLS_WINDOW_INDEX].hide();
+    self.showing_controls = false;
     self.hidden = false;
   }
   
+  fn show_controls(&mut self) {
+    self.widgets[MAIN_WINDOW_INDEX].hide();
+    self.widgets[CONTROLS_WINDOW_INDEX].show();
+    self.showing_controls = true;
+    self.key_bindings = self.settings.key_bindings.clone();
+    self.rebinding = None;
+    self.refresh_key_bindings();
+  }
+  
   pub fn hide(&mut self) {
     for widget in &mut self.widgets {
       widget.hide();
     }
     self.hidden = true;
+    self.showing_controls = false;
+    self.rebinding = None;
   }
   
   pub fn is_hidden(&self) -> bool {
     self.hidden
   }
   
+  pub fn settings(&self) -> &Settings {
+    &self.settings
+  }
+  
   pub fn update(&mut self, delta_time: f32, mouse_pos: Vector2<f32>, left_mouse: bool, keys_pressed_this_frame: &Vec<String>, scroll_delta: f32) {
     self.apply_settings = false;
-    for widget in &mut self.widgets {
-      widget.update(delta_time, mouse_pos, left_mouse, keys_pressed_this_frame, scroll_delta);
+    
+    // Only the page on show can be clicked, hidden buttons shouldn't fire
+    let visible_page = if self.hidden { None } else if self.showing_controls { Some(CONTROLS_WINDOW_INDEX) } else { Some(MAIN_WINDOW_INDEX) };
+    for i in 0..self.widgets.len() {
+      let clickable = visible_page == Some(i) && left_mouse;
+      self.widgets[i].update(delta_time, mouse_pos, clickable, keys_pressed_this_frame, scroll_delta);
+    }
+    
+    if self.showing_controls {
+      self.update_controls(keys_pressed_this_frame);
+      return;
+    }
+    
+    if self.widgets[MAIN_WINDOW_INDEX].get_button_state(&CONTROLS_BUTTON.to_string()) {
+      self.show_controls();
+      return;
     }
     
     if self.widgets[MAIN_WINDOW_INDEX].get_button_state(&RETURN_BUTTON_NAME.to_string()) {
@@ -225,6 +376,21 @@ impl OptionsUi {
     
     if self.widgets[MAIN_WINDOW_INDEX].get_button_state(&APPLY_BUTTON_NAME.to_string()) {
       self.apply_settings = true;
+      
+      let window = &self.widgets[MAIN_WINDOW_INDEX];
+      if let Ok(dpi) = window.get_text(&CURRENT_DPI_NAME.to_string()).parse::<f32>() {
+        self.settings.dpi = dpi;
+      }
+      self.settings.force_dpi = window.get_button_state(&DPI_BUTTON.to_string());
+      self.settings.vsync = window.get_button_state(&VSYNC_BUTTON.to_string());
+      self.settings.fullscreen = window.get_button_state(&FULLSCREEN_BUTTON.to_string());
+      if !self.settings.fullscreen {
+        self.settings.resolution = self.available_resolutions[self.current_resolution_index];
+      }
+      
+      if let Err(e) = self.settings.save() {
+        println!("Unable to save settings: {}", e);
+      }
     }
     
     
@@ -257,21 +423,98 @@ impl OptionsUi {
     }
   }
   
+  fn update_controls(&mut self, keys_pressed_this_frame: &Vec<String>) {
+    let controls = CONTROLS_WINDOW_INDEX;
+    
+    if let Some(action) = self.rebinding {
+      for key in keys_pressed_this_frame {
+        if let Some(name) = settings::binding_name(key) {
+          self.key_bindings.set(action, name);
+          self.rebinding = None;
+          self.refresh_key_bindings();
+          break;
+        }
+      }
+    }
+    
+    for action in KeyAction::all() {
+      if self.widgets[controls].get_button_state(&(BIND_BUTTON_PREFIX.to_owned() + action.name())) {
+        // Clicking the key being rebound again leaves it as it was
+        if self.rebinding == Some(action) {
+          self.rebinding = None;
+        } else {
+          self.rebinding = Some(action);
+        }
+        self.refresh_key_bindings();
+      }
+    }
+    
+    if self.widgets[controls].get_button_state(&CONTROLS_DEFAULTS_BUTTON.to_string()) {
+      self.key_bindings = KeyBindings::new();
+      self.rebinding = None;
+      self.refresh_key_bindings();
+    }
+    
+    if self.widgets[controls].get_button_state(&CONTROLS_SAVE_BUTTON.to_string()) {
+      self.rebinding = None;
+      self.refresh_key_bindings();
+      
+      if self.key_bindings.conflicts().is_empty() {
+        self.settings.key_bindings = self.key_bindings.clone();
+        let status = match self.settings.save() {
+          Ok(()) => "Controls saved".to_string(),
+          Err(e) => "Unable to save settings: ".to_owned() + &e,
+        };
+        self.widgets[controls].update_text_field(&CONTROLS_STATUS_NAME.to_string(), status);
+      }
+    }
+    
+    if self.widgets[controls].get_button_state(&CONTROLS_BACK_BUTTON.to_string()) {
+      self.rebinding = None;
+      self.show();
+    }
+  }
+  
+  // Shows the key for every action, with any keys bound twice in red
+  fn refresh_key_bindings(&mut self) {
+    let conflicts = self.key_bindings.conflicts();
+    let controls = &mut self.widgets[CONTROLS_WINDOW_INDEX];
+    
+    for action in KeyAction::all() {
+      let key = self.key_bindings.key(action);
+      let conflicting = conflicts.iter().any(|(_, actions)| actions.contains(&action));
+      
+      let text = if self.rebinding == Some(action) { "...".to_string() } else { key.to_string() };
+      let colour = if conflicting { Vector4::new(1.0, 0.0, 0.0, 1.0) } else { Vector4::new(0.0, 0.0, 0.0, 1.0) };
+      
+      let name = BOUND_KEY_PREFIX.to_owned() + action.name();
+      controls.update_text_field(&name, text);
+      controls.set_textfield_colour(&name, colour);
+    }
+    
+    let status = match conflicts.first() {
+      Some((key, actions)) => {
+        let names: Vec<&str> = actions.iter().map(|a| a.name()).collect();
+        key.to_owned() + " is bound to " + &names.join(" and ")
+      },
+      None => {
+        if self.rebinding.is_some() {
+          "Press a key".to_string()
+        } else {
+          "".to_string()
+        }
+      }
+    };
+    controls.update_text_field(&CONTROLS_STATUS_NAME.to_string(), status);
+  }
+  
   pub fn draw(&self, draw_calls: &mut Vec<DrawCall>) {
     for widget in &self.widgets {
       widget.draw(draw_calls);
     }
     
     if self.apply_settings {
-      // do stuff
-      draw_calls.push(DrawCall::change_dpi(self.widgets[MAIN_WINDOW_INDEX].get_text(&CURRENT_DPI_NAME.to_string()).parse::<f32>().unwrap()));
-      draw_calls.push(DrawCall::enable_dpi(self.widgets[MAIN_WINDOW_INDEX].get_button_state(&DPI_BUTTON.to_string())));
-      draw_calls.push(DrawCall::enable_vsync(self.widgets[MAIN_WINDOW_INDEX].get_button_state(&VSYNC_BUTTON.to_string())));
-      let fullscreen = self.widgets[MAIN_WINDOW_INDEX].get_button_state(&FULLSCREEN_BUTTON.to_string());
-      draw_calls.push(DrawCall::enable_fullscreen(fullscreen));
-      if !fullscreen {
-        draw_calls.push(DrawCall::change_resolution(self.available_resolutions[self.current_resolution_index]));
-      }
+      OptionsUi::apply(&self.settings, draw_calls);
       println!("settings applied!");
     }
   }
@@ -0,0 +1,135 @@
+use maat_graphics::DrawCall;
+
+use crate::modules::system_interface::Widget;
+use crate::modules::system_interface::OptionsUi;
+use crate::modules::settings::Settings;
+
+use cgmath::Vector2;
+use cgmath::Vector4;
+
+const BACKGROUND_INDEX: usize = 0;
+const PAUSE_MENU_INDEX: usize = 1;
+
+const BACKGROUND_NAME: &str = "PauseBackground";
+const PAUSE_MENU_NAME: &str = "PauseMenu";
+const TITLE_NAME: &str = "PauseTitle";
+const RESUME_BUTTON_NAME: &str = "Resume";
+const OPTIONS_BUTTON_NAME: &str = "PauseOptions";
+const RESTART_BUTTON_NAME: &str = "PauseRestart";
+const QUIT_BUTTON_NAME: &str = "QuitToMenu";
+
+// Shown over the game while it is paused from the escape key
+pub struct PauseUi {
+  options_menu: OptionsUi,
+  widgets: Vec<Widget>,
+}
+
+impl PauseUi {
+  pub fn new(window_size: Vector2<f32>) -> PauseUi {
+    let mut widgets: Vec<Widget> = Vec::new();
+    
+    let menu_width = 256.0;
+    let menu_height = 320.0;
+    
+    let button_width = 160.0;
+    let button_height = 32.0;
+    let button_location = menu_height-96.0;
+    let button_offset = button_height + button_height*0.5;
+    
+    let text_colour = Vector4::new(0.0, 0.0, 0.0, 1.0);
+    let button_colour = Vector4::new(0.5019, 0.749, 1.0, 1.0);
+    let background_colour = Vector4::new(0.0, 0.1411, 0.4, 1.0);
+    
+    // Dims the game behind the menu
+    widgets.push(
+      Widget::new(BACKGROUND_NAME.to_string(), Vector2::new(window_size.x*0.5, window_size.y*0.5),
+                  Vector2::new(window_size.x, window_size.y),
+                  Vector4::new(0.0, 0.0, 0.0, 0.5))
+                .start_hidden()
+    );
+    
+    let mut menu = Widget::new(PAUSE_MENU_NAME.to_string(), Vector2::new(window_size.x*0.5, window_size.y*0.5),
+                               Vector2::new(menu_width, menu_height),
+                               background_colour)
+                             .start_hidden()
+                             .with_text_field_centered(TITLE_NAME.to_string(),
+                                                       Vector2::new(menu_width*0.5, menu_height-48.0),
+                                                       Vector2::new(196.0, 196.0),
+                                                       Vector4::new(1.0, 1.0, 1.0, 1.0),
+                                                       "Paused".to_string(), "Arial".to_string());
+    
+    let buttons = [(RESUME_BUTTON_NAME, "Resume"), (OPTIONS_BUTTON_NAME, "Options"),
+                   (RESTART_BUTTON_NAME, "Restart"), (QUIT_BUTTON_NAME, "Quit to menu")];
+    for (i, (name, text)) in buttons.iter().enumerate() {
+      menu = menu.with_button(Vector2::new(menu_width*0.5, button_location-button_offset*i as f32),
+                              Vector2::new(button_width, button_height),
+                              Vector2::new(button_width*0.5, button_height*0.33),
+                              Vector2::new(128.0, 128.0),
+                              name.to_string(),
+                              text_colour,
+                              Vector4::new(1.0, 0.0, 0.0, 1.0),
+                              button_colour,
+                              true, text.to_string(), "Arial".to_string());
+    }
+    widgets.push(menu);
+    
+    PauseUi {
+      options_menu: OptionsUi::new(window_size),
+      widgets,
+    }
+  }
+  
+  pub fn show(&mut self) {
+    for widget in &mut self.widgets {
+      widget.show();
+    }
+  }
+  
+  pub fn hide(&mut self) {
+    for widget in &mut self.widgets {
+      widget.hide();
+    }
+    self.options_menu.hide();
+  }
+  
+  pub fn is_hidden(&self) -> bool {
+    self.widgets[PAUSE_MENU_INDEX]._is_hidden()
+  }
+  
+  // As last saved from the options menu
+  pub fn settings(&self) -> &Settings {
+    self.options_menu.settings()
+  }
+  
+  pub fn resume_button_pressed(&self) -> bool {
+    self.widgets[PAUSE_MENU_INDEX].get_button_state(&RESUME_BUTTON_NAME.to_string())
+  }
+  
+  pub fn restart_button_pressed(&self) -> bool {
+    self.widgets[PAUSE_MENU_INDEX].get_button_state(&RESTART_BUTTON_NAME.to_string())
+  }
+  
+  pub fn quit_button_pressed(&self) -> bool {
+    self.widgets[PAUSE_MENU_INDEX].get_button_state(&QUIT_BUTTON_NAME.to_string())
+  }
+  
+  pub fn update(&mut self, delta_time: f32, mouse_pos: Vector2<f32>, left_mouse: bool, keys_pressed_this_frame: &Vec<String>, scroll_delta: f32) {
+    // The options menu sits on top, nothing under it can be clicked
+    let clickable = !self.is_hidden() && self.options_menu.is_hidden() && left_mouse;
+    self.widgets[PAUSE_MENU_INDEX].update(delta_time, mouse_pos, clickable, keys_pressed_this_frame, scroll_delta);
+    
+    if self.widgets[PAUSE_MENU_INDEX].get_button_state(&OPTIONS_BUTTON_NAME.to_string()) {
+      self.options_menu.show();
+    }
+    
+    self.options_menu.update(delta_time, mouse_pos, left_mouse, keys_pressed_this_frame, scroll_delta);
+  }
+  
+  pub fn draw(&self, draw_calls: &mut Vec<DrawCall>) {
+    self.widgets[BACKGROUND_INDEX].draw(draw_calls);
+    if self.options_menu.is_hidden() {
+      self.widgets[PAUSE_MENU_INDEX].draw(draw_calls);
+    }
+    self.options_menu.draw(draw_calls);
+  }
+}
@@ -129,6 +129,34 @@ impl Selection {
     selection
   }
   
+  pub fn touching_option(&self, against: Vector2<f32>) -> Option<String> {
+    for button in &self.buttons {
+      if button.is_touching(against) {
+        return Some(button.get_name());
+      }
+    }
+    None
+  }
+  
+  // None leaves nothing selected
+  pub fn set_selected_option(&mut self, name: Option<&String>) {
+    for button in &mut self.buttons {
+      if name.map(|name| button.name_matches(name)).unwrap_or(false) {
+        button.toggle_on();
+      } else {
+        button.toggle_off();
+      }
+    }
+  }
+  
+  pub fn set_option_colour(&mut self, name: &String, pressed: Vector4<f32>, unpressed: Vector4<f32>) {
+    for button in &mut self.buttons {
+      if button.name_matches(name) {
+        button.set_colour(pressed, unpressed);
+      }
+    }
+  }
+  
   pub fn update(&mut self, _delta_time: f32, mouse_pos: Vector2<f32>, left_mouse: bool) {
     if left_mouse {
       for i in 0..self.buttons.len() {
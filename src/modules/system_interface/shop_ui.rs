@@ -0,0 +1,218 @@
+use maat_graphics::DrawCall;
+
+use crate::modules::system_interface::Widget;
+use crate::modules::appliances::traits::ApplianceKind;
+use crate::modules::settings::{KeyBindings, KeyAction};
+use crate::modules::map::Map;
+
+use cgmath::Vector2;
+use cgmath::Vector4;
+
+const SHOP_INDEX: usize = 0;
+const ITEMS_SELECTION: usize = 0;
+
+const SHOP_NAME: &str = "Shop";
+const ITEM_PREFIX: &str = "ShopItem";
+const ITEM_NAME_PREFIX: &str = "ShopName";
+const ITEM_COST_PREFIX: &str = "ShopCost";
+const ITEM_DESCRIPTION_PREFIX: &str = "ShopDescription";
+
+const SHOP_WIDTH: f32 = 280.0;
+const ITEM_HEIGHT: f32 = 48.0;
+const ITEM_SPACING: f32 = 8.0;
+const TOOLTIP_SIZE: Vector2<f32> = Vector2 { x: 224.0, y: 84.0 };
+
+fn place_action(kind: ApplianceKind) -> KeyAction {
+  match kind {
+    ApplianceKind::Dishwasher => KeyAction::PlaceDishwasher,
+    ApplianceKind::Fridge => KeyAction::PlaceFridge,
+    ApplianceKind::MeatTenderizer => KeyAction::PlaceMeatTenderizer,
+    ApplianceKind::CoffeeMachine => KeyAction::PlaceCoffeeMachine,
+    ApplianceKind::SaltGrinder => KeyAction::PlaceSaltGrinder,
+    ApplianceKind::CutleryDrawer => KeyAction::PlaceCutleryDrawer,
+  }
+}
+
+// What the shop shows about an appliance, read from a fresh one
+struct ShopItem {
+  kind: ApplianceKind,
+  cost: i32,
+  range: u32,
+  fire_rate: f32,
+  life_expectancy: i32,
+}
+
+impl ShopItem {
+  fn option_name(&self) -> String {
+    format!("{}{:?}", ITEM_PREFIX, self.kind)
+  }
+  
+  fn field_name(&self, prefix: &str) -> String {
+    format!("{}{:?}", prefix, self.kind)
+  }
+}
+
+// Panel down the left of the game screen for buying appliances with the mouse
+pub struct ShopUi {
+  items: Vec<ShopItem>,
+  hovered: Option<usize>,
+  left_mouse_last_frame: bool,
+  widgets: Vec<Widget>,
+}
+
+impl ShopUi {
+  pub fn new(window_size: Vector2<f32>, map: &Map, key_bindings: &KeyBindings) -> ShopUi {
+    let items: Vec<ShopItem> = ApplianceKind::all().into_iter().map(|kind| {
+      let appliance = kind.create(Vector2::new(0, 0), map);
+      ShopItem {
+        kind,
+        cost: appliance.buy_cost(),
+        range: appliance.get_range(),
+        fire_rate: appliance.get_fire_rate(),
+        life_expectancy: appliance.max_life_expectancy(),
+      }
+    }).collect();
+    
+    let item_count = items.len() as f32;
+    let shop_height = item_count*ITEM_HEIGHT + (item_count-1.0)*ITEM_SPACING + ITEM_SPACING*2.0;
+    // Top lines up with where the first buy key hint used to be
+    let shop_position = Vector2::new(16.0+SHOP_WIDTH*0.5, window_size.y*0.5+80.0-shop_height*0.5);
+    let item_size = Vector2::new(SHOP_WIDTH-ITEM_SPACING*2.0, ITEM_HEIGHT);
+    
+    let item_colour = Vector4::new(0.5019, 0.749, 1.0, 1.0);
+    
+    // Options are stacked upwards, so the first item goes on the end to sit on top
+    let option_names: Vec<String> = items.iter().rev().map(|item| item.option_name()).collect();
+    let option_colours = vec!(item_colour; items.len());
+    let option_text = vec!("".to_string(); items.len());
+    
+    let mut shop = Widget::new(SHOP_NAME.to_string(), shop_position,
+                               Vector2::new(SHOP_WIDTH, shop_height),
+                               Vector4::new(0.0, 0.1411, 0.4, 0.8))
+                             .with_upwards_selection(Vector2::new(SHOP_WIDTH*0.5, ITEM_SPACING+ITEM_HEIGHT*0.5),
+                                                     item_size,
+                                                     Vector2::new(0.0, 0.0),
+                                                     Vector2::new(64.0, 64.0),
+                                                     Vector4::new(0.0, 0.0, 0.0, 1.0),
+                                                     ITEM_SPACING,
+                                                     option_names,
+                                                     option_colours,
+                                                     Vector4::new(1.0, 1.0, 0.0, 1.0),
+                                                     false, option_text, "Arial".to_string());
+    
+    for (i, item) in items.iter().enumerate() {
+      let bottom = shop_height - (i as f32 + 1.0)*(ITEM_HEIGHT+ITEM_SPACING);
+      shop = shop.with_text_field(item.field_name(ITEM_NAME_PREFIX),
+                                  Vector2::new(ITEM_SPACING*2.0, bottom+24.0),
+                                  Vector2::new(96.0, 96.0),
+                                  Vector4::new(0.0, 0.0, 0.0, 1.0),
+                                  "".to_string(), "Arial".to_string())
+                 .with_text_field(item.field_name(ITEM_COST_PREFIX),
+                                  Vector2::new(SHOP_WIDTH-72.0, bottom+24.0),
+                                  Vector2::new(96.0, 96.0),
+                                  Vector4::new(0.0, 0.0, 0.0, 1.0),
+                                  "$".to_owned() + &item.cost.to_string(), "Arial".to_string())
+                 .with_text_field(item.field_name(ITEM_DESCRIPTION_PREFIX),
+                                  Vector2::new(ITEM_SPACING*2.0, bottom+6.0),
+                                  Vector2::new(64.0, 64.0),
+                                  Vector4::new(0.1, 0.1, 0.1, 1.0),
+                                  item.kind.description().to_string(), "Arial".to_string());
+    }
+    
+    let mut shop_ui = ShopUi {
+      items,
+      hovered: None,
+      left_mouse_last_frame: false,
+      widgets: vec!(shop),
+    };
+    shop_ui.widgets[SHOP_INDEX].set_selected_option_i(ITEMS_SELECTION, None);
+    shop_ui.set_key_bindings(key_bindings);
+    
+    shop_ui
+  }
+  
+  // Each item is labelled with the key that buys it
+  pub fn set_key_bindings(&mut self, key_bindings: &KeyBindings) {
+    for item in &self.items {
+      let label = key_bindings.key(place_action(item.kind)).to_owned() + ": " + item.kind.name();
+      self.widgets[SHOP_INDEX].update_text_field(&item.field_name(ITEM_NAME_PREFIX), label);
+    }
+  }
+  
+  pub fn is_touching(&self, at_location: Vector2<f32>) -> bool {
+    self.widgets[SHOP_INDEX].is_touching(at_location)
+  }
+  
+  // Returns the appliance clicked on this frame, as long as it can be paid for
+  pub fn update(&mut self, delta_time: f32, mouse_pos: Vector2<f32>, left_mouse: bool, money: i32, placing: Option<ApplianceKind>) -> Option<ApplianceKind> {
+    let keys_pressed_this_frame = Vec::new();
+    self.widgets[SHOP_INDEX].update(delta_time, mouse_pos, left_mouse, &keys_pressed_this_frame, 0.0);
+    
+    let touching = self.widgets[SHOP_INDEX].touching_selection_option_i(ITEMS_SELECTION, mouse_pos);
+    self.hovered = self.items.iter().position(|item| Some(item.option_name()) == touching);
+    
+    let clicked = left_mouse && !self.left_mouse_last_frame;
+    self.left_mouse_last_frame = left_mouse;
+    
+    let mut bought = None;
+    for item in &self.items {
+      let affordable = item.cost <= money;
+      let (pressed, unpressed, text) = if affordable {
+        (Vector4::new(0.7, 0.9, 1.0, 1.0), Vector4::new(0.5019, 0.749, 1.0, 1.0), Vector4::new(0.0, 0.0, 0.0, 1.0))
+      } else {
+        (Vector4::new(0.5, 0.5, 0.5, 1.0), Vector4::new(0.4, 0.4, 0.4, 1.0), Vector4::new(1.0, 0.0, 0.0, 1.0))
+      };
+      
+      let option_name = item.option_name();
+      self.widgets[SHOP_INDEX].set_selection_option_i_colour(ITEMS_SELECTION, &option_name, pressed, unpressed);
+      self.widgets[SHOP_INDEX].set_textfield_colour(&item.field_name(ITEM_COST_PREFIX), text);
+      
+      if clicked && affordable && touching == Some(option_name) {
+        bought = Some(item.kind);
+      }
+    }
+    
+    // Whatever is being placed stays highlighted, however it was picked
+    let selected = bought.or(placing).and_then(|kind| self.items.iter().find(|item| item.kind == kind)).map(|item| item.option_name());
+    self.widgets[SHOP_INDEX].set_selected_option_i(ITEMS_SELECTION, selected.as_ref());
+    
+    bought
+  }
+  
+  pub fn draw(&self, draw_calls: &mut Vec<DrawCall>) {
+    for widget in &self.widgets {
+      widget.draw(draw_calls);
+    }
+    
+    if let Some(i) = self.hovered {
+      self.draw_tooltip(i, draw_calls);
+    }
+  }
+  
+  fn draw_tooltip(&self, i: usize, draw_calls: &mut Vec<DrawCall>) {
+    let item = &self.items[i];
+    
+    let shop_position = self.widgets[SHOP_INDEX].get_widget_position();
+    let shop_size = self.widgets[SHOP_INDEX].get_widget_size();
+    let item_center = shop_position.y + shop_size.y*0.5 - (i as f32 + 1.0)*(ITEM_HEIGHT+ITEM_SPACING) + ITEM_HEIGHT*0.5;
+    let position = Vector2::new(shop_position.x + shop_size.x*0.5 + ITEM_SPACING + TOOLTIP_SIZE.x*0.5, item_center);
+    
+    draw_calls.push(DrawCall::draw_coloured(position, TOOLTIP_SIZE, Vector4::new(0.0, 0.0, 0.0, 0.8), 90.0));
+    
+    // The coffee machine buffs rather than fires
+    let rate = if item.kind == ApplianceKind::CoffeeMachine { "Buffs every " } else { "Fires every " };
+    let lines = ["Range: ".to_owned() + &item.range.to_string(),
+                 rate.to_owned() + &format!("{:.1}", item.fire_rate) + "s",
+                 "Life expectancy: ".to_owned() + &item.life_expectancy.to_string()];
+    
+    let left = position.x - TOOLTIP_SIZE.x*0.5 + ITEM_SPACING;
+    let top = position.y + TOOLTIP_SIZE.y*0.5 - 28.0;
+    for (j, line) in lines.iter().enumerate() {
+      draw_calls.push(DrawCall::draw_text_basic(Vector2::new(left, top - j as f32*24.0),
+                                           Vector2::new(64.0, 64.0),
+                                           Vector4::new(1.0, 1.0, 1.0, 1.0),
+                                           line.to_string(),
+                                           "Arial".to_string()));
+    }
+  }
+}
@@ -35,7 +35,7 @@ impl TextField {
     }
   }
   
-  pub fn _new_text_field_editable(name: String, position: Vector2<f32>, size: Vector2<f32>, colour: Vector4<f32>, centered: bool, text: String, font: String) -> TextField {
+  pub fn new_text_field_editable(name: String, position: Vector2<f32>, size: Vector2<f32>, colour: Vector4<f32>, centered: bool, text: String, font: String) -> TextField {
     
     println!("editable made");
     TextField {
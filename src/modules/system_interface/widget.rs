@@ -70,7 +70,7 @@ impl Widget {
     }
   }
   
-  pub fn _with_upwards_selection(mut self, relative_position: Vector2<f32>, option_size: Vector2<f32>, relative_text_position: Vector2<f32>, text_size: Vector2<f32>, text_colour: Vector4<f32>, spacing: f32, option_names: Vec<String>, option_colours: Vec<Vector4<f32>>, selected_background: Vector4<f32>, center_text: bool, text: Vec<String>, font: String) -> Widget {
+  pub fn with_upwards_selection(mut self, relative_position: Vector2<f32>, option_size: Vector2<f32>, relative_text_position: Vector2<f32>, text_size: Vector2<f32>, text_colour: Vector4<f32>, spacing: f32, option_names: Vec<String>, option_colours: Vec<Vector4<f32>>, selected_background: Vector4<f32>, center_text: bool, text: Vec<String>, font: String) -> Widget {
     let pos = (self.position-self.size*0.5) + relative_position;
     self.selections.push(Selection::_new_upwards(pos, option_size, relative_text_position, text_size, 
                                                 text_colour, spacing, option_names, option_colours, 
@@ -141,9 +141,9 @@ impl Widget {
     self
   }
   
-  pub fn _with_text_field_editable(mut self, text_name: String, relative_position: Vector2<f32>, text_size: Vector2<f32>, text_colour: Vector4<f32>, text: String, font: String) -> Widget {
+  pub fn with_text_field_editable(mut self, text_name: String, relative_position: Vector2<f32>, text_size: Vector2<f32>, text_colour: Vector4<f32>, text: String, font: String) -> Widget {
     let pos = (self.position-self.size*0.5) + relative_position;
-    self.text_fields.push(TextField::_new_text_field_editable(text_name, pos, text_size, text_colour, false, text, font));
+    self.text_fields.push(TextField::new_text_field_editable(text_name, pos, text_size, text_colour, false, text, font));
     
     self
   }
@@ -181,11 +181,11 @@ impl Widget {
     (name, position, size, colour)
   }
   
-  pub fn _get_widget_position(&self) -> Vector2<f32> {
+  pub fn get_widget_position(&self) -> Vector2<f32> {
     self.position
   }
   
-  pub fn _get_widget_size(&self) -> Vector2<f32> {
+  pub fn get_widget_size(&self) -> Vector2<f32> {
     self.size
   }
   
@@ -311,7 +311,7 @@ impl Widget {
     self.text_fields[index].clone()
   }
   
-  pub fn _set_textfield_colour(&mut self, name: &String, text_colour: Vector4<f32>) {
+  pub fn set_textfield_colour(&mut self, name: &String, text_colour: Vector4<f32>) {
     for text in &mut self.text_fields {
       if text.name_matches(name) {
         text._set_colour(text_colour);
@@ -387,7 +387,7 @@ impl Widget {
   pub fn _set_button_colour(&mut self, name: &String, pressed: Vector4<f32>, unpressed: Vector4<f32>) {
     for button in &mut self.buttons {
       if button.name_matches(name) {
-        button._set_colour(pressed, unpressed);
+        button.set_colour(pressed, unpressed);
         break;
       }
     }
@@ -453,7 +453,7 @@ impl Widget {
     pressed
   }
   
-  pub fn _set_button_state(&mut self, button_name: &String, new_state: bool) {
+  pub fn set_button_state(&mut self, button_name: &String, new_state: bool) {
     for button in &mut self.buttons {
       if button.name_matches(button_name) {
         if new_state {
@@ -512,6 +512,18 @@ impl Widget {
     self.selections[selection_set]._is_touching_button(button_index, against)
   }
   
+  pub fn touching_selection_option_i(&self, selection_set: usize, against: Vector2<f32>) -> Option<String> {
+    self.selections[selection_set].touching_option(against)
+  }
+  
+  pub fn set_selected_option_i(&mut self, selection_set: usize, name: Option<&String>) {
+    self.selections[selection_set].set_selected_option(name);
+  }
+  
+  pub fn set_selection_option_i_colour(&mut self, selection_set: usize, name: &String, pressed: Vector4<f32>, unpressed: Vector4<f32>) {
+    self.selections[selection_set].set_option_colour(name, pressed, unpressed);
+  }
+  
   //
   // WIDGET FUNCTIONS
   //
@@ -1,119 +1,242 @@
-use crate::modules::food::{Food, Strawberry, Banana, Pineapple, Mushroom, Jelly, Cake};
+use crate::modules::food::{Food, FoodIds, Strawberry, Banana, Pineapple, Mushroom, Jelly, Cake};
 use crate::modules::map::Map;
+use crate::modules::map_file::RouteSelection;
+use crate::modules::wave_file::{WaveFile, WaveDefinition, SpawnGroup, FoodKind};
 
-use cgmath::{Vector3};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use cgmath::{Vector2};
+
+use std::rc::Rc;
 
 type Wave = Vec<(Box<Food>, f32)>;
 
+const ENDLESS_BASE_COUNT: u32 = 60;
+const ENDLESS_COUNT_PER_WAVE: u32 = 10;
+const ENDLESS_HEALTH_PER_WAVE: f32 = 0.15;
+const ENDLESS_SPEED_PER_WAVE: f32 = 0.02;
+const ENDLESS_MAX_SPEED: f32 = 1.5;
+const ENDLESS_MIN_SPACING: f32 = 0.2;
+
+fn new_food(kind: FoodKind, id: i32, position: Vector2<f32>, path: Rc<Vec<u32>>, location: Vector2<i32>) -> Box<Food> {
+  match kind {
+    FoodKind::Banana => Box::new(Banana::new(id, position, path, location)),
+    FoodKind::Strawberry => Box::new(Strawberry::new(id, position, path, location)),
+    FoodKind::Pineapple => Box::new(Pineapple::new(id, position, path, location)),
+    FoodKind::Cake => Box::new(Cake::new(id, position, path, location)),
+    FoodKind::Mushroom => Box::new(Mushroom::new(id, position, path, location)),
+    FoodKind::Jelly => Box::new(Jelly::new(id, position, path, location)),
+  }
+}
+
+// extra_wave counts from 1 for the first wave past the scripted ones. Every
+// wave brings more food that is tougher, faster and leans towards the nastier kinds
+fn endless_wave(extra_wave: u32, rng: &mut StdRng) -> WaveDefinition {
+  let total = ENDLESS_BASE_COUNT + ENDLESS_COUNT_PER_WAVE*extra_wave;
+  let health = 1.0 + ENDLESS_HEALTH_PER_WAVE*extra_wave as f32;
+  let speed = (1.0 + ENDLESS_SPEED_PER_WAVE*extra_wave as f32).min(ENDLESS_MAX_SPEED);
+  let spacing = (0.5 - 0.01*extra_wave as f32).max(ENDLESS_MIN_SPACING);
+  
+  let weights = vec!(
+    (FoodKind::Banana, 10u32.saturating_sub(extra_wave).max(1)),
+    (FoodKind::Strawberry, 8),
+    (FoodKind::Pineapple, 2 + extra_wave),
+    (FoodKind::Mushroom, 1 + extra_wave/2),
+    (FoodKind::Jelly, 1 + extra_wave/2),
+    (FoodKind::Cake, extra_wave/3),
+  );
+  
+  let group_count = rng.gen_range(2, 5);
+  let mut groups = Vec::new();
+  for i in 0..group_count {
+    let food = weights.choose_weighted(rng, |w| w.1).unwrap().0;
+    let count = {
+      match food {
+        FoodKind::Cake => 1 + extra_wave/10,
+        _ => (total / group_count).max(1),
+      }
+    };
+    
+    groups.push(SpawnGroup {
+      food,
+      count,
+      start: i as f32*rng.gen_range(0.0, 5.0),
+      spacing,
+      health,
+      speed,
+      repeat: 1,
+      repeat_every: 0.0,
+    });
+  }
+  
+  WaveDefinition {
+    groups,
+  }
+}
+
+#[derive(Clone)]
+struct SpawnPoint {
+  path: Rc<Vec<u32>>,
+  position: Vector2<f32>,
+  location: Vector2<i32>,
+}
+
 #[derive(Clone)]
 pub struct FoodStore {
   waves: Vec<Wave>,
+  spawn_points: Vec<SpawnPoint>,
+  // Spawn points grouped by the start they leave from, so every start gets
+  // its share however many routes it has
+  lanes: Vec<Vec<usize>>,
+  route_selection: RouteSelection,
+  next_route: usize,
+  scripted_waves: usize,
+  endless: bool,
+  rng: StdRng,
   current_idx: usize,
   current_wave: usize,
   wave_delta: f32,
 }
 
 impl FoodStore {
-  pub fn new(map: &Map) -> FoodStore {
-    let path = map.get_path();
-    let food_pos = map.tile_position_from_index(path[0] as usize);
-    let tile_loc = map.get_qr_from_index(path[0] as usize);
-    
-    // 83 dollars
-    let mut wave1 = Vec::new();
-    for i in 0..39 {
-      wave1.push((Box::new(Banana::new(i, food_pos, path.clone(), tile_loc)) as Box<Food>, i as f32));
-    }
-    wave1.push((Box::new(Strawberry::new(40, food_pos, path.clone(), tile_loc)) as Box<Food>, 40.0));
-    
-    // 145 dollars
-    let mut wave2 = Vec::new();
-    for i in 0..5 {
-      wave2.push((Box::new(Strawberry::new(i, food_pos, path.clone(), tile_loc)) as Box<Food>, ((i as f32*0.25))));
-    }
-    for i in 0..60 {
-      wave2.push((Box::new(Banana::new(6+i, food_pos, path.clone(), tile_loc)) as Box<Food>, 1.25+i as f32*0.75));
+  pub fn new(map: &Map, wave_file: &WaveFile, seed: u64) -> FoodStore {
+    let mut spawn_points = Vec::new();
+    for route in map.get_routes() {
+      spawn_points.push(SpawnPoint {
+        path: Rc::new(route.clone()),
+        position: map.tile_position_from_index(route[0] as usize),
+        location: map.get_qr_from_index(route[0] as usize),
+      });
     }
     
-    // 280 dollars
-    let mut wave3 = Vec::new();
-    for i in 0..80 {
-      if i%2 == 0 {
-        wave3.push((Box::new(Banana::new(i, food_pos, path.clone(), tile_loc)) as Box<Food>, i as f32*0.5));
-      } else {
-        wave3.push((Box::new(Strawberry::new(i, food_pos, path.clone(), tile_loc)) as Box<Food>, i as f32*0.5));
+    let mut lanes: Vec<Vec<usize>> = Vec::new();
+    for (i, spawn) in spawn_points.iter().enumerate() {
+      match lanes.iter_mut().find(|lane| spawn_points[lane[0]].path[0] == spawn.path[0]) {
+        Some(lane) => lane.push(i),
+        None => lanes.push(vec!(i)),
       }
     }
     
-    // 400 dollars
-    let mut wave4 = Vec::new();
-    for i in 0..80 {
-      wave4.push((Box::new(Strawberry::new(i, food_pos, path.clone(), tile_loc)) as Box<Food>, i as f32*0.5));
-    }
-    
-    // 800 dollars
-    let mut wave5 = Vec::new();
-    let mut offset = 0;
-    
-    let spacing = 0.3;
-    
-    for j in 0..3 {
-      for i in 0..20 {
-        wave5.push((Box::new(Strawberry::new(offset+i, food_pos, path.clone(), tile_loc)) as Box<Food>, offset as f32*spacing+i as f32*spacing));
-      }
-      offset += 20;
-      for i in 0..50 {
-        wave5.push((Box::new(Banana::new(offset+i, food_pos, path.clone(), tile_loc)) as Box<Food>, offset as f32*spacing+i as f32*spacing));
-      }
-      offset += 50;
-    }
-    wave5.push((Box::new(Pineapple::new(offset+1, food_pos, path.clone(), tile_loc)) as Box<Food>, 0 as f32));
-    //
-    let mut wave6 = Vec::new();
-    wave6.push((Box::new(Cake::new(0, food_pos, path.clone(), tile_loc)) as Box<Food>, 0 as f32));
+    let mut store = FoodStore {
+      waves: Vec::new(),
+      spawn_points,
+      lanes,
+      route_selection: map.get_info().route_selection.clone(),
+      next_route: 0,
+      scripted_waves: wave_file.waves.len(),
+      endless: false,
+      rng: StdRng::seed_from_u64(seed),
+      current_idx: 0,
+      current_wave: 0,
+      wave_delta: 0.0,
+    };
     
-    let mut wave7 = Vec::new();
-    for i in 0..20 {
-      wave7.push((Box::new(Strawberry::new(i, food_pos, path.clone(), tile_loc)) as Box<Food>, (i as f32*0.5)));
-    }
-    for i in 20..80 {
-      wave7.push((Box::new(Pineapple::new(i, food_pos, path.clone(), tile_loc)) as Box<Food>, i as f32*0.5));
+    for definition in &wave_file.waves {
+      let wave = store.build_wave(definition);
+      store.waves.push(wave);
     }
     
-    let mut wave8 = Vec::new();
-    wave8.push((Box::new(Cake::new(0, food_pos, path.clone(), tile_loc)) as Box<Food>, 0 as f32));
-    for i in 0..25 {
-      wave8.push((Box::new(Pineapple::new(i, food_pos, path.clone(), tile_loc)) as Box<Food>, i as f32*0.25));
-      wave8.push((Box::new(Banana::new(i, food_pos, path.clone(), tile_loc)) as Box<Food>, i as f32*0.25));
+    store
+  }
+  
+  // Once the scripted waves run out new ones are generated from the seed forever
+  pub fn set_endless(&mut self) {
+    self.endless = true;
+    self.add_endless_waves();
+  }
+  
+  pub fn is_endless(&self) -> bool {
+    self.endless
+  }
+  
+  fn build_wave(&mut self, definition: &WaveDefinition) -> Wave {
+    let mut spawns = Vec::new();
+    for group in &definition.groups {
+      for time in group.spawn_times() {
+        spawns.push((group, time));
+      }
     }
     
-    let mut wave9 = Vec::new();
-    wave9.push((Box::new(Cake::new(0, food_pos, path.clone(), tile_loc)) as Box<Food>, 0 as f32));
-    for i in 1..31 {
-      wave9.push((Box::new(Mushroom::new(i, food_pos, path.clone(), tile_loc)) as Box<Food>, i as f32*1.5));
-    }
+    // Foods come out in list order so the wave has to be sorted by time
+    spawns.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
     
-    let mut wave10 = Vec::new();
-    for i in 0..60 {
-      wave10.push((Box::new(Jelly::new(i, food_pos, path.clone(), tile_loc)) as Box<Food>, i as f32));
+    let mut wave: Wave = Vec::new();
+    for i in 0..spawns.len() {
+      let (group, time) = spawns[i];
+      
+      // The lane is picked first, then one of its routes
+      let lane_count = self.lanes.len();
+      let route_idx = {
+        match self.route_selection {
+          RouteSelection::Random => {
+            let lane = if lane_count > 1 { &self.lanes[self.rng.gen_range(0, lane_count)] } else { &self.lanes[0] };
+            lane[self.rng.gen_range(0, lane.len())]
+          },
+          RouteSelection::RoundRobin => {
+            self.next_route += 1;
+            let turn = self.next_route-1;
+            let lane = &self.lanes[turn % lane_count];
+            lane[(turn / lane_count) % lane.len()]
+          },
+          RouteSelection::PerWave => {
+            let turn = self.waves.len();
+            let lane = &self.lanes[turn % lane_count];
+            lane[(turn / lane_count) % lane.len()]
+          },
+        }
+      };
+      
+      let spawn = &self.spawn_points[route_idx];
+      // ids are handed out as the food spawns
+      let mut food = new_food(group.food, 0, spawn.position, spawn.path.clone(), spawn.location);
+      food.scale_stats(group.health, group.speed);
+      wave.push((food, time));
     }
     
-    FoodStore {
-      waves: vec!(wave1, wave2, wave3, wave4, wave5, wave6,wave7, wave8, wave9, wave10),
-      current_idx: 0,
-      current_wave: 0,
-      wave_delta: 0.0,
+    wave
+  }
+  
+  fn add_endless_waves(&mut self) {
+    while self.endless && self.current_wave >= self.waves.len() {
+      let extra_wave = (self.waves.len() - self.scripted_waves) as u32 + 1;
+      let definition = endless_wave(extra_wave, &mut self.rng);
+      let wave = self.build_wave(&definition);
+      self.waves.push(wave);
     }
   }
   
+  // The map's routes in the same order, shared with every food spawned on them
+  pub fn routes(&self) -> Vec<Rc<Vec<u32>>> {
+    self.spawn_points.iter().map(|spawn| spawn.path.clone()).collect()
+  }
+  
+  // Which wave, how many of its foods have come out and how far into it we are
+  pub fn progress(&self) -> (usize, usize, f32) {
+    (self.current_wave, self.current_idx, self.wave_delta)
+  }
+  
+  pub fn set_progress(&mut self, wave: usize, idx: usize, wave_delta: f32) {
+    self.current_wave = wave;
+    self.current_idx = idx;
+    self.wave_delta = wave_delta;
+    self.add_endless_waves();
+  }
+  
   pub fn wave_number(&self) -> usize {
     self.current_wave
   }
   
+  pub fn is_finished(&self) -> bool {
+    self.current_wave >= self.waves.len()
+  }
+  
   pub fn skip_wave(&mut self) {
     self.current_wave += 1;
     self.wave_delta = 0.0;
     self.current_idx = 0;
+    self.add_endless_waves();
   }
   
   pub fn next_wave(&mut self) -> bool {
@@ -124,19 +247,21 @@ impl FoodStore {
         self.current_wave += 1;
         self.current_idx = 0;
         self.wave_delta = 0.0;
+        self.add_endless_waves();
       }
     }
     
     next_wave_started
   }
   
-  pub fn update(&mut self, delta_time: f32) -> Option<Box<Food>> {
+  pub fn update(&mut self, delta_time: f32, ids: &mut FoodIds) -> Option<Box<Food>> {
     if self.current_wave < self.waves.len() {
       let wave = &mut self.waves[self.current_wave];
       
       if self.current_idx < wave.len() {
         if self.wave_delta >= wave[self.current_idx].1 {
-          let food = wave[self.current_idx].0.clone();
+          let mut food = wave[self.current_idx].0.clone();
+          food.set_id(ids.allocate());
           self.current_idx += 1;
           return Some(food);
         }
@@ -0,0 +1,89 @@
+use crate::modules::appliances::traits::{Appliance, Buff};
+use crate::modules::simulation::DELTA_STEP;
+
+use cgmath::Vector2;
+
+// Seconds of game time the player has to take something back
+pub const UNDO_WINDOW: f32 = 10.0;
+
+// What it takes to put the game back the way it was before a command.
+// Appliances are found again by the hex they sit on, as indices shift
+#[derive(Clone)]
+pub enum UndoAction {
+  Place { q: i32, r: i32, cost: i32 },
+  Move { index: usize, from: Vector2<i32>, to: Vector2<i32>, tiles: i32 },
+  // A copy of the appliance as it was when sold, and the buffs it was giving
+  Sell { index: usize, appliance: Box<Appliance>, refund: i32, lost_buffs: Vec<LostBuff> },
+  Clean { q: i32, r: i32, life_expectancy: i32, cost: i32 },
+  EmptyBin { emptied: i32, cost: i32 },
+}
+
+impl UndoAction {
+  pub fn name(&self) -> &'static str {
+    match self {
+      UndoAction::Place { .. } => "placement",
+      UndoAction::Move { .. } => "move",
+      UndoAction::Sell { .. } => "sale",
+      UndoAction::Clean { .. } => "clean",
+      UndoAction::EmptyBin { .. } => "bin empty",
+    }
+  }
+}
+
+// A buff taken off a neighbour when the appliance giving it was removed,
+// along with any life the neighbour lost with it
+#[derive(Clone)]
+pub struct LostBuff {
+  pub q: i32,
+  pub r: i32,
+  pub buff: Buff,
+  pub life: i32,
+}
+
+#[derive(Clone)]
+pub struct UndoEntry {
+  pub action: UndoAction,
+  pub wave: usize,
+  pub tick: u64,
+}
+
+// Most recent last, only the top entry can be undone
+#[derive(Clone)]
+pub struct UndoStack {
+  entries: Vec<UndoEntry>,
+}
+
+impl UndoStack {
+  pub fn new() -> UndoStack {
+    UndoStack {
+      entries: Vec::new(),
+    }
+  }
+  
+  pub fn push(&mut self, action: UndoAction, wave: usize, tick: u64) {
+    self.entries.push(UndoEntry {
+      action,
+      wave,
+      tick,
+    });
+  }
+  
+  pub fn pop(&mut self) -> Option<UndoEntry> {
+    self.entries.pop()
+  }
+  
+  pub fn last(&self) -> Option<&UndoEntry> {
+    self.entries.last()
+  }
+  
+  pub fn clear(&mut self) {
+    self.entries.clear();
+  }
+  
+  // Drops everything from an earlier wave or older than window seconds
+  pub fn expire(&mut self, wave: usize, tick: u64, window: f32) {
+    self.entries.retain(|entry| {
+      entry.wave == wave && (tick - entry.tick) as f32 * DELTA_STEP <= window
+    });
+  }
+}
@@ -3,53 +3,37 @@ use crate::modules::appliances::traits::{Appliance};
 use crate::modules::weapons::Weapon;
 use crate::modules::hexagon::{Hexagon, HexagonType};
 use crate::modules::map::Map;
+use crate::modules::food_index::FoodIndex;
 
 use cgmath::Vector3;
 
-pub fn update_game(map: &mut Map, appliances: &mut Vec<Box<Appliance>>, foods: &mut Vec<Box<Food>>, weapons: &mut Vec<Box<Weapon>>, mut selected_appliance: &mut Option<usize>, model_sizes: &mut Vec<(String, Vector3<f32>)>, delta_time: f32) {
+pub fn update_game(map: &mut Map, appliances: &mut Vec<Box<Appliance>>, foods: &mut Vec<Box<Food>>, weapons: &mut Vec<Box<Weapon>>, selected_appliance: &mut Option<usize>, model_sizes: &mut Vec<(String, Vector3<f32>)>, delta_time: f32) {
   for food in &mut foods.iter_mut() {
     food.update(map, delta_time);
   }
   
   foods.sort_by(|a,b| (a.get_path_num()).cmp(&b.get_path_num()).reverse());
+  let food_index = FoodIndex::new(foods, map);
   
   let mut buffs = Vec::new();
-  
-  let mut offset = 0;
   for i in 0..appliances.len() {
-    if offset > i {
-      break;
-    }
-    
-    let new_buffs = appliances[i-offset].update(foods, weapons, model_sizes, map, delta_time);
+    let new_buffs = appliances[i].update(foods, &food_index, weapons, model_sizes, map, delta_time);
     for buff in new_buffs {
       buffs.push((buff, i));
     }
-    
-    if appliances[i-offset].current_life_expectancy() <= 0 {
-      let qr = appliances[i-offset].get_qr_location();
-      map.set_hexagon_type(qr.x, qr.y, HexagonType::Open);
-      appliances.remove(i-offset);
-      if let Some(idx) = &mut selected_appliance {
-        if i-offset < *idx {
-          *idx -= 1;
-        }
-      }
-      offset += 1;
-    }
   }
   
+  // Buffs go out before any appliance is removed so the index of the
+  // appliance handing one out still points at it
   for (buff, idx) in buffs {
+    let (actual_buff, qr_location, range) = buff;
+    let hex = Hexagon::new(qr_location.x, qr_location.y, "".to_string());
+    
     for i in 0..appliances.len() {
-      if offset > idx {
-        break;
-      }
-      if idx-offset == i {
+      if i == idx {
         continue;
       }
       
-      let (actual_buff, qr_location, range) = buff;
-      let hex = Hexagon::new(qr_location.x, qr_location.y, "".to_string());
       let qr = appliances[i].get_qr_location();
       let other_hex = Hexagon::new(qr.x, qr.y, "".to_string());
       
@@ -60,17 +44,44 @@ pub fn update_game(map: &mut Map, appliances: &mut Vec<Box<Appliance>>, foods: &
     }
   }
   
-  let mut dead_weapons = Vec::new();
-  let mut i = 0;
-  for weapon in &mut weapons.iter_mut() {
-    if weapon.update(delta_time) {
-      dead_weapons.push(i);
+  remove_worn_out_appliances(map, appliances, selected_appliance);
+  
+  // Homing weapons turn towards their food before moving
+  for weapon in weapons.iter_mut() {
+    weapon.steer(foods);
+  }
+  weapons.retain_mut(|weapon| !weapon.update(delta_time));
+}
+
+// Appliances with no life left are taken off the map. The selected
+// appliance keeps pointing at the same appliance, or nothing if it was removed
+pub fn remove_worn_out_appliances(map: &mut Map, appliances: &mut Vec<Box<Appliance>>, selected_appliance: &mut Option<usize>) {
+  let selected = *selected_appliance;
+  let mut idx = 0;
+  let mut removed_before_selected = 0;
+  
+  appliances.retain(|appliance| {
+    let worn_out = appliance.current_life_expectancy() <= 0;
+    if worn_out {
+      let qr = appliance.get_qr_location();
+      map.set_hexagon_type(qr.x, qr.y, HexagonType::Open);
+      
+      match selected {
+        Some(selected_idx) if selected_idx == idx => {
+          *selected_appliance = None;
+        },
+        Some(selected_idx) if idx < selected_idx => {
+          removed_before_selected += 1;
+        },
+        _ => {},
+      }
     }
     
-    i += 1;
-  }
+    idx += 1;
+    !worn_out
+  });
   
-  for i in 0..dead_weapons.len() {
-    weapons.remove(dead_weapons[i]-i);
+  if let Some(selected_idx) = selected_appliance {
+    *selected_idx -= removed_before_selected;
   }
 }
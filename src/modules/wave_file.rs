@@ -0,0 +1,179 @@
+use serde::{Serialize, Deserialize};
+
+use ron;
+
+use std::fmt;
+use std::error::Error;
+use std::fs;
+
+pub const WAVE_FILE_VERSION: u32 = 1;
+
+const WAVE_DIRECTORY: &str = "./resources/Waves/";
+
+#[derive(Debug)]
+pub enum WaveError {
+  MissingFile(String),
+  BadFormat(String),
+  UnsupportedVersion { found: u32, supported: u32 },
+  NoWaves,
+  EmptyWave { wave: usize },
+  InvalidGroup { wave: usize, group: usize, reason: String },
+}
+
+impl fmt::Display for WaveError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      WaveError::MissingFile(path) => {
+        write!(f, "Cant find wave file {}", path)
+      },
+      WaveError::BadFormat(message) => {
+        write!(f, "Invalid wave file: {}", message)
+      },
+      WaveError::UnsupportedVersion { found, supported } => {
+        write!(f, "Wave file version {} is newer than the supported version {}", found, supported)
+      },
+      WaveError::NoWaves => {
+        write!(f, "Wave file has no waves")
+      },
+      WaveError::EmptyWave { wave } => {
+        write!(f, "Wave {} has no food in it", wave)
+      },
+      WaveError::InvalidGroup { wave, group, reason } => {
+        write!(f, "Wave {}, group {}: {}", wave, group, reason)
+      },
+    }
+  }
+}
+
+impl Error for WaveError {}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum FoodKind {
+  Banana,
+  Strawberry,
+  Pineapple,
+  Cake,
+  Mushroom,
+  Jelly,
+}
+
+fn default_multiplier() -> f32 {
+  1.0
+}
+
+fn default_repeat() -> u32 {
+  1
+}
+
+// count foods spawned from start seconds into the wave, spacing seconds apart.
+// The whole group is spawned repeat times, each repeat_every seconds after the last
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SpawnGroup {
+  pub food: FoodKind,
+  pub count: u32,
+  pub start: f32,
+  #[serde(default)]
+  pub spacing: f32,
+  #[serde(default = "default_multiplier")]
+  pub health: f32,
+  #[serde(default = "default_multiplier")]
+  pub speed: f32,
+  #[serde(default = "default_repeat")]
+  pub repeat: u32,
+  #[serde(default)]
+  pub repeat_every: f32,
+}
+
+impl SpawnGroup {
+  pub fn spawn_times(&self) -> Vec<f32> {
+    let mut times = Vec::new();
+    for r in 0..self.repeat {
+      for i in 0..self.count {
+        times.push(self.start + r as f32*self.repeat_every + i as f32*self.spacing);
+      }
+    }
+    
+    times
+  }
+  
+  fn validate(&self) -> Result<(), String> {
+    if self.count == 0 {
+      return Err("count must be at least 1".to_string());
+    }
+    if self.repeat == 0 {
+      return Err("repeat must be at least 1".to_string());
+    }
+    if !(self.start >= 0.0) {
+      return Err("start can't be negative".to_string());
+    }
+    if !(self.spacing >= 0.0) {
+      return Err("spacing can't be negative".to_string());
+    }
+    if !(self.repeat_every >= 0.0) {
+      return Err("repeat_every can't be negative".to_string());
+    }
+    if !(self.health > 0.0) {
+      return Err("health multiplier must be above 0".to_string());
+    }
+    if !(self.speed > 0.0) {
+      return Err("speed multiplier must be above 0".to_string());
+    }
+    
+    Ok(())
+  }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WaveDefinition {
+  pub groups: Vec<SpawnGroup>,
+}
+
+// Wave sets live in resources/Waves and are picked by a map's wave_set
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WaveFile {
+  pub version: u32,
+  pub name: String,
+  pub waves: Vec<WaveDefinition>,
+}
+
+impl WaveFile {
+  pub fn load_set(wave_set: &str) -> Result<WaveFile, WaveError> {
+    WaveFile::load(&(WAVE_DIRECTORY.to_owned() + wave_set + ".ron"))
+  }
+  
+  pub fn load(path: &str) -> Result<WaveFile, WaveError> {
+    let text = match fs::read_to_string(path) {
+      Ok(text) => text,
+      Err(_) => return Err(WaveError::MissingFile(path.to_string())),
+    };
+    
+    let wave_file: WaveFile = ron::de::from_str(&text).map_err(|e| WaveError::BadFormat(e.to_string()))?;
+    wave_file.validate()?;
+    
+    Ok(wave_file)
+  }
+  
+  pub fn validate(&self) -> Result<(), WaveError> {
+    if self.version > WAVE_FILE_VERSION {
+      return Err(WaveError::UnsupportedVersion { found: self.version, supported: WAVE_FILE_VERSION });
+    }
+    
+    if self.waves.len() == 0 {
+      return Err(WaveError::NoWaves);
+    }
+    
+    for i in 0..self.waves.len() {
+      if self.waves[i].groups.len() == 0 {
+        return Err(WaveError::EmptyWave { wave: i+1 });
+      }
+      
+      for j in 0..self.waves[i].groups.len() {
+        if let Err(reason) = self.waves[i].groups[j].validate() {
+          return Err(WaveError::InvalidGroup { wave: i+1, group: j+1, reason });
+        }
+      }
+    }
+    
+    Ok(())
+  }
+}
@@ -2,36 +2,42 @@ pub use self::dish::Dish;
 pub use self::coldsnap::ColdSnap;
 pub use self::tenderizer::Tenderizer;
 pub use self::salt::Salt;
+pub use self::spoon::Spoon;
 
 mod dish;
 mod coldsnap;
 mod tenderizer;
 mod salt;
+mod spoon;
 
+#[cfg(feature = "graphics")]
 use maat_graphics::DrawCall;
 
-use crate::modules::food::Food;
+use crate::modules::food::{Food, FoodTarget};
 use crate::modules::hexagon::Layout;
 use crate::modules::hexagon::Hexagon;
 use crate::modules::map::Map;
 
-use cgmath::{Vector2, Vector3};
+use serde::{Serialize, Deserialize};
 
-#[derive(Clone, PartialEq)]
+use cgmath::{InnerSpace, Vector2, Vector3};
+
+// The timer is how many seconds the debuff lasts on a food
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Debuff {
   Slow(f32),
   Freeze(f32),
   Reverse(f32),
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum WeaponType {
   Tile,
   Projectile,
-  AntiFood,
+  AntiFood, // homes in on one food and can only hit that food
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct WeaponData {
   position: Vector3<f32>,
   tile_position: Vector2<i32>,
@@ -47,6 +53,11 @@ pub struct WeaponData {
   model: String,
   food_hit: Vec<i32>,
   timer: f32,
+  #[serde(default)]
+  target: Option<i32>,
+  // Where the target was last seen in the foods vec, checked by id before use
+  #[serde(default)]
+  target_index: usize,
 }
 
 impl WeaponData {
@@ -66,6 +77,30 @@ impl WeaponData {
       model,
       food_hit: Vec::new(),
       timer: timer,
+      target: None,
+      target_index: 0,
+    }
+  }
+}
+
+// Lets weapons still in flight be saved and loaded
+#[derive(Clone, Serialize, Deserialize)]
+pub enum SavedWeapon {
+  Dish(Dish),
+  ColdSnap(ColdSnap),
+  Tenderizer(Tenderizer),
+  Salt(Salt),
+  Spoon(Spoon),
+}
+
+impl SavedWeapon {
+  pub fn restore(self) -> Box<Weapon> {
+    match self {
+      SavedWeapon::Dish(weapon) => Box::new(weapon),
+      SavedWeapon::ColdSnap(weapon) => Box::new(weapon),
+      SavedWeapon::Tenderizer(weapon) => Box::new(weapon),
+      SavedWeapon::Salt(weapon) => Box::new(weapon),
+      SavedWeapon::Spoon(weapon) => Box::new(weapon),
     }
   }
 }
@@ -89,15 +124,28 @@ impl Clone for Box<Weapon> {
 pub trait Weapon: WeaponClone {
   fn data(&self) -> &WeaponData;
   fn mut_data(&mut self) -> &mut WeaponData;
+  fn save(&self) -> SavedWeapon;
   
   fn add_pierce(&mut self, extra_pierce: i32) {
     self.mut_data().pierce += extra_pierce;
   }
   
+  fn add_damage(&mut self, extra_damage: i32) {
+    self.mut_data().damage += extra_damage;
+  }
+  
+  fn add_debuff(&mut self, debuff: Debuff) {
+    self.mut_data().debuffs.push(debuff);
+  }
+  
   fn damage_multiplier(&mut self, percentage: f32) {
     self.mut_data().damage += (self.data().damage as f32*percentage).ceil() as i32;
   }
   
+  fn get_location(&self) -> Vector2<f32> {
+    self.data().position.xz()
+  }
+  
   fn get_hexagon(&self, map: &Map) -> Hexagon {
     map.pixel_to_hex(self.data().position.xz())
   }
@@ -115,11 +163,61 @@ pub trait Weapon: WeaponClone {
         self.mut_data().rotation = rotation;
       }
       WeaponType::AntiFood => {
-        
+        self.mut_data().position = position;
+        self.mut_data().rotation = rotation;
+        self.mut_data().direction = direction;
       },
     }
   }
   
+  fn lock_on(&mut self, food: &FoodTarget) {
+    self.mut_data().target = Some(food.id);
+    self.mut_data().target_index = food.index;
+  }
+  
+  // Turns homing weapons towards their food, if it has already been cooked
+  // or binned the weapon has nothing left to chase. Foods only get searched
+  // for when they have moved in the foods vec since last tick
+  fn steer(&mut self, foods: &Vec<Box<Food>>) {
+    if let WeaponType::AntiFood = self.data().weapon_type {
+      let target = match self.data().target {
+        Some(id) => id,
+        None => {
+          self.mut_data().pierce = 0;
+          return;
+        },
+      };
+      
+      let cached = self.data().target_index;
+      let index = if foods.get(cached).map(|food| food.get_id()) == Some(target) {
+        Some(cached)
+      } else {
+        foods.iter().position(|food| food.get_id() == target)
+      };
+      
+      match index {
+        Some(index) => {
+          self.mut_data().target_index = index;
+          let loc = foods[index].get_location();
+          let offset = Vector2::new(loc.x-self.data().position.x, loc.y-self.data().position.z);
+          if offset.magnitude2() > 0.0 {
+            self.mut_data().direction = offset.normalize();
+          }
+        },
+        None => {
+          self.mut_data().pierce = 0;
+        }
+      }
+    }
+  }
+  
+  fn can_hit(&self, id: i32) -> bool {
+    match self.data().weapon_type {
+      WeaponType::AntiFood => self.data().target == Some(id) && self.hasnt_hit(id),
+      _ => self.hasnt_hit(id),
+    }
+  }
+  
   fn update(&mut self, delta_time: f32) -> bool {
     match self.data().weapon_type {
       WeaponType::Projectile => {
@@ -136,7 +234,13 @@ pub trait Weapon: WeaponClone {
         }
       }
       WeaponType::AntiFood => {
-        
+        self.mut_data().position.x += self.data().velocity*self.data().direction.x*delta_time;
+        self.mut_data().position.z += self.data().velocity*self.data().direction.y*delta_time;
+        self.mut_data().rotation.z += self.data().rotation_velocity.z*delta_time;
+        self.mut_data().timer -= delta_time;
+        if self.mut_data().timer <= 0.0 {
+          self.mut_data().pierce = 0;
+        }
       },
     }
     
@@ -153,6 +257,7 @@ pub trait Weapon: WeaponClone {
   
   fn hit_target(&mut self, food: &mut Box<Food>);
   
+  #[cfg(feature = "graphics")]
   fn draw(&self, draw_calls: &mut Vec<DrawCall>) {
     let position = self.data().position;
     let rotation = self.data().rotation;
@@ -1,10 +1,12 @@
 
-use crate::modules::weapons::{Weapon, WeaponData, WeaponType};
+use crate::modules::weapons::{Weapon, WeaponData, SavedWeapon, WeaponType};
 use crate::modules::food::Food;
 
+use serde::{Serialize, Deserialize};
+
 use cgmath::Vector3;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Salt {
   data: WeaponData,
 }
@@ -33,6 +35,10 @@ impl Weapon for Salt {
     &mut self.data
   }
   
+  fn save(&self) -> SavedWeapon {
+    SavedWeapon::Salt(self.clone())
+  }
+  
   fn hit_target(&mut self, food: &mut Box<Food>) {
     food.apply_damage(self.data.damage);
     self.data.pierce -= 1;
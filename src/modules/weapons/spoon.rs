@@ -0,0 +1,47 @@
+use crate::modules::weapons::{Weapon, WeaponData, SavedWeapon, WeaponType};
+use crate::modules::food::Food;
+
+use serde::{Serialize, Deserialize};
+
+use cgmath::Vector3;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Spoon {
+  data: WeaponData,
+}
+
+impl Spoon {
+  pub fn new() -> Spoon {
+    let velocity = 70.0;
+    let rotation_velocity = Vector3::new(0.0, 0.0, 720.0);
+    let damage = 20;
+    let pierce = 1;
+    let timer = 3.0;
+    let scale = Vector3::new(1.0, 1.0, 1.0);
+    let debuff = Vec::new();
+    Spoon {
+      data: WeaponData::new(velocity, rotation_velocity, damage, pierce, timer, scale, WeaponType::AntiFood, debuff, "Spoon".to_string()),
+    }
+  }
+}
+
+impl Weapon for Spoon {
+  fn data(&self) -> &WeaponData {
+    &self.data
+  }
+  
+  fn mut_data(&mut self) -> &mut WeaponData {
+    &mut self.data
+  }
+  
+  fn save(&self) -> SavedWeapon {
+    SavedWeapon::Spoon(self.clone())
+  }
+  
+  fn hit_target(&mut self, food: &mut Box<Food>) {
+    food.apply_damage(self.data.damage);
+    food.apply_debuffs(self.data.debuffs.clone());
+    self.data.pierce -= 1;
+    self.data.food_hit.push(food.get_id());
+  }
+}
@@ -1,10 +1,12 @@
 
-use crate::modules::weapons::{Weapon, WeaponData, WeaponType, Debuff};
+use crate::modules::weapons::{Weapon, WeaponData, SavedWeapon, WeaponType, Debuff};
 use crate::modules::food::Food;
 
+use serde::{Serialize, Deserialize};
+
 use cgmath::Vector3;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Tenderizer {
   data: WeaponData,
 }
@@ -33,6 +35,10 @@ impl Weapon for Tenderizer {
     &mut self.data
   }
   
+  fn save(&self) -> SavedWeapon {
+    SavedWeapon::Tenderizer(self.clone())
+  }
+  
   fn hit_target(&mut self, food: &mut Box<Food>) {
     food.apply_damage(self.data.damage);
     food.apply_debuffs(self.data.debuffs.clone());
@@ -0,0 +1,34 @@
+extern crate delinquent_food;
+
+use delinquent_food::modules::map_file;
+
+use std::fs;
+use std::env;
+
+// Maps saved from the editor never overwrite each other
+
+fn empty_directory(name: &str) -> String {
+  let directory = env::temp_dir().join(name);
+  let _ = fs::remove_dir_all(&directory);
+  fs::create_dir_all(&directory).unwrap();
+  directory.to_str().unwrap().to_owned() + "/"
+}
+
+#[test]
+fn the_first_map_gets_the_plain_name() {
+  let directory = empty_directory("delinquent_food_first_map");
+  
+  assert!(map_file::numbered_map_names(&directory, "CustomMap").is_empty());
+  assert_eq!(map_file::unused_map_name(&directory, "CustomMap"), "CustomMap");
+}
+
+#[test]
+fn later_maps_are_numbered_after_the_ones_saved() {
+  let directory = empty_directory("delinquent_food_later_maps");
+  fs::write(directory.to_owned() + "CustomMap.ron", "").unwrap();
+  fs::write(directory.to_owned() + "CustomMap2.ron", "").unwrap();
+  fs::write(directory.to_owned() + "OtherMap.ron", "").unwrap();
+  
+  assert_eq!(map_file::numbered_map_names(&directory, "CustomMap"), vec!("CustomMap", "CustomMap2"));
+  assert_eq!(map_file::unused_map_name(&directory, "CustomMap"), "CustomMap3");
+}
@@ -0,0 +1,183 @@
+extern crate delinquent_food;
+extern crate cgmath;
+extern crate rand;
+
+use delinquent_food::modules::map::Map;
+use delinquent_food::modules::update::{update_game, remove_worn_out_appliances};
+use delinquent_food::modules::physics::collisions;
+use delinquent_food::modules::simulation::GameStats;
+use delinquent_food::modules::appliances::traits::{Appliance, ApplianceKind, Buff};
+use delinquent_food::modules::food::{Food, FoodIds, Pineapple, Strawberry};
+use delinquent_food::modules::weapons::{Weapon, Salt};
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use cgmath::{Vector2, Vector3};
+
+use std::rc::Rc;
+
+// Regression tests for appliances, weapons and foods being removed part way
+// through a tick
+
+fn test_map() -> Map {
+  Map::new_random_map(5, &mut StdRng::seed_from_u64(1))
+}
+
+fn appliance(kind: ApplianceKind, q: i32, r: i32, map: &Map) -> Box<Appliance> {
+  kind.create(Vector2::new(q, r), map)
+}
+
+fn worn_out(kind: ApplianceKind, q: i32, r: i32, map: &Map) -> Box<Appliance> {
+  let mut appliance = appliance(kind, q, r, map);
+  appliance.mut_data().life_expectancy = 0;
+  appliance
+}
+
+fn food_on_path(id: i32, map: &Map) -> Box<Food> {
+  let route = map.get_routes()[0].clone();
+  let position = map.tile_position_from_index(route[0] as usize);
+  let location = map.get_qr_from_index(route[0] as usize);
+  Box::new(Strawberry::new(id, position, Rc::new(route), location))
+}
+
+#[test]
+fn selection_follows_the_appliance_after_an_earlier_one_is_removed() {
+  let mut map = test_map();
+  let mut appliances = vec!(
+    worn_out(ApplianceKind::Dishwasher, -3, 0, &map),
+    appliance(ApplianceKind::Dishwasher, 0, 0, &map),
+    appliance(ApplianceKind::Fridge, 3, 0, &map),
+  );
+  let mut selected = Some(2);
+  
+  remove_worn_out_appliances(&mut map, &mut appliances, &mut selected);
+  
+  assert_eq!(appliances.len(), 2);
+  assert_eq!(selected, Some(1));
+  assert_eq!(appliances[1].get_qr_location(), Vector2::new(3, 0));
+}
+
+#[test]
+fn selection_is_cleared_when_the_selected_appliance_is_removed() {
+  let mut map = test_map();
+  let mut appliances = vec!(
+    appliance(ApplianceKind::Dishwasher, -3, 0, &map),
+    worn_out(ApplianceKind::Dishwasher, 0, 0, &map),
+    appliance(ApplianceKind::Fridge, 3, 0, &map),
+  );
+  let mut selected = Some(1);
+  
+  remove_worn_out_appliances(&mut map, &mut appliances, &mut selected);
+  
+  assert_eq!(appliances.len(), 2);
+  assert_eq!(selected, None);
+}
+
+#[test]
+fn selection_before_a_removed_appliance_is_untouched() {
+  let mut map = test_map();
+  let mut appliances = vec!(
+    appliance(ApplianceKind::Dishwasher, -3, 0, &map),
+    appliance(ApplianceKind::Dishwasher, 0, 0, &map),
+    worn_out(ApplianceKind::Fridge, 3, 0, &map),
+  );
+  let mut selected = Some(1);
+  
+  remove_worn_out_appliances(&mut map, &mut appliances, &mut selected);
+  
+  assert_eq!(appliances.len(), 2);
+  assert_eq!(selected, Some(1));
+  assert_eq!(appliances[1].get_qr_location(), Vector2::new(0, 0));
+}
+
+#[test]
+fn buffs_still_reach_neighbours_when_a_later_appliance_wears_out() {
+  let mut map = test_map();
+  let mut appliances = vec!(
+    appliance(ApplianceKind::CoffeeMachine, 0, 0, &map),
+    appliance(ApplianceKind::Dishwasher, 1, 0, &map),
+    worn_out(ApplianceKind::Fridge, -4, 0, &map),
+  );
+  let mut foods = Vec::new();
+  let mut weapons = Vec::new();
+  let mut selected = None;
+  let mut model_sizes = Vec::new();
+  
+  update_game(&mut map, &mut appliances, &mut foods, &mut weapons, &mut selected, &mut model_sizes, 0.01);
+  
+  assert_eq!(appliances.len(), 2);
+  assert!(appliances[1].data().buffs.contains(&Buff::Range));
+  assert!(!appliances[0].data().buffs.contains(&Buff::Range));
+}
+
+#[test]
+fn buffs_are_not_given_to_the_wrong_appliance_after_an_earlier_one_wears_out() {
+  let mut map = test_map();
+  let mut appliances = vec!(
+    worn_out(ApplianceKind::Fridge, -4, 4, &map),
+    appliance(ApplianceKind::CoffeeMachine, 0, 0, &map),
+    appliance(ApplianceKind::Dishwasher, -1, 0, &map),
+    appliance(ApplianceKind::Dishwasher, 5, -5, &map),
+  );
+  let mut foods = Vec::new();
+  let mut weapons = Vec::new();
+  let mut selected = None;
+  let mut model_sizes = Vec::new();
+  
+  update_game(&mut map, &mut appliances, &mut foods, &mut weapons, &mut selected, &mut model_sizes, 0.01);
+  
+  assert_eq!(appliances.len(), 3);
+  assert!(!appliances[0].data().buffs.contains(&Buff::Range), "coffee machine buffed itself");
+  assert!(appliances[1].data().buffs.contains(&Buff::Range), "neighbour missed its buff");
+  assert!(!appliances[2].data().buffs.contains(&Buff::Range), "out of range appliance was buffed");
+}
+
+#[test]
+fn broken_weapons_stop_hitting_and_are_removed() {
+  let map = test_map();
+  let mut foods = vec!(food_on_path(0, &map), food_on_path(1, &map));
+  let health = foods[0].get_health();
+  
+  let location = foods[0].get_location();
+  let mut weapon: Box<Weapon> = Box::new(Salt::new());
+  weapon.launch(Vector3::new(location.x, 0.0, location.y), foods[0].get_tile_location(), Vector3::new(0.0, 0.0, 0.0), Vector2::new(1.0, 0.0));
+  let mut weapons = vec!(weapon);
+  
+  let mut model_sizes = Vec::new();
+  let (mut bin, mut money) = (0, 0);
+  let mut stats = GameStats::default();
+  let mut food_ids = FoodIds::after(&foods);
+  
+  collisions(&map, &mut foods, &mut weapons, &mut model_sizes, &mut bin, &mut money, &mut stats, &mut food_ids, 0.01);
+  
+  assert_eq!(weapons.len(), 0);
+  let damaged = foods.iter().filter(|food| food.get_health() < health).count();
+  assert_eq!(damaged, 1);
+}
+
+#[test]
+fn cooked_foods_are_sold_and_their_children_get_new_ids() {
+  let map = test_map();
+  let route = map.get_routes()[0].clone();
+  let position = map.tile_position_from_index(route[0] as usize);
+  let location = map.get_qr_from_index(route[0] as usize);
+  
+  let mut pineapple: Box<Food> = Box::new(Pineapple::new(0, position, Rc::new(route), location));
+  pineapple.apply_damage(1000);
+  let mut foods = vec!(food_on_path(1, &map), pineapple, food_on_path(2, &map));
+  let mut weapons = Vec::new();
+  
+  let mut model_sizes = Vec::new();
+  let (mut bin, mut money) = (0, 0);
+  let mut stats = GameStats::default();
+  let mut food_ids = FoodIds::after(&foods);
+  
+  collisions(&map, &mut foods, &mut weapons, &mut model_sizes, &mut bin, &mut money, &mut stats, &mut food_ids, 0.01);
+  
+  assert_eq!(stats.foods_cooked, 1);
+  assert!(money > 0);
+  
+  let ids = foods.iter().map(|food| food.get_id()).collect::<Vec<i32>>();
+  assert_eq!(ids, vec!(1, 2, 3));
+}
@@ -0,0 +1,104 @@
+extern crate delinquent_food;
+extern crate rand;
+
+use delinquent_food::modules::map::Map;
+use delinquent_food::modules::simulation::Simulation;
+use delinquent_food::modules::wave_file::{WaveFile, WaveDefinition, SpawnGroup, FoodKind};
+use delinquent_food::modules::command::{GameCommand, CommandError};
+use delinquent_food::modules::replay::{Replay, ReplayPlayer};
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+// Watching replays back, including ones recorded before pausing had its own flag
+
+fn simulation() -> Simulation {
+  let wave = WaveDefinition {
+    groups: vec!(SpawnGroup {
+      food: FoodKind::Strawberry,
+      count: 1,
+      start: 1000.0,
+      spacing: 0.0,
+      health: 1.0,
+      speed: 1.0,
+      repeat: 1,
+      repeat_every: 0.0,
+    }),
+  };
+  let waves = WaveFile {
+    version: 1,
+    name: "test".to_string(),
+    waves: vec!(wave),
+  };
+  
+  let map = Map::new_random_map(5, &mut StdRng::seed_from_u64(1));
+  let mut simulation = Simulation::new(map, waves, 1);
+  simulation.skip_map_animation();
+  simulation
+}
+
+fn player(commands: Vec<(u64, GameCommand)>) -> ReplayPlayer {
+  let mut replay = Replay::new(&simulation());
+  for (tick, command) in commands {
+    replay.record(tick, command);
+  }
+  
+  ReplayPlayer::new(replay)
+}
+
+// Plays the commands due on tick the way the game screen does, returning
+// whether playback is paused afterwards
+fn play(player: &mut ReplayPlayer, simulation: &mut Simulation, tick: u64, paused: bool) -> bool {
+  for command in player.due_commands(tick) {
+    let _ = simulation.apply_command(&command, &mut Vec::new());
+  }
+  
+  player.take_pause_change().unwrap_or(paused)
+}
+
+#[test]
+fn an_old_pause_then_speed_change_resumes() {
+  let mut simulation = simulation();
+  let mut player = player(vec!((3, GameCommand::Speed(0)), (3, GameCommand::Speed(1))));
+  
+  assert!(!play(&mut player, &mut simulation, 3, false));
+  assert_eq!(player.commands_played(), 2);
+}
+
+#[test]
+fn an_old_pause_holds_until_the_next_speed_change() {
+  let mut simulation = simulation();
+  let mut player = player(vec!((3, GameCommand::Speed(0)), (8, GameCommand::Speed(4))));
+  
+  let paused = play(&mut player, &mut simulation, 3, false);
+  assert!(paused);
+  assert!(play(&mut player, &mut simulation, 5, paused));
+  assert!(!play(&mut player, &mut simulation, 8, paused));
+}
+
+#[test]
+fn other_commands_leave_the_viewer_pause_alone() {
+  let mut simulation = simulation();
+  let mut player = player(vec!((3, GameCommand::EmptyBin)));
+  
+  assert!(play(&mut player, &mut simulation, 3, true));
+  assert!(player.take_pause_change().is_none());
+}
+
+#[test]
+fn old_pauses_are_kept_from_the_simulation() {
+  let mut simulation = simulation();
+  let mut player = player(vec!((3, GameCommand::Speed(0)), (3, GameCommand::Speed(2))));
+  
+  let commands = player.due_commands(3);
+  assert_eq!(commands.len(), 1);
+  match commands[0] {
+    GameCommand::Speed(speed) => assert_eq!(speed, 2),
+    _ => panic!("expected the speed change to be played"),
+  }
+  
+  match simulation.apply_command(&GameCommand::Speed(0), &mut Vec::new()) {
+    Err(CommandError::InvalidSpeed(0)) => {},
+    _ => panic!("a new game can't be given speed 0"),
+  }
+}
@@ -0,0 +1,176 @@
+extern crate delinquent_food;
+
+use delinquent_food::modules::map::{Map, MapInfo, MapError};
+use delinquent_food::modules::map_file::{TileKind, RouteSelection};
+use delinquent_food::modules::thefoodstore::FoodStore;
+use delinquent_food::modules::wave_file::{WaveFile, WaveDefinition, SpawnGroup, FoodKind};
+use delinquent_food::modules::food::FoodIds;
+
+use std::time::{Duration, Instant};
+
+// Route finding on wide open boards, where every tile is path
+
+fn open_board(radius: i32, starts: &[(i32, i32)], ends: &[(i32, i32)]) -> Map {
+  open_board_with(radius, starts, ends, RouteSelection::RoundRobin)
+}
+
+fn open_board_with(radius: i32, starts: &[(i32, i32)], ends: &[(i32, i32)], route_selection: RouteSelection) -> Map {
+  let mut info = MapInfo::new("test".to_string());
+  info.route_selection = route_selection;
+  let mut map = Map::new_empty(radius, info);
+  for q in -radius..=radius {
+    for r in -radius..=radius {
+      map.set_tile(q, r, &TileKind::Path);
+    }
+  }
+  for &(q, r) in starts {
+    map.set_tile(q, r, &TileKind::Start);
+  }
+  for &(q, r) in ends {
+    map.set_tile(q, r, &TileKind::End);
+  }
+  map
+}
+
+fn starts_on_routes(map: &Map, starts: &[(i32, i32)]) -> Vec<bool> {
+  starts.iter().map(|&(q, r)| {
+    let idx = map.get_index_from_qr(q, r).unwrap() as u32;
+    map.get_routes().iter().any(|route| route[0] == idx)
+  }).collect()
+}
+
+#[test]
+fn every_start_gets_routes_on_an_open_board() {
+  for radius in 3..=5 {
+    let starts = [(-radius, 0), (0, -radius)];
+    let mut map = open_board(radius, &starts, &[(radius, 0)]);
+    
+    assert!(map.recalculate_routes().is_ok(), "radius {}", radius);
+    assert_eq!(starts_on_routes(&map, &starts), vec!(true, true), "radius {}", radius);
+  }
+}
+
+#[test]
+fn large_open_boards_are_quick() {
+  let started = Instant::now();
+  let mut map = open_board(9, &[(-9, 0)], &[(9, 0)]);
+  
+  assert!(map.recalculate_routes().is_ok());
+  assert!(started.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn routes_go_from_a_start_to_an_end() {
+  let mut map = open_board(4, &[(-4, 0)], &[(4, 0)]);
+  map.recalculate_routes().unwrap();
+  
+  let start = map.get_index_from_qr(-4, 0).unwrap() as u32;
+  let end = map.get_index_from_qr(4, 0).unwrap() as u32;
+  assert!(map.get_routes().len() > 1);
+  for route in map.get_routes() {
+    assert_eq!(route[0], start);
+    assert_eq!(route[route.len()-1], end);
+  }
+}
+
+#[test]
+fn a_walled_off_start_is_reported() {
+  let mut map = open_board(3, &[(-3, 0), (0, -3)], &[(3, 0)]);
+  // Close off every neighbour of the second start
+  for &(q, r) in &[(1, -3), (1, -4), (0, -4), (-1, -3), (-1, -2), (0, -2)] {
+    map.set_tile(q, r, &TileKind::Blocked);
+  }
+  
+  match map.recalculate_routes() {
+    Err(MapError::InvalidTile { q, r, .. }) => assert_eq!((q, r), (0, -3)),
+    _ => panic!("expected the walled off start to be reported"),
+  }
+}
+
+#[test]
+fn checking_a_map_builds_no_routes() {
+  let mut map = open_board(5, &[(-5, 0)], &[(5, 0)]);
+  map.recalculate_routes().unwrap();
+  map.set_tile(0, 0, &TileKind::Blocked);
+  
+  assert!(map.check_routes().is_ok());
+  assert!(!map.get_routes().is_empty());
+  
+  match map.recalculate_routes() {
+    Ok(routes) => assert_eq!(routes, map.get_routes().len()),
+    Err(e) => panic!("{}", e),
+  }
+}
+
+#[test]
+fn checking_reports_a_walled_off_end() {
+  let mut map = open_board(3, &[(-3, 0)], &[(3, 0), (0, 3)]);
+  for &(q, r) in &[(1, 3), (1, 2), (0, 2), (-1, 3), (-1, 4), (0, 4)] {
+    map.set_tile(q, r, &TileKind::Blocked);
+  }
+  
+  match map.check_routes() {
+    Err(MapError::InvalidTile { q, r, .. }) => assert_eq!((q, r), (0, 3)),
+    _ => panic!("expected the walled off end to be reported"),
+  }
+}
+
+// Eight waves of five strawberries
+fn waves() -> WaveFile {
+  let wave = WaveDefinition {
+    groups: vec!(SpawnGroup {
+      food: FoodKind::Strawberry,
+      count: 5,
+      start: 0.0,
+      spacing: 1.0,
+      health: 1.0,
+      speed: 1.0,
+      repeat: 1,
+      repeat_every: 0.0,
+    }),
+  };
+  
+  WaveFile {
+    version: 1,
+    name: "test".to_string(),
+    waves: vec!(wave; 8),
+  }
+}
+
+// Where every food in the waves comes out
+fn spawn_locations(map: &Map) -> Vec<(i32, i32)> {
+  let mut store = FoodStore::new(map, &waves(), 1);
+  let mut ids = FoodIds::new();
+  let mut locations = Vec::new();
+  
+  while !store.is_finished() {
+    if let Some(food) = store.update(1.0, &mut ids) {
+      let tile = food.get_tile_location();
+      locations.push((tile.x, tile.y));
+    }
+    store.next_wave();
+  }
+  
+  locations
+}
+
+#[test]
+fn starts_share_the_food_evenly_whatever_the_route_selection() {
+  let starts = [(-4, 0), (0, -4)];
+  for selection in vec!(RouteSelection::Random, RouteSelection::RoundRobin, RouteSelection::PerWave) {
+    // Plenty of detours off each start, which mustn't count for extra food
+    let mut map = open_board_with(4, &starts, &[(4, 0)], selection.clone());
+    map.recalculate_routes().unwrap();
+    
+    let locations = spawn_locations(&map);
+    assert_eq!(locations.len(), 40);
+    for start in &starts {
+      let count = locations.iter().filter(|location| *location == start).count();
+      if selection == RouteSelection::Random {
+        assert!(count >= 10, "{:?} sent {} of 40 from {:?}", selection, count, start);
+      } else {
+        assert_eq!(count, 20, "{:?} sent {} of 40 from {:?}", selection, count, start);
+      }
+    }
+  }
+}
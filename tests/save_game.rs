@@ -0,0 +1,86 @@
+extern crate delinquent_food;
+extern crate rand;
+extern crate ron;
+
+use delinquent_food::modules::map::Map;
+use delinquent_food::modules::simulation::Simulation;
+use delinquent_food::modules::wave_file::{WaveFile, WaveDefinition, SpawnGroup, FoodKind};
+use delinquent_food::modules::save_game::SaveGame;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+// Games written to a save file and carried on from it
+
+fn simulation() -> Simulation {
+  let wave = WaveDefinition {
+    groups: vec!(SpawnGroup {
+      food: FoodKind::Pineapple,
+      count: 12,
+      start: 0.0,
+      spacing: 0.2,
+      health: 1.0,
+      speed: 1.0,
+      repeat: 1,
+      repeat_every: 0.0,
+    }),
+  };
+  let waves = WaveFile {
+    version: 1,
+    name: "test".to_string(),
+    waves: vec!(wave),
+  };
+  
+  let map = Map::new_random_map(5, &mut StdRng::seed_from_u64(3));
+  let mut simulation = Simulation::new(map, waves, 3);
+  simulation.skip_map_animation();
+  simulation
+}
+
+fn step(simulation: &mut Simulation, ticks: usize) {
+  for _ in 0..ticks {
+    simulation.step(&mut None, &mut Vec::new());
+  }
+}
+
+fn food_positions(simulation: &Simulation) -> Vec<(i32, u32, (i32, i32))> {
+  simulation.foods.iter().map(|food| {
+    let tile = food.get_tile_location();
+    (food.get_id(), food.get_path_num(), (tile.x, tile.y))
+  }).collect()
+}
+
+fn save_and_load(simulation: &Simulation) -> (String, Simulation) {
+  let text = ron::ser::to_string(&SaveGame::new(simulation, 1, None)).unwrap();
+  let save: SaveGame = ron::de::from_str(&text).unwrap();
+  
+  let mut loaded = save.into_simulation().unwrap();
+  loaded.skip_map_animation();
+  (text, loaded)
+}
+
+#[test]
+fn foods_keep_their_route_index_instead_of_the_path() {
+  let mut simulation = simulation();
+  step(&mut simulation, 120);
+  assert!(!simulation.foods.is_empty());
+  
+  let (text, _) = save_and_load(&simulation);
+  
+  assert!(!text.contains("path:"));
+  assert!(text.contains("route:Some("));
+}
+
+#[test]
+fn loaded_foods_carry_on_along_the_same_route() {
+  let mut simulation = simulation();
+  step(&mut simulation, 120);
+  
+  let (_, mut loaded) = save_and_load(&simulation);
+  assert_eq!(food_positions(&loaded), food_positions(&simulation));
+  
+  step(&mut simulation, 300);
+  step(&mut loaded, 300);
+  assert!(!simulation.foods.is_empty());
+  assert_eq!(food_positions(&loaded), food_positions(&simulation));
+}
@@ -0,0 +1,110 @@
+extern crate delinquent_food;
+
+use delinquent_food::modules::settings::{self, Settings, KeyBindings, KeyAction, SETTINGS_FILE_VERSION};
+
+// Settings files written by older versions have to keep loading
+
+#[test]
+fn missing_fields_fall_back_to_defaults() {
+  let (settings, migrated) = Settings::parse("(fullscreen: true, dpi: 1.5)").unwrap();
+  
+  assert!(migrated);
+  assert_eq!(settings.version, SETTINGS_FILE_VERSION);
+  assert!(settings.fullscreen);
+  assert_eq!(settings.dpi, 1.5);
+  assert_eq!(settings.resolution, Settings::default().resolution);
+  assert_eq!(settings.key_bindings, Settings::default().key_bindings);
+}
+
+#[test]
+fn current_file_is_not_migrated() {
+  let text = Settings::default().to_ron().unwrap();
+  let (settings, migrated) = Settings::parse(&text).unwrap();
+  
+  assert!(!migrated);
+  assert_eq!(settings, Settings::default());
+}
+
+#[test]
+fn dropped_fields_are_ignored() {
+  let (settings, _) = Settings::parse("(version: 1, ui_scale: 2.0, dpi: 1.5)").unwrap();
+  
+  assert_eq!(settings.dpi, 1.5);
+}
+
+#[test]
+fn unbound_actions_get_their_default_key() {
+  let text = "(version: 1, key_bindings: (keys: { CameraForward: \"Z\" }))";
+  let (settings, _) = Settings::parse(text).unwrap();
+  
+  assert_eq!(settings.key_bindings.key(KeyAction::CameraForward), "Z");
+  assert_eq!(settings.key_bindings.key(KeyAction::Sell), "X");
+  assert_eq!(settings.key_bindings.keys.len(), KeyAction::all().len());
+}
+
+#[test]
+fn unusable_values_are_replaced() {
+  let (settings, _) = Settings::parse("(version: 1, default_game_speed: 3, dpi: 0.0, resolution: (x: -1, y: 720))").unwrap();
+  let defaults = Settings::default();
+  
+  assert_eq!(settings.default_game_speed, defaults.default_game_speed);
+  assert_eq!(settings.dpi, defaults.dpi);
+  assert_eq!(settings.resolution, defaults.resolution);
+}
+
+#[test]
+fn unbindable_keys_fall_back_to_the_default() {
+  let text = "(version: 1, key_bindings: (keys: { Sell: \"Escape\" }))";
+  let (settings, _) = Settings::parse(text).unwrap();
+  
+  assert_eq!(settings.key_bindings.key(KeyAction::Sell), KeyAction::Sell.default_key());
+}
+
+#[test]
+fn default_bindings_have_no_conflicts() {
+  assert!(KeyBindings::new().conflicts().is_empty());
+}
+
+#[test]
+fn keys_bound_twice_are_reported() {
+  let mut bindings = KeyBindings::new();
+  bindings.set(KeyAction::CameraForward, "Z".to_string());
+  bindings.set(KeyAction::CameraLeft, "Q".to_string());
+  bindings.set(KeyAction::Sell, "Q".to_string());
+  
+  let conflicts = bindings.conflicts();
+  assert_eq!(conflicts, vec!(("Q".to_string(), vec!(KeyAction::CameraLeft, KeyAction::Sell))));
+}
+
+#[test]
+fn typed_keys_map_to_binding_names() {
+  assert_eq!(settings::binding_name("z"), Some("Z".to_string()));
+  assert_eq!(settings::binding_name(" "), Some("Space".to_string()));
+  assert_eq!(settings::binding_name("7"), Some("7".to_string()));
+  assert_eq!(settings::binding_name("Backspace"), None);
+}
+
+#[test]
+fn editor_brushes_can_share_keys_with_the_game() {
+  let bindings = KeyBindings::new();
+  
+  assert_eq!(bindings.key(KeyAction::OpenBrush), bindings.key(KeyAction::PlaceDishwasher));
+  assert!(bindings.conflicts().is_empty());
+}
+
+#[test]
+fn editor_brushes_cant_share_keys_with_the_camera() {
+  let mut bindings = KeyBindings::new();
+  bindings.set(KeyAction::PathBrush, "W".to_string());
+  
+  let conflicts = bindings.conflicts();
+  assert_eq!(conflicts, vec!(("W".to_string(), vec!(KeyAction::CameraForward, KeyAction::PathBrush))));
+}
+
+#[test]
+fn files_from_before_the_editor_brushes_get_their_default_keys() {
+  let text = "(version: 1, key_bindings: (keys: { Sell: \"Q\" }))";
+  let (settings, _) = Settings::parse(text).unwrap();
+  
+  assert_eq!(settings.key_bindings.key(KeyAction::EndBrush), "5");
+}
@@ -0,0 +1,137 @@
+extern crate delinquent_food;
+extern crate cgmath;
+extern crate rand;
+
+use delinquent_food::modules::map::Map;
+use delinquent_food::modules::update::update_game;
+use delinquent_food::modules::physics::collisions;
+use delinquent_food::modules::simulation::GameStats;
+use delinquent_food::modules::appliances::traits::ApplianceKind;
+use delinquent_food::modules::food::{Food, FoodIds, Strawberry};
+use delinquent_food::modules::weapons::{Weapon, Spoon};
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use cgmath::{InnerSpace, Vector2, Vector3};
+
+use std::rc::Rc;
+
+// Spoons thrown by the cutlery drawer home in on the one food they were thrown at
+
+fn test_map() -> Map {
+  Map::new_random_map(5, &mut StdRng::seed_from_u64(1))
+}
+
+fn food_on_path(id: i32, map: &Map) -> Box<Food> {
+  let route = map.get_routes()[0].clone();
+  let position = map.tile_position_from_index(route[0] as usize);
+  let location = map.get_qr_from_index(route[0] as usize);
+  Box::new(Strawberry::new(id, position, Rc::new(route), location))
+}
+
+// A spoon a little way from the food, thrown straight away from it
+fn spoon_thrown_away_from(foods: &Vec<Box<Food>>, index: usize) -> Box<Weapon> {
+  let location = foods[index].get_location();
+  let mut spoon: Box<Weapon> = Box::new(Spoon::new());
+  spoon.lock_on(&foods[index].as_target(index));
+  spoon.launch(Vector3::new(location.x+10.0, 0.0, location.y), Vector2::new(0, 0), Vector3::new(0.0, 0.0, 0.0), Vector2::new(1.0, 0.0));
+  spoon
+}
+
+fn distance(weapon: &Box<Weapon>, food: &Box<Food>) -> f32 {
+  (weapon.get_location() - food.get_location()).magnitude()
+}
+
+fn run_collisions(map: &Map, foods: &mut Vec<Box<Food>>, weapons: &mut Vec<Box<Weapon>>) {
+  let (mut bin, mut money) = (0, 0);
+  let mut stats = GameStats::default();
+  let mut food_ids = FoodIds::after(foods);
+  collisions(map, foods, weapons, &mut Vec::new(), &mut bin, &mut money, &mut stats, &mut food_ids, 0.01);
+}
+
+#[test]
+fn the_cutlery_drawer_locks_a_spoon_on_to_one_food() {
+  let mut map = test_map();
+  let start = map.get_routes()[0][0] as usize;
+  let mut appliances = vec!(ApplianceKind::CutleryDrawer.create(map.get_qr_from_index(start), &map));
+  let mut foods = vec!(food_on_path(0, &map), food_on_path(1, &map));
+  let mut weapons = Vec::new();
+  
+  for _ in 0..500 {
+    update_game(&mut map, &mut appliances, &mut foods, &mut weapons, &mut None, &mut Vec::new(), 0.01);
+    if !weapons.is_empty() {
+      break;
+    }
+  }
+  
+  assert_eq!(weapons.len(), 1, "the drawer never threw a spoon");
+  assert!(weapons[0].can_hit(0) != weapons[0].can_hit(1));
+}
+
+#[test]
+fn a_thrown_spoon_turns_towards_its_moving_food() {
+  let mut map = test_map();
+  let mut foods = vec!(food_on_path(0, &map));
+  let mut weapons = vec!(spoon_thrown_away_from(&foods, 0));
+  let mut appliances = Vec::new();
+  
+  for _ in 0..10 {
+    let before = distance(&weapons[0], &foods[0]);
+    update_game(&mut map, &mut appliances, &mut foods, &mut weapons, &mut None, &mut Vec::new(), 0.01);
+    
+    assert!(distance(&weapons[0], &foods[0]) < before, "spoon didn't close in on the food");
+  }
+}
+
+#[test]
+fn a_spoon_still_finds_its_food_after_the_foods_move_around() {
+  let map = test_map();
+  let mut foods = vec!(food_on_path(0, &map));
+  let mut spoon = spoon_thrown_away_from(&foods, 0);
+  
+  // Another food now sits where the target was, which is somewhere else entirely
+  let mut decoy = food_on_path(1, &map);
+  let route_end = *map.get_routes()[0].last().unwrap() as usize;
+  decoy.set_route(Rc::new(vec!(route_end as u32)), map.tile_position_from_index(route_end), map.get_qr_from_index(route_end));
+  foods.insert(0, decoy);
+  
+  let from = spoon.get_location();
+  spoon.steer(&foods);
+  spoon.update(0.01);
+  
+  let heading = (spoon.get_location() - from).normalize();
+  let to_target = (foods[1].get_location() - from).normalize();
+  assert!(heading.dot(to_target) > 0.99, "spoon went after the wrong food");
+}
+
+#[test]
+fn spoons_only_hit_the_food_they_are_locked_on_to() {
+  let map = test_map();
+  let mut foods = vec!(food_on_path(0, &map), food_on_path(1, &map));
+  let health = foods[0].get_health();
+  
+  let location = foods[1].get_location();
+  let mut spoon: Box<Weapon> = Box::new(Spoon::new());
+  spoon.lock_on(&foods[1].as_target(1));
+  spoon.launch(Vector3::new(location.x, 0.0, location.y), Vector2::new(0, 0), Vector3::new(0.0, 0.0, 0.0), Vector2::new(1.0, 0.0));
+  let mut weapons = vec!(spoon);
+  
+  run_collisions(&map, &mut foods, &mut weapons);
+  
+  assert_eq!(foods[0].get_health(), health);
+  assert!(foods[1].get_health() < health);
+  assert!(weapons.is_empty());
+}
+
+#[test]
+fn a_spoon_breaks_once_its_food_is_gone() {
+  let map = test_map();
+  let mut foods = vec!(food_on_path(0, &map), food_on_path(1, &map));
+  let mut spoon = spoon_thrown_away_from(&foods, 1);
+  
+  foods.remove(1);
+  spoon.steer(&foods);
+  
+  assert!(spoon.is_broken());
+}
@@ -0,0 +1,110 @@
+extern crate delinquent_food;
+extern crate cgmath;
+extern crate rand;
+
+use delinquent_food::modules::map::{Map, MapInfo};
+use delinquent_food::modules::hexagon::Hexagon;
+use delinquent_food::modules::food_index::FoodIndex;
+use delinquent_food::modules::appliances::traits::{Appliance, ApplianceKind, TargetPriority};
+use delinquent_food::modules::food::{Food, Strawberry};
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use cgmath::Vector2;
+
+use std::rc::Rc;
+
+// Appliances picking which food to aim at out of the ones in range
+
+const PRIORITIES: [TargetPriority; 6] = [TargetPriority::First, TargetPriority::Last, TargetPriority::Close,
+                                         TargetPriority::Far, TargetPriority::Strong, TargetPriority::Weak];
+
+fn food_at(id: i32, q: i32, r: i32, health: f32, map: &Map) -> Box<Food> {
+  let position = map.get_tile_position(q, r);
+  let mut food: Box<Food> = Box::new(Strawberry::new(id, position, Rc::new(vec!(0)), Vector2::new(q, r)));
+  food.scale_stats(health, 1.0);
+  food
+}
+
+// Targeting as it was before the food index, every food checked in order
+fn checked_in_order(appliance: &Box<Appliance>, foods: &Vec<Box<Food>>, map: &Map) -> Option<usize> {
+  let centre = Hexagon::new(appliance.get_qr_location().x, appliance.get_qr_location().y, "".to_string());
+  let range = appliance.get_range() as i32;
+  
+  let mut in_range = Vec::new();
+  for (idx, food) in foods.iter().enumerate() {
+    let hex = map.pixel_to_hex(food.get_location());
+    let dist = Hexagon::hex_distance(&centre, &hex);
+    if dist > range || (appliance.data().directional_range && range > 1 && !Hexagon::is_on_same_axis(&centre, &hex)) {
+      continue;
+    }
+    in_range.push((idx, dist, food.get_health()));
+  }
+  
+  if in_range.is_empty() {
+    return None;
+  }
+  
+  let mut pick = 0;
+  match appliance.data().target {
+    TargetPriority::First => {},
+    TargetPriority::Last => pick = in_range.len()-1,
+    TargetPriority::Close => for i in 1..in_range.len() {
+      if in_range[i].1 < in_range[pick].1 { pick = i; }
+    },
+    TargetPriority::Far => for i in 1..in_range.len() {
+      if in_range[i].1 > in_range[pick].1 { pick = i; }
+    },
+    TargetPriority::Strong => for i in (1..in_range.len()).rev() {
+      if in_range[i].2 > in_range[pick].2 { pick = i; }
+    },
+    TargetPriority::Weak => for i in (1..in_range.len()).rev() {
+      if in_range[i].2 < in_range[pick].2 { pick = i; }
+    },
+  }
+  
+  Some(in_range[pick].0)
+}
+
+#[test]
+fn foods_in_range_have_their_hex_distance() {
+  let map = Map::new_empty(5, MapInfo::new("test".to_string()));
+  let foods = vec!(food_at(0, 0, 0, 1.0, &map), food_at(1, 2, -1, 1.0, &map), food_at(2, -3, 3, 1.0, &map), food_at(3, 4, 0, 1.0, &map));
+  let index = FoodIndex::new(&foods, &map);
+  
+  let mut in_range: Vec<(usize, i32)> = index.foods_in_range(Vector2::new(0, 0), 3, false).collect();
+  in_range.sort();
+  assert_eq!(in_range, vec!((0, 0), (1, 2), (2, 3)));
+  
+  let mut on_axis: Vec<(usize, i32)> = index.foods_in_range(Vector2::new(0, 0), 3, true).collect();
+  on_axis.sort();
+  assert_eq!(on_axis, vec!((0, 0), (2, 3)));
+}
+
+#[test]
+fn every_priority_picks_the_same_food_as_checking_in_order() {
+  let map = Map::new_empty(6, MapInfo::new("test".to_string()));
+  let mut rng = StdRng::seed_from_u64(7);
+  
+  for _ in 0..200 {
+    let count = rng.gen_range(1, 12);
+    let foods: Vec<Box<Food>> = (0..count).map(|id| {
+      let (q, r) = (rng.gen_range(-3, 4), rng.gen_range(-3, 4));
+      let health = [1.0, 2.0, 3.0][rng.gen_range(0, 3)];
+      food_at(id, q, r, health, &map)
+    }).collect();
+    let index = FoodIndex::new(&foods, &map);
+    
+    for &(kind, range) in &[(ApplianceKind::Dishwasher, 3), (ApplianceKind::MeatTenderizer, 3)] {
+      let mut appliance = kind.create(Vector2::new(0, 0), &map);
+      appliance.mut_data().range = range;
+      
+      for priority in PRIORITIES.iter() {
+        appliance.set_targeting(priority.clone());
+        let picked = appliance.get_prioritised_food(&foods, &index).map(|target| target.index);
+        assert_eq!(picked, checked_in_order(&appliance, &foods, &map));
+      }
+    }
+  }
+}
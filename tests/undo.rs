@@ -0,0 +1,258 @@
+extern crate delinquent_food;
+extern crate cgmath;
+extern crate rand;
+
+use delinquent_food::modules::map::Map;
+use delinquent_food::modules::hexagon::HexagonType;
+use delinquent_food::modules::simulation::{Simulation, DELTA_STEP};
+use delinquent_food::modules::wave_file::{WaveFile, WaveDefinition, SpawnGroup, FoodKind};
+use delinquent_food::modules::command::{GameCommand, CommandOutcome, CommandError};
+use delinquent_food::modules::appliances::traits::ApplianceKind;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use cgmath::Vector2;
+
+// Undoing place, sell, move, clean and bin empty commands
+
+fn waves() -> WaveFile {
+  // Nothing spawns for long enough that the board stays empty
+  let wave = WaveDefinition {
+    groups: vec!(SpawnGroup {
+      food: FoodKind::Strawberry,
+      count: 1,
+      start: 1000.0,
+      spacing: 0.0,
+      health: 1.0,
+      speed: 1.0,
+      repeat: 1,
+      repeat_every: 0.0,
+    }),
+  };
+  
+  WaveFile {
+    version: 1,
+    name: "test".to_string(),
+    waves: vec!(wave.clone(), wave),
+  }
+}
+
+fn simulation() -> Simulation {
+  let map = Map::new_random_map(5, &mut StdRng::seed_from_u64(1));
+  let mut simulation = Simulation::new(map, waves(), 1);
+  simulation.skip_map_animation();
+  simulation.money = 10000;
+  simulation
+}
+
+// Two open hexes next to each other
+fn open_hexes(simulation: &Simulation) -> (Vector2<i32>, Vector2<i32>) {
+  for q in -5..=5 {
+    for r in -5..=5 {
+      let open = |q, r| simulation.map.get_hex_from_qr(q, r).map(|hex| hex.is_open()).unwrap_or(false);
+      if open(q, r) && open(q+1, r) {
+        return (Vector2::new(q, r), Vector2::new(q+1, r));
+      }
+    }
+  }
+  
+  panic!("No open hexes on the test map");
+}
+
+fn is_open(simulation: &Simulation, qr: Vector2<i32>) -> bool {
+  simulation.map.get_hex_from_qr(qr.x, qr.y).unwrap().is_open()
+}
+
+fn run(simulation: &mut Simulation, command: GameCommand) -> Result<CommandOutcome, CommandError> {
+  simulation.apply_command(&command, &mut Vec::new())
+}
+
+fn place(simulation: &mut Simulation, qr: Vector2<i32>) {
+  run(simulation, GameCommand::Place { kind: ApplianceKind::Dishwasher, q: qr.x, r: qr.y }).unwrap();
+}
+
+fn step(simulation: &mut Simulation, ticks: usize) {
+  for _ in 0..ticks {
+    simulation.step(&mut None, &mut Vec::new());
+  }
+}
+
+#[test]
+fn undoing_a_placement_refunds_it_in_full() {
+  let mut simulation = simulation();
+  let (a, _) = open_hexes(&simulation);
+  
+  place(&mut simulation, a);
+  step(&mut simulation, 10);
+  
+  assert!(run(&mut simulation, GameCommand::Undo).is_ok());
+  assert_eq!(simulation.money, 10000);
+  assert_eq!(simulation.appliances.len(), 0);
+  assert_eq!(simulation.stats.appliances_bought, 0);
+  assert!(is_open(&simulation, a));
+}
+
+#[test]
+fn undoing_a_sale_puts_the_appliance_back() {
+  let mut simulation = simulation();
+  let (a, _) = open_hexes(&simulation);
+  
+  place(&mut simulation, a);
+  let money = simulation.money;
+  run(&mut simulation, GameCommand::Sell { appliance: 0 }).unwrap();
+  
+  assert!(run(&mut simulation, GameCommand::Undo).is_ok());
+  assert_eq!(simulation.money, money);
+  assert_eq!(simulation.appliance_at(a.x, a.y), Some(0));
+  assert!(!is_open(&simulation, a));
+}
+
+#[test]
+fn undoing_a_move_gives_back_the_life_it_cost() {
+  let mut simulation = simulation();
+  let (a, b) = open_hexes(&simulation);
+  
+  place(&mut simulation, a);
+  let life = simulation.appliances[0].current_life_expectancy();
+  run(&mut simulation, GameCommand::Move { appliance: 0, q: b.x, r: b.y }).unwrap();
+  
+  assert!(run(&mut simulation, GameCommand::Undo).is_ok());
+  assert_eq!(simulation.appliance_at(a.x, a.y), Some(0));
+  assert_eq!(simulation.appliances[0].current_life_expectancy(), life);
+  assert!(is_open(&simulation, b));
+}
+
+#[test]
+fn undoing_a_clean_refunds_it() {
+  let mut simulation = simulation();
+  let (a, _) = open_hexes(&simulation);
+  
+  place(&mut simulation, a);
+  simulation.appliances[0].decrease_life_expectancy();
+  let money = simulation.money;
+  run(&mut simulation, GameCommand::Clean { appliance: 0 }).unwrap();
+  
+  assert!(run(&mut simulation, GameCommand::Undo).is_ok());
+  assert_eq!(simulation.money, money);
+  assert_eq!(simulation.appliances[0].current_life_expectancy(), simulation.appliances[0].max_life_expectancy()-1);
+}
+
+#[test]
+fn undoing_a_bin_empty_puts_the_food_back() {
+  let mut simulation = simulation();
+  simulation.bin = 30;
+  
+  run(&mut simulation, GameCommand::EmptyBin).unwrap();
+  assert!(run(&mut simulation, GameCommand::Undo).is_ok());
+  assert_eq!(simulation.bin, 30);
+  assert_eq!(simulation.money, 10000);
+}
+
+#[test]
+fn a_bin_empty_cant_be_undone_if_the_bin_would_overflow() {
+  let mut simulation = simulation();
+  simulation.bin = 60;
+  
+  run(&mut simulation, GameCommand::EmptyBin).unwrap();
+  simulation.bin = 60;
+  
+  assert_eq!(run(&mut simulation, GameCommand::Undo).err(), Some(CommandError::UndoWouldFillBin));
+  assert_eq!(simulation.bin, 60);
+}
+
+#[test]
+fn undo_takes_back_the_latest_command_first() {
+  let mut simulation = simulation();
+  let (a, b) = open_hexes(&simulation);
+  
+  place(&mut simulation, a);
+  place(&mut simulation, b);
+  
+  assert!(run(&mut simulation, GameCommand::Undo).is_ok());
+  assert_eq!(simulation.appliances.len(), 1);
+  assert_eq!(simulation.appliance_at(a.x, a.y), Some(0));
+}
+
+#[test]
+fn undo_runs_out_after_the_window() {
+  let mut simulation = simulation();
+  simulation.undo_window = 0.5;
+  let (a, _) = open_hexes(&simulation);
+  
+  place(&mut simulation, a);
+  step(&mut simulation, (0.5/DELTA_STEP) as usize + 2);
+  
+  assert_eq!(run(&mut simulation, GameCommand::Undo).err(), Some(CommandError::NothingToUndo));
+  assert_eq!(simulation.appliances.len(), 1);
+}
+
+#[test]
+fn undo_runs_out_when_the_wave_moves_on() {
+  let mut simulation = simulation();
+  let (a, _) = open_hexes(&simulation);
+  
+  place(&mut simulation, a);
+  simulation.the_food_store.set_progress(1, 0, 0.0);
+  
+  assert!(simulation.next_undo().is_none());
+  assert_eq!(run(&mut simulation, GameCommand::Undo).err(), Some(CommandError::NothingToUndo));
+}
+
+#[test]
+fn upgrading_commits_earlier_commands() {
+  let mut simulation = simulation();
+  let (a, _) = open_hexes(&simulation);
+  
+  place(&mut simulation, a);
+  run(&mut simulation, GameCommand::Upgrade { appliance: 0, path: 0 }).unwrap();
+  
+  assert_eq!(run(&mut simulation, GameCommand::Undo).err(), Some(CommandError::NothingToUndo));
+}
+
+#[test]
+fn a_sale_whose_hex_was_built_on_is_dropped_from_the_undo_stack() {
+  let mut simulation = simulation();
+  let (a, b) = open_hexes(&simulation);
+  
+  place(&mut simulation, a);
+  place(&mut simulation, b);
+  run(&mut simulation, GameCommand::Sell { appliance: 0 }).unwrap();
+  simulation.map.set_hexagon_type(a.x, a.y, HexagonType::Blocked);
+  
+  assert_eq!(run(&mut simulation, GameCommand::Undo).err(), Some(CommandError::HexBuiltOn { q: a.x, r: a.y }));
+  
+  // The sale's gone, so the next undo takes back the placement underneath it
+  assert_eq!(simulation.next_undo().map(|entry| entry.action.name()), Some("placement"));
+  assert!(run(&mut simulation, GameCommand::Undo).is_ok());
+  assert_eq!(simulation.appliances.len(), 0);
+  assert!(is_open(&simulation, b));
+}
+
+#[test]
+fn undoing_a_sale_gives_back_the_buffs_it_was_handing_out() {
+  let mut simulation = simulation();
+  let (a, b) = open_hexes(&simulation);
+  
+  place(&mut simulation, a);
+  run(&mut simulation, GameCommand::Place { kind: ApplianceKind::CoffeeMachine, q: b.x, r: b.y }).unwrap();
+  step(&mut simulation, 1);
+  
+  let buffs = simulation.appliances[0].data().buffs.clone();
+  let life = simulation.appliances[0].current_life_expectancy();
+  let max_life = simulation.appliances[0].data().max_life_expectancy;
+  assert!(!buffs.is_empty());
+  
+  for _ in 0..3 {
+    run(&mut simulation, GameCommand::Sell { appliance: 1 }).unwrap();
+    assert!(simulation.appliances[0].data().buffs.is_empty());
+    
+    assert!(run(&mut simulation, GameCommand::Undo).is_ok());
+    assert!(simulation.appliances[0].data().buffs == buffs);
+    assert_eq!(simulation.appliances[0].current_life_expectancy(), life);
+    assert_eq!(simulation.appliances[0].data().max_life_expectancy, max_life);
+    
+    step(&mut simulation, 1);
+    assert_eq!(simulation.appliances[0].current_life_expectancy(), life);
+  }
+}